@@ -1,12 +1,18 @@
 use std::marker::PhantomData;
 use std::pin::Pin;
+use std::time::Duration;
 
 #[cfg(feature = "tracing")]
 use tracing::{Instrument, field};
 
 use crate::Result;
-use crate::component::{InjectTo, WatchFrom};
+use crate::component::rate_limit::RateLimiter;
+use crate::component::refresh::Refresher;
+use crate::component::retry::Retrier;
+use crate::component::unwind::{catch_unwind, catch_unwind_future};
+use crate::component::{InjectTo, RateLimit, RetryPolicy, WatchFrom};
 use crate::injector::{Injector, InjectorTask, Watch};
+use crate::runtime::{Runtime, Task, Timer};
 
 /// Constructs a component from smaller components.
 pub trait Constructor<T> {
@@ -95,9 +101,47 @@ macro_rules! impl_async_constructor_tuple {
 
 apply_tuples!(impl_async_constructor_tuple);
 
+/// An adapter that runs a [`Constructor`] on a blocking thread of a [`Runtime`].
+///
+/// The constructed component is wrapped in [`Result`], as the blocking task may fail to complete,
+/// in which case the error is [`Error::Panicked`](crate::Error::Panicked) if it panicked, or
+/// [`Error::Cancelled`](crate::Error::Cancelled) if it was aborted.
+#[derive(Debug, Clone)]
+pub struct BlockingConstructor<C, R> {
+    constructor: C,
+    rt: R,
+}
+
+impl<C, R> BlockingConstructor<C, R> {
+    /// Creates a new [`BlockingConstructor`] that runs `constructor` on `rt`.
+    pub const fn new(constructor: C, rt: R) -> Self {
+        Self { constructor, rt }
+    }
+}
+
+impl<C, R, T> AsyncConstructor<T> for BlockingConstructor<C, R>
+where
+    C: Constructor<T> + Send + 'static,
+    C::Constructed: Send + 'static,
+    R: Runtime,
+    T: Send + 'static,
+{
+    type Constructed = Result<C::Constructed>;
+
+    type Future = Pin<Box<dyn Future<Output = Self::Constructed> + Send>>;
+
+    fn construct(self, param: T) -> Self::Future {
+        let Self { constructor, rt } = self;
+        let task = rt.spawn_blocking(move || constructor.construct(param));
+
+        Box::pin(async move { task.join().await.map_err(R::Task::join_error) })
+    }
+}
+
 /// A adapter for [`Constructor`] types so that it implements [`InjectorTask`].
 pub struct ConstructorTask<C, T> {
     constructor: C,
+    rate_limiter: Option<RateLimiter>,
     _marker: PhantomData<fn() -> T>,
 }
 
@@ -109,9 +153,20 @@ where
     pub fn new(constructor: C) -> Self {
         Self {
             constructor,
+            rate_limiter: None,
             _marker: PhantomData,
         }
     }
+
+    /// Limits how often the constructor is run in response to changes of its dependencies.
+    #[must_use]
+    pub fn with_rate_limit<Tm>(mut self, limit: RateLimit, timer: Tm) -> Self
+    where
+        Tm: Timer,
+    {
+        self.rate_limiter = Some(RateLimiter::new(limit, timer));
+        self
+    }
 }
 
 impl<I, C, T> InjectorTask<I> for ConstructorTask<C, T>
@@ -127,17 +182,42 @@ where
     fn run(self, injector: I) -> Self::Future {
         C::Constructed::promise_to(&injector);
 
+        let Self {
+            constructor,
+            mut rate_limiter,
+            ..
+        } = self;
+
         let fut = async move {
             let mut watch = T::watch_from(&injector);
             trace!("start task");
 
             loop {
+                // Retract the outputs instead of keeping them stale while waiting for the
+                // dependencies to become available again. Other dependencies may be in error, so
+                // wait for a change rather than constructing from them.
+                if watch.any_pending() {
+                    trace!("retract");
+                    C::Constructed::retract_from(&injector);
+
+                    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+                    watch
+                        .changed()
+                        .await
+                        .inspect_err(|error| error!(%error, "error while waiting for change"))?;
+                    continue;
+                }
+
                 let input: Result<T> = watch.wait().await;
                 trace!(error = input.as_ref().err().map(field::display), "waited");
 
+                if let Some(rate_limiter) = &mut rate_limiter {
+                    rate_limiter.mark_run();
+                }
+
                 {
                     let output: Result<C::Constructed> = match input {
-                        Ok(input) => Ok(self.constructor.clone().construct(input)),
+                        Ok(input) => catch_unwind(|| constructor.clone().construct(input)),
                         Err(err) => Err(err),
                     };
                     trace!(
@@ -154,6 +234,11 @@ where
                     .await
                     .inspect_err(|error| error!(%error, "error while waiting for change"))?;
                 trace!("changed");
+
+                if let Some(rate_limiter) = &mut rate_limiter {
+                    rate_limiter.settle(&mut watch).await?;
+                    trace!("settled");
+                }
             }
         };
 
@@ -171,6 +256,9 @@ where
 /// A adapter for [`AsyncConstructor`] types so that it implements [`InjectorTask`].
 pub struct AsyncConstructorTask<C, T> {
     constructor: C,
+    rate_limiter: Option<RateLimiter>,
+    refresher: Option<Refresher>,
+    retrier: Option<Retrier>,
     _marker: PhantomData<fn() -> T>,
 }
 
@@ -182,9 +270,52 @@ where
     pub fn new(constructor: C) -> Self {
         Self {
             constructor,
+            rate_limiter: None,
+            refresher: None,
+            retrier: None,
             _marker: PhantomData,
         }
     }
+
+    /// Limits how often the constructor is run in response to changes of its dependencies.
+    #[must_use]
+    pub fn with_rate_limit<Tm>(mut self, limit: RateLimit, timer: Tm) -> Self
+    where
+        Tm: Timer,
+    {
+        self.rate_limiter = Some(RateLimiter::new(limit, timer));
+        self
+    }
+
+    /// Re-runs the constructor once `interval` has elapsed since its last run, even if its
+    /// dependencies are unchanged.
+    #[must_use]
+    pub fn with_refresh<Tm>(mut self, interval: Duration, timer: Tm) -> Self
+    where
+        Tm: Timer,
+    {
+        self.refresher = Some(Refresher::new(interval, timer));
+        self
+    }
+
+    /// Retries failed attempts of the constructor according to `policy`.
+    ///
+    /// Errors of failed attempts are not injected while the constructor is being retried.
+    /// Instead, the [`RetryStatus`](crate::component::RetryStatus) of the constructed components
+    /// is injected after every attempt.
+    #[must_use]
+    pub fn with_retry<Tm>(self, policy: RetryPolicy, timer: Tm) -> Self
+    where
+        Tm: Timer,
+    {
+        self.with_retrier(Retrier::new(policy, timer))
+    }
+
+    #[must_use]
+    pub(crate) fn with_retrier(mut self, retrier: Retrier) -> Self {
+        self.retrier = Some(retrier);
+        self
+    }
 }
 
 impl<I, C, T> InjectorTask<I> for AsyncConstructorTask<C, T>
@@ -200,34 +331,87 @@ where
 
     fn run(self, injector: I) -> Self::Future {
         C::Constructed::promise_to(&injector);
+        if self.retrier.is_some() {
+            C::Constructed::promise_retry_status_to(&injector);
+        }
+
+        let Self {
+            constructor,
+            mut rate_limiter,
+            refresher,
+            retrier,
+            ..
+        } = self;
 
         let fut = async move {
             let mut watch = T::watch_from(&injector);
             trace!("start task");
 
             loop {
+                // Retract the outputs instead of keeping them stale while waiting for the
+                // dependencies to become available again. Other dependencies may be in error, so
+                // wait for a change rather than constructing from them.
+                if watch.any_pending() {
+                    trace!("retract");
+                    C::Constructed::retract_from(&injector);
+
+                    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+                    watch
+                        .changed()
+                        .await
+                        .inspect_err(|error| error!(%error, "error while waiting for change"))?;
+                    continue;
+                }
+
                 let input: Result<T> = watch.wait().await;
                 trace!(error = input.as_ref().err().map(field::display), "waited");
 
-                {
-                    let output: Result<C::Constructed> = match input {
-                        Ok(input) => Ok(self.constructor.clone().construct(input).await),
-                        Err(err) => Err(err),
-                    };
-                    trace!(
-                        error = output.as_ref().err().map(tracing::field::display),
-                        "constructed"
-                    );
+                if let Some(rate_limiter) = &mut rate_limiter {
+                    rate_limiter.mark_run();
+                }
 
-                    C::Constructed::inject_to(output, &injector);
+                match (input, &retrier) {
+                    (Ok(input), Some(retrier)) => {
+                        if !retrier
+                            .construct(&constructor, input, &mut watch, injector.clone())
+                            .await?
+                        {
+                            continue;
+                        }
+                    }
+                    (input, _) => {
+                        let output: Result<C::Constructed> = match input {
+                            Ok(input) => {
+                                catch_unwind_future(constructor.clone().construct(input)).await
+                            }
+                            Err(err) => Err(err),
+                        };
+                        trace!(
+                            error = output.as_ref().err().map(tracing::field::display),
+                            "constructed"
+                        );
+
+                        C::Constructed::inject_to(output, &injector);
+                    }
                 }
 
+                let changed = match &refresher {
+                    Some(refresher) => refresher.changed_or_due(&mut watch).await,
+                    None => watch.changed().await.map(|()| true),
+                };
                 #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
-                watch
-                    .changed()
-                    .await
+                let changed = changed
                     .inspect_err(|error| error!(%error, "error while waiting for change"))?;
+                if !changed {
+                    trace!("refresh");
+                    continue;
+                }
                 trace!("changed");
+
+                if let Some(rate_limiter) = &mut rate_limiter {
+                    rate_limiter.settle(&mut watch).await?;
+                    trace!("settled");
+                }
             }
         };
 
@@ -0,0 +1,78 @@
+use std::any::TypeId;
+use std::time::Instant;
+
+use crate::component::{InjectTo, RetryStatus};
+use crate::{Error, Result};
+
+/// A wrapper around components that expire at a given instant.
+///
+/// Returning `Expiring<T>` from a constructor injects the components that make up `T` with
+/// [`Injector::inject_expiring`](crate::injector::Injector::inject_expiring), so they revert to
+/// pending once `expires_at` has passed. Errors are injected without expiry. Expiry has to be
+/// driven by the injector, e.g. with
+/// [`SimpleContainerBuilder::with_expiry`](crate::container::SimpleContainerBuilder::with_expiry),
+/// and [`SimpleContainerBuilder::try_build`](crate::container::SimpleContainerBuilder::try_build)
+/// fails if nothing drives it. Injectors that do not support expiry inject the components as-is.
+///
+/// An expired component is not rebuilt by itself. To keep it available, register its constructor
+/// with
+/// [`with_refreshing_constructor`](crate::container::SimpleContainerBuilder::with_refreshing_constructor)
+/// at an interval shorter than its lifetime.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expiring<T> {
+    /// The wrapped components.
+    pub value: T,
+    /// The instant at which the components expire.
+    pub expires_at: Instant,
+}
+
+impl<T> Expiring<T> {
+    /// Wraps components that expire at `expires_at` in a new `Expiring`.
+    pub const fn new(value: T, expires_at: Instant) -> Self {
+        Self { value, expires_at }
+    }
+}
+
+impl<I, T> InjectTo<I> for Expiring<T>
+where
+    T: InjectTo<I>,
+{
+    fn promise_to(injector: &I) {
+        T::promise_to(injector);
+    }
+
+    fn inject_to(result: Result<Self>, injector: &I) {
+        match result {
+            Ok(Self { value, expires_at }) => T::inject_expiring_to(value, injector, expires_at),
+            Err(err) => T::inject_to(Err(err), injector),
+        }
+    }
+
+    fn inject_expiring_to(value: Self, injector: &I, expires_at: Instant) {
+        T::inject_expiring_to(value.value, injector, expires_at.min(value.expires_at));
+    }
+
+    fn retract_from(injector: &I) {
+        T::retract_from(injector);
+    }
+
+    fn is_expiring() -> bool {
+        true
+    }
+
+    fn error_of(value: &Self) -> Option<&Error> {
+        T::error_of(&value.value)
+    }
+
+    fn promise_retry_status_to(injector: &I) {
+        T::promise_retry_status_to(injector);
+    }
+
+    fn inject_retry_status_to(status: &RetryStatus<Self>, injector: &I) {
+        T::inject_retry_status_to(&status.cast(), injector);
+    }
+
+    fn provides(types: &mut Vec<(TypeId, &'static str)>) {
+        T::provides(types);
+    }
+}
@@ -0,0 +1,741 @@
+//! Thread-local variants of the component traits, for components that are not [`Send`].
+
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::Arc;
+
+#[cfg(feature = "tracing")]
+use tracing::{Instrument, field};
+
+use crate::component::unwind::{catch_unwind, catch_unwind_future};
+use crate::component::{
+    Component, ComponentWatch, Constructor, Current, CurrentWatch, OptionalWatch, ResultWatch,
+    WaitAlways, WaitAlwaysWatch, WaitOk, WaitOkWatch,
+};
+use crate::injector::{LocalInjector, LocalInjectorTask, LocalWatch};
+use crate::{Error, Result};
+
+/// A component or aggregate of components that can be watched for its values from a
+/// [`LocalInjector`].
+pub trait LocalWatchFrom<I>: Sized {
+    /// The watch returned by [`watch_from`](Self::watch_from) method.
+    type Watch: LocalWatch<Ty = Self>;
+
+    /// Watches for values of components that make up this types from the injector.
+    fn watch_from(injector: &I) -> Self::Watch;
+}
+
+/// A component or aggregate of components that can be injected into a [`LocalInjector`].
+pub trait LocalInjectTo<I>: Sized {
+    /// Tells the injector that the components that make up this type might be injected to it.
+    fn promise_to(injector: &I);
+
+    /// Injects the components that make up this type to the injector.
+    fn inject_to(result: Result<Self>, injector: &I);
+
+    /// Reverts the components that make up this type to pending in the injector, e.g. because
+    /// the components they were constructed from are no longer available.
+    fn retract_from(injector: &I);
+}
+
+impl<I, T> LocalWatchFrom<I> for Rc<T>
+where
+    I: LocalInjector,
+    T: ?Sized + 'static,
+{
+    type Watch = I::Watch<Self>;
+
+    fn watch_from(injector: &I) -> Self::Watch {
+        injector.watch()
+    }
+}
+
+impl<I, T> LocalInjectTo<I> for Rc<T>
+where
+    I: LocalInjector,
+    T: ?Sized + 'static,
+{
+    fn promise_to(injector: &I) {
+        injector.define::<Self>();
+    }
+
+    fn inject_to(result: Result<Self>, injector: &I) {
+        injector.inject(result.map_err(Error::propagated_through::<Self>));
+    }
+
+    fn retract_from(injector: &I) {
+        injector.retract::<Self>();
+    }
+}
+
+impl<I, T> LocalWatchFrom<I> for Arc<T>
+where
+    I: LocalInjector,
+    T: ?Sized + 'static,
+{
+    type Watch = I::Watch<Self>;
+
+    fn watch_from(injector: &I) -> Self::Watch {
+        injector.watch()
+    }
+}
+
+impl<I, T> LocalInjectTo<I> for Arc<T>
+where
+    I: LocalInjector,
+    T: ?Sized + 'static,
+{
+    fn promise_to(injector: &I) {
+        injector.define::<Self>();
+    }
+
+    fn inject_to(result: Result<Self>, injector: &I) {
+        injector.inject(result.map_err(Error::propagated_through::<Self>));
+    }
+
+    fn retract_from(injector: &I) {
+        injector.retract::<Self>();
+    }
+}
+
+impl<I> LocalWatchFrom<I> for () {
+    type Watch = ();
+
+    fn watch_from(_injector: &I) -> Self::Watch {}
+}
+
+impl<I> LocalInjectTo<I> for () {
+    fn promise_to(_injector: &I) {}
+
+    fn inject_to(_result: Result<Self>, _injector: &I) {}
+
+    fn retract_from(_injector: &I) {}
+}
+
+impl<I, T> LocalWatchFrom<I> for Component<T>
+where
+    I: LocalInjector,
+    T: Clone + 'static,
+{
+    type Watch = ComponentWatch<I::Watch<T>>;
+
+    fn watch_from(injector: &I) -> Self::Watch {
+        ComponentWatch::new(injector.watch())
+    }
+}
+
+impl<I, T> LocalInjectTo<I> for Component<T>
+where
+    I: LocalInjector,
+    T: Clone + 'static,
+{
+    fn promise_to(injector: &I) {
+        injector.define::<T>();
+    }
+
+    fn inject_to(result: Result<Self>, injector: &I) {
+        injector.inject(result.map(|v| v.0).map_err(Error::propagated_through::<T>));
+    }
+
+    fn retract_from(injector: &I) {
+        injector.retract::<T>();
+    }
+}
+
+impl<I, T> LocalWatchFrom<I> for Option<T>
+where
+    T: LocalWatchFrom<I>,
+{
+    type Watch = OptionalWatch<T::Watch>;
+
+    fn watch_from(injector: &I) -> Self::Watch {
+        OptionalWatch::new(T::watch_from(injector))
+    }
+}
+
+impl<I, T> LocalInjectTo<I> for Option<T>
+where
+    T: LocalInjectTo<I>,
+{
+    fn promise_to(injector: &I) {
+        T::promise_to(injector);
+    }
+
+    fn inject_to(result: Result<Self>, injector: &I) {
+        match result {
+            Ok(Some(value)) => T::inject_to(Ok(value), injector),
+            Ok(None) => {}
+            Err(err) => T::inject_to(Err(err), injector),
+        }
+    }
+
+    fn retract_from(injector: &I) {
+        T::retract_from(injector);
+    }
+}
+
+impl<I, T> LocalWatchFrom<I> for Result<T>
+where
+    T: LocalWatchFrom<I>,
+{
+    type Watch = ResultWatch<T::Watch>;
+
+    fn watch_from(injector: &I) -> Self::Watch {
+        ResultWatch::new(T::watch_from(injector))
+    }
+}
+
+impl<I, T> LocalInjectTo<I> for Result<T>
+where
+    T: LocalInjectTo<I>,
+{
+    fn promise_to(injector: &I) {
+        T::promise_to(injector);
+    }
+
+    fn inject_to(result: Result<Self>, injector: &I) {
+        T::inject_to(result.flatten(), injector);
+    }
+
+    fn retract_from(injector: &I) {
+        T::retract_from(injector);
+    }
+}
+
+impl<I, T> LocalWatchFrom<I> for Current<T>
+where
+    T: LocalWatchFrom<I>,
+{
+    type Watch = CurrentWatch<T::Watch>;
+
+    fn watch_from(injector: &I) -> Self::Watch {
+        CurrentWatch::new(T::watch_from(injector))
+    }
+}
+
+impl<I, T> LocalWatchFrom<I> for WaitAlways<T>
+where
+    T: LocalWatchFrom<I>,
+{
+    type Watch = WaitAlwaysWatch<T::Watch>;
+
+    fn watch_from(injector: &I) -> Self::Watch {
+        WaitAlwaysWatch::new(T::watch_from(injector))
+    }
+}
+
+impl<I, T> LocalWatchFrom<I> for WaitOk<T>
+where
+    T: LocalWatchFrom<I>,
+{
+    type Watch = WaitOkWatch<T::Watch>;
+
+    fn watch_from(injector: &I) -> Self::Watch {
+        WaitOkWatch::new(T::watch_from(injector))
+    }
+}
+
+macro_rules! impl_local_composite_tuple {
+    ($($ty:ident),*) => {
+        #[allow(non_snake_case)]
+        #[allow(clippy::too_many_arguments)]
+        #[allow(clippy::type_complexity)]
+        impl<I, $($ty,)*> LocalWatchFrom<I> for ($($ty,)*)
+        where
+            I: LocalInjector,
+            $($ty: LocalWatchFrom<I>,)*
+        {
+            type Watch = ($($ty::Watch,)*);
+
+            fn watch_from(injector: &I) -> Self::Watch {
+                ($($ty::watch_from(injector),)*)
+            }
+        }
+
+        #[allow(non_snake_case)]
+        #[allow(clippy::too_many_arguments)]
+        #[allow(clippy::type_complexity)]
+        #[allow(clippy::redundant_clone)]
+        impl<I, $($ty,)*> LocalInjectTo<I> for ($($ty,)*)
+        where
+            I: LocalInjector,
+            $($ty: LocalInjectTo<I>,)*
+        {
+            fn promise_to(injector: &I) {
+                $($ty::promise_to(injector);)*
+            }
+
+            fn inject_to(result: Result<Self>, injector: &I) {
+                match result {
+                    Ok(($($ty,)*)) => {
+                        $($ty::inject_to(Ok($ty), injector);)*
+                    },
+                    Err(err) => {
+                        $($ty::inject_to(Err(err.clone()), injector);)*
+                    }
+                }
+            }
+
+            fn retract_from(injector: &I) {
+                $($ty::retract_from(injector);)*
+            }
+        }
+    }
+}
+
+apply_tuples!(impl_local_composite_tuple);
+
+impl<W> LocalWatch for ComponentWatch<W>
+where
+    W: LocalWatch,
+{
+    type Ty = Component<W::Ty>;
+
+    fn current(&self) -> Result<Self::Ty> {
+        self.0.current().map(Component)
+    }
+
+    fn current_optional(&self) -> Result<Option<Self::Ty>> {
+        let value = self.0.current_optional()?;
+        Ok(value.map(Component))
+    }
+
+    async fn wait(&mut self) -> Result<Self::Ty> {
+        self.0.wait().await.map(Component)
+    }
+
+    async fn wait_optional(&mut self) -> Result<Option<Self::Ty>> {
+        let value = self.0.wait_optional().await?;
+        Ok(value.map(Component))
+    }
+
+    async fn wait_always(&mut self) -> Result<Self::Ty> {
+        self.0.wait_always().await.map(Component)
+    }
+
+    async fn wait_ok(&mut self) -> Result<Self::Ty> {
+        self.0.wait_ok().await.map(Component)
+    }
+
+    async fn changed(&mut self) -> Result<()> {
+        self.0.changed().await
+    }
+}
+
+impl<W> LocalWatch for OptionalWatch<W>
+where
+    W: LocalWatch,
+{
+    type Ty = Option<W::Ty>;
+
+    fn current(&self) -> Result<Self::Ty> {
+        self.0.current_optional()
+    }
+
+    fn current_optional(&self) -> Result<Option<Self::Ty>> {
+        Ok(Some(self.0.current_optional()?))
+    }
+
+    async fn wait(&mut self) -> Result<Self::Ty> {
+        self.0.wait_optional().await
+    }
+
+    async fn wait_optional(&mut self) -> Result<Option<Self::Ty>> {
+        Ok(Some(self.0.wait_optional().await?))
+    }
+
+    async fn wait_always(&mut self) -> Result<Self::Ty> {
+        Ok(Some(self.0.wait_always().await?))
+    }
+
+    async fn wait_ok(&mut self) -> Result<Self::Ty> {
+        Ok(Some(self.0.wait_ok().await?))
+    }
+
+    async fn changed(&mut self) -> Result<()> {
+        self.0.changed().await
+    }
+}
+
+impl<W> LocalWatch for ResultWatch<W>
+where
+    W: LocalWatch,
+{
+    type Ty = Result<W::Ty>;
+
+    fn current(&self) -> Result<Self::Ty> {
+        Ok(self.0.current())
+    }
+
+    fn current_optional(&self) -> Result<Option<Self::Ty>> {
+        Ok(Some(self.0.current()))
+    }
+
+    async fn wait(&mut self) -> Result<Self::Ty> {
+        Ok(self.0.wait().await)
+    }
+
+    async fn wait_optional(&mut self) -> Result<Option<Self::Ty>> {
+        Ok(Some(self.0.wait().await))
+    }
+
+    async fn wait_always(&mut self) -> Result<Self::Ty> {
+        Ok(self.0.wait_always().await)
+    }
+
+    async fn wait_ok(&mut self) -> Result<Self::Ty> {
+        Ok(self.0.wait_ok().await)
+    }
+
+    async fn changed(&mut self) -> Result<()> {
+        self.0.changed().await
+    }
+}
+
+impl<W> LocalWatch for CurrentWatch<W>
+where
+    W: LocalWatch,
+{
+    type Ty = Current<W::Ty>;
+
+    fn current(&self) -> Result<Self::Ty> {
+        self.0.current().map(Current)
+    }
+
+    fn current_optional(&self) -> Result<Option<Self::Ty>> {
+        let value = self.0.current_optional()?;
+        Ok(value.map(Current))
+    }
+
+    async fn wait(&mut self) -> Result<Self::Ty> {
+        self.0.current().map(Current)
+    }
+
+    async fn wait_optional(&mut self) -> Result<Option<Self::Ty>> {
+        let value = self.0.current_optional()?;
+        Ok(value.map(Current))
+    }
+
+    async fn wait_always(&mut self) -> Result<Self::Ty> {
+        self.0.current().map(Current)
+    }
+
+    async fn wait_ok(&mut self) -> Result<Self::Ty> {
+        self.0.current().map(Current)
+    }
+
+    fn changed(&mut self) -> impl Future<Output = Result<()>> {
+        std::future::pending()
+    }
+
+    // The value is read without waiting, so it never holds back the dependent.
+    fn any_pending(&self) -> bool {
+        false
+    }
+}
+
+impl<W> LocalWatch for WaitAlwaysWatch<W>
+where
+    W: LocalWatch,
+{
+    type Ty = WaitAlways<W::Ty>;
+
+    fn current(&self) -> Result<Self::Ty> {
+        self.0.current().map(WaitAlways)
+    }
+
+    fn current_optional(&self) -> Result<Option<Self::Ty>> {
+        let value = self.0.current_optional()?;
+        Ok(value.map(WaitAlways))
+    }
+
+    async fn wait(&mut self) -> Result<Self::Ty> {
+        self.0.wait_always().await.map(WaitAlways)
+    }
+
+    async fn wait_optional(&mut self) -> Result<Option<Self::Ty>> {
+        let value = self.0.wait_always().await?;
+        Ok(Some(WaitAlways(value)))
+    }
+
+    async fn wait_always(&mut self) -> Result<Self::Ty> {
+        self.0.wait_always().await.map(WaitAlways)
+    }
+
+    async fn wait_ok(&mut self) -> Result<Self::Ty> {
+        self.0.wait_ok().await.map(WaitAlways)
+    }
+
+    async fn changed(&mut self) -> Result<()> {
+        self.0.changed().await
+    }
+}
+
+impl<W> LocalWatch for WaitOkWatch<W>
+where
+    W: LocalWatch,
+{
+    type Ty = WaitOk<W::Ty>;
+
+    fn current(&self) -> Result<Self::Ty> {
+        self.0.current().map(WaitOk)
+    }
+
+    fn current_optional(&self) -> Result<Option<Self::Ty>> {
+        let value = self.0.current_optional()?;
+        Ok(value.map(WaitOk))
+    }
+
+    async fn wait(&mut self) -> Result<Self::Ty> {
+        self.0.wait_always().await.map(WaitOk)
+    }
+
+    async fn wait_optional(&mut self) -> Result<Option<Self::Ty>> {
+        let value = self.0.wait_always().await?;
+        Ok(Some(WaitOk(value)))
+    }
+
+    async fn wait_always(&mut self) -> Result<Self::Ty> {
+        self.0.wait_always().await.map(WaitOk)
+    }
+
+    async fn wait_ok(&mut self) -> Result<Self::Ty> {
+        self.0.wait_ok().await.map(WaitOk)
+    }
+
+    async fn changed(&mut self) -> Result<()> {
+        self.0.changed().await
+    }
+}
+
+/// Asynchronously constructs a component from smaller components, with a future that is not
+/// necessarily [`Send`].
+pub trait LocalAsyncConstructor<T> {
+    /// The type of the constructed component.
+    type Constructed;
+
+    /// The future returned by [`construct`](Self::construct) method.
+    type Future: Future<Output = Self::Constructed>;
+
+    /// Calls the constructor.
+    fn construct(self, param: T) -> Self::Future;
+}
+
+impl<F, Fut> LocalAsyncConstructor<()> for F
+where
+    F: FnOnce() -> Fut,
+    Fut: Future,
+{
+    type Constructed = Fut::Output;
+
+    type Future = Fut;
+
+    fn construct(self, _param: ()) -> Self::Future {
+        self()
+    }
+}
+
+macro_rules! impl_local_async_constructor_tuple {
+    ($($ty:ident),*) => {
+        #[allow(non_snake_case)]
+        impl<F, Fut, $($ty,)*> LocalAsyncConstructor<($($ty,)*)> for F
+        where
+            F: FnOnce($($ty,)*) -> Fut,
+            Fut: Future,
+        {
+            type Constructed = Fut::Output;
+
+            type Future = Fut;
+
+            fn construct(self, param: ($($ty,)*)) -> Self::Future {
+                let ($($ty,)*) = param;
+                self($($ty,)*)
+            }
+        }
+    };
+}
+
+apply_tuples!(impl_local_async_constructor_tuple);
+
+/// A adapter for [`Constructor`] types so that it implements [`LocalInjectorTask`].
+pub struct LocalConstructorTask<C, T> {
+    constructor: C,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<C, T> LocalConstructorTask<C, T>
+where
+    C: Constructor<T>,
+{
+    /// Creates a new [`LocalConstructorTask`] from a [`Constructor`].
+    pub const fn new(constructor: C) -> Self {
+        Self {
+            constructor,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<I, C, T> LocalInjectorTask<I> for LocalConstructorTask<C, T>
+where
+    I: LocalInjector + 'static,
+    T: LocalWatchFrom<I> + 'static,
+    T::Watch: 'static,
+    C: Constructor<T> + Clone + 'static,
+    C::Constructed: LocalInjectTo<I>,
+{
+    type Future = Pin<Box<dyn Future<Output = Result<()>>>>;
+
+    fn run(self, injector: I) -> Self::Future {
+        C::Constructed::promise_to(&injector);
+
+        let Self { constructor, .. } = self;
+
+        let fut = async move {
+            let mut watch = T::watch_from(&injector);
+            trace!("start task");
+
+            loop {
+                // Retract the outputs instead of keeping them stale while waiting for the
+                // dependencies to become available again. Other dependencies may be in error, so
+                // wait for a change rather than constructing from them.
+                if watch.any_pending() {
+                    trace!("retract");
+                    C::Constructed::retract_from(&injector);
+
+                    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+                    watch
+                        .changed()
+                        .await
+                        .inspect_err(|error| error!(%error, "error while waiting for change"))?;
+                    continue;
+                }
+
+                let input: Result<T> = watch.wait().await;
+                trace!(error = input.as_ref().err().map(field::display), "waited");
+
+                {
+                    let output: Result<C::Constructed> = match input {
+                        Ok(input) => catch_unwind(|| constructor.clone().construct(input)),
+                        Err(err) => Err(err),
+                    };
+                    trace!(
+                        error = output.as_ref().err().map(tracing::field::display),
+                        "constructed"
+                    );
+
+                    C::Constructed::inject_to(output, &injector);
+                }
+
+                #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+                watch
+                    .changed()
+                    .await
+                    .inspect_err(|error| error!(%error, "error while waiting for change"))?;
+                trace!("changed");
+            }
+        };
+
+        #[cfg(feature = "tracing")]
+        let fut = fut.instrument(tracing::debug_span!(
+            "local_constructor_task",
+            dependency = std::any::type_name::<T>(),
+            constructed = std::any::type_name::<C::Constructed>(),
+        ));
+
+        Box::pin(fut)
+    }
+}
+
+/// A adapter for [`LocalAsyncConstructor`] types so that it implements [`LocalInjectorTask`].
+pub struct LocalAsyncConstructorTask<C, T> {
+    constructor: C,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<C, T> LocalAsyncConstructorTask<C, T>
+where
+    C: LocalAsyncConstructor<T>,
+{
+    /// Creates a new [`LocalAsyncConstructorTask`] from a [`LocalAsyncConstructor`].
+    pub const fn new(constructor: C) -> Self {
+        Self {
+            constructor,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<I, C, T> LocalInjectorTask<I> for LocalAsyncConstructorTask<C, T>
+where
+    I: LocalInjector + 'static,
+    T: LocalWatchFrom<I> + 'static,
+    T::Watch: 'static,
+    C: LocalAsyncConstructor<T> + Clone + 'static,
+    C::Constructed: LocalInjectTo<I>,
+{
+    type Future = Pin<Box<dyn Future<Output = Result<()>>>>;
+
+    fn run(self, injector: I) -> Self::Future {
+        C::Constructed::promise_to(&injector);
+
+        let Self { constructor, .. } = self;
+
+        let fut = async move {
+            let mut watch = T::watch_from(&injector);
+            trace!("start task");
+
+            loop {
+                // Retract the outputs instead of keeping them stale while waiting for the
+                // dependencies to become available again. Other dependencies may be in error, so
+                // wait for a change rather than constructing from them.
+                if watch.any_pending() {
+                    trace!("retract");
+                    C::Constructed::retract_from(&injector);
+
+                    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+                    watch
+                        .changed()
+                        .await
+                        .inspect_err(|error| error!(%error, "error while waiting for change"))?;
+                    continue;
+                }
+
+                let input: Result<T> = watch.wait().await;
+                trace!(error = input.as_ref().err().map(field::display), "waited");
+
+                {
+                    let output: Result<C::Constructed> = match input {
+                        Ok(input) => {
+                            catch_unwind_future(constructor.clone().construct(input)).await
+                        }
+                        Err(err) => Err(err),
+                    };
+                    trace!(
+                        error = output.as_ref().err().map(tracing::field::display),
+                        "constructed"
+                    );
+
+                    C::Constructed::inject_to(output, &injector);
+                }
+
+                #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+                watch
+                    .changed()
+                    .await
+                    .inspect_err(|error| error!(%error, "error while waiting for change"))?;
+                trace!("changed");
+            }
+        };
+
+        #[cfg(feature = "tracing")]
+        let fut = fut.instrument(tracing::debug_span!(
+            "local_async_constructor_task",
+            dependency = std::any::type_name::<T>(),
+            constructed = std::any::type_name::<C::Constructed>(),
+        ));
+
+        Box::pin(fut)
+    }
+}
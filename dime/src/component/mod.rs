@@ -1,12 +1,40 @@
 //! Type-safe component system.
 
+use std::any::{TypeId, type_name};
 use std::sync::Arc;
+use std::time::Instant;
 
-use crate::Result;
 use crate::injector::{Injector, Watch};
+use crate::{Error, Result};
 
 mod constructor;
-pub use constructor::{AsyncConstructor, AsyncConstructorTask, Constructor, ConstructorTask};
+pub use constructor::{
+    AsyncConstructor, AsyncConstructorTask, BlockingConstructor, Constructor, ConstructorTask,
+};
+
+mod expiring;
+pub use expiring::Expiring;
+
+mod local;
+pub use local::{
+    LocalAsyncConstructor, LocalAsyncConstructorTask, LocalConstructorTask, LocalInjectTo,
+    LocalWatchFrom,
+};
+
+mod rate_limit;
+pub use rate_limit::RateLimit;
+
+mod refresh;
+
+mod retry;
+pub(crate) use retry::Retrier;
+pub use retry::{RetryPhase, RetryPolicy, RetryStatus};
+
+mod select;
+pub use select::Select;
+
+mod timer;
+pub(crate) mod unwind;
 
 /// A component or aggregate of components that can be watched for its values from an injector.
 pub trait WatchFrom<I>: Sized {
@@ -15,6 +43,11 @@ pub trait WatchFrom<I>: Sized {
 
     /// Watches for values of components that make up this types from the injector.
     fn watch_from(injector: &I) -> Self::Watch;
+
+    /// Appends the IDs and names of the component types this type waits on to `types`.
+    ///
+    /// Components that are read without waiting, e.g. through [`Current`], are not included.
+    fn dependencies(types: &mut Vec<(TypeId, &'static str)>);
 }
 
 /// A component or aggregate of components that can be injected into an injector.
@@ -24,6 +57,43 @@ pub trait InjectTo<I>: Sized {
 
     /// Injects the components that make up this type to the injector.
     fn inject_to(result: Result<Self>, injector: &I);
+
+    /// Injects the components that make up this type to the injector, expiring at `expires_at`.
+    ///
+    /// See [`Injector::inject_expiring`]. By default, the components are injected as if by
+    /// [`inject_to`](Self::inject_to), without expiry.
+    fn inject_expiring_to(value: Self, injector: &I, expires_at: Instant) {
+        let _ = expires_at;
+        Self::inject_to(Ok(value), injector);
+    }
+
+    /// Reverts the components that make up this type to pending in the injector, e.g. because
+    /// the components they were constructed from are no longer available.
+    fn retract_from(injector: &I);
+
+    /// Returns `true` if this type injects components that expire, e.g. through [`Expiring`], in
+    /// which case the injector has to drive their expiry.
+    ///
+    /// By default, the components do not expire.
+    fn is_expiring() -> bool {
+        false
+    }
+
+    /// Returns the error that injecting `value` would inject, if any.
+    ///
+    /// Retrying constructors use this to tell failed attempts apart, so aggregates have to return
+    /// the error of any of their components.
+    fn error_of(value: &Self) -> Option<&Error>;
+
+    /// Tells the injector that the [`RetryStatus`] of the components that make up this type
+    /// might be injected to it.
+    fn promise_retry_status_to(injector: &I);
+
+    /// Injects `status` as the [`RetryStatus`] of each component that makes up this type.
+    fn inject_retry_status_to(status: &RetryStatus<Self>, injector: &I);
+
+    /// Appends the IDs and names of the component types that make up this type to `types`.
+    fn provides(types: &mut Vec<(TypeId, &'static str)>);
 }
 
 impl<I, T> WatchFrom<I> for Arc<T>
@@ -36,6 +106,10 @@ where
     fn watch_from(injector: &I) -> Self::Watch {
         injector.watch()
     }
+
+    fn dependencies(types: &mut Vec<(TypeId, &'static str)>) {
+        types.push((TypeId::of::<Self>(), type_name::<Self>()));
+    }
 }
 
 impl<I, T> InjectTo<I> for Arc<T>
@@ -48,7 +122,31 @@ where
     }
 
     fn inject_to(result: Result<Self>, injector: &I) {
-        injector.inject(result);
+        injector.inject(result.map_err(Error::propagated_through::<Self>));
+    }
+
+    fn inject_expiring_to(value: Self, injector: &I, expires_at: Instant) {
+        injector.inject_expiring(Ok(value), expires_at);
+    }
+
+    fn retract_from(injector: &I) {
+        injector.retract::<Self>();
+    }
+
+    fn error_of(_value: &Self) -> Option<&Error> {
+        None
+    }
+
+    fn promise_retry_status_to(injector: &I) {
+        injector.define::<RetryStatus<Self>>();
+    }
+
+    fn inject_retry_status_to(status: &RetryStatus<Self>, injector: &I) {
+        injector.inject(Ok(status.clone()));
+    }
+
+    fn provides(types: &mut Vec<(TypeId, &'static str)>) {
+        types.push((TypeId::of::<Self>(), type_name::<Self>()));
     }
 }
 
@@ -58,12 +156,26 @@ impl<I> WatchFrom<I> for () {
     type Watch = ();
 
     fn watch_from(_injector: &I) -> Self::Watch {}
+
+    fn dependencies(_types: &mut Vec<(TypeId, &'static str)>) {}
 }
 
 impl<I> InjectTo<I> for () {
     fn promise_to(_injector: &I) {}
 
     fn inject_to(_result: Result<Self>, _injector: &I) {}
+
+    fn retract_from(_injector: &I) {}
+
+    fn error_of(_value: &Self) -> Option<&Error> {
+        None
+    }
+
+    fn promise_retry_status_to(_injector: &I) {}
+
+    fn inject_retry_status_to(_status: &RetryStatus<Self>, _injector: &I) {}
+
+    fn provides(_types: &mut Vec<(TypeId, &'static str)>) {}
 }
 
 /// A wrapper around a single component type.
@@ -81,6 +193,10 @@ where
     fn watch_from(injector: &I) -> Self::Watch {
         ComponentWatch::new(injector.watch())
     }
+
+    fn dependencies(types: &mut Vec<(TypeId, &'static str)>) {
+        types.push((TypeId::of::<T>(), type_name::<T>()));
+    }
 }
 
 impl<I, T> InjectTo<I> for Component<T>
@@ -94,7 +210,115 @@ where
     }
 
     fn inject_to(result: Result<Self>, injector: &I) {
-        injector.inject(result.map(|v| v.0));
+        injector.inject(result.map(|v| v.0).map_err(Error::propagated_through::<T>));
+    }
+
+    fn inject_expiring_to(value: Self, injector: &I, expires_at: Instant) {
+        injector.inject_expiring(Ok(value.0), expires_at);
+    }
+
+    fn retract_from(injector: &I) {
+        injector.retract::<T>();
+    }
+
+    fn error_of(_value: &Self) -> Option<&Error> {
+        None
+    }
+
+    fn promise_retry_status_to(injector: &I) {
+        injector.define::<RetryStatus<T>>();
+    }
+
+    fn inject_retry_status_to(status: &RetryStatus<Self>, injector: &I) {
+        injector.inject(Ok(status.cast::<T>()));
+    }
+
+    fn provides(types: &mut Vec<(TypeId, &'static str)>) {
+        types.push((TypeId::of::<T>(), type_name::<T>()));
+    }
+}
+
+/// A wrapper around a single component type that is stored and retrieved as an [`Arc`].
+///
+/// Unlike [`Component`], the wrapped type does not need to implement [`Clone`], and reading it
+/// never clones the underlying value. Both wrappers refer to the same state, so a component
+/// injected as `Component<T>` can be watched as `Shared<T>`, and vice versa if `T` is [`Clone`].
+#[derive(Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Shared<T>(pub Arc<T>);
+
+impl<T> Shared<T> {
+    /// Wraps a value in a new `Shared`.
+    pub fn new(value: T) -> Self {
+        Self(Arc::new(value))
+    }
+}
+
+impl<T> Clone for Shared<T> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<T> std::ops::Deref for Shared<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<I, T> WatchFrom<I> for Shared<T>
+where
+    I: Injector,
+    T: Send + Sync + 'static,
+    I::SharedWatch<T>: Send,
+{
+    type Watch = SharedComponentWatch<I::SharedWatch<T>>;
+
+    fn watch_from(injector: &I) -> Self::Watch {
+        SharedComponentWatch::new(injector.watch_shared())
+    }
+
+    fn dependencies(types: &mut Vec<(TypeId, &'static str)>) {
+        types.push((TypeId::of::<T>(), type_name::<T>()));
+    }
+}
+
+impl<I, T> InjectTo<I> for Shared<T>
+where
+    I: Injector,
+    T: Send + Sync + 'static,
+{
+    fn promise_to(injector: &I) {
+        injector.define::<T>();
+    }
+
+    fn inject_to(result: Result<Self>, injector: &I) {
+        injector.inject_shared(result.map(|v| v.0).map_err(Error::propagated_through::<T>));
+    }
+
+    fn inject_expiring_to(value: Self, injector: &I, expires_at: Instant) {
+        injector.inject_shared_expiring(Ok(value.0), expires_at);
+    }
+
+    fn retract_from(injector: &I) {
+        injector.retract::<T>();
+    }
+
+    fn error_of(_value: &Self) -> Option<&Error> {
+        None
+    }
+
+    fn promise_retry_status_to(injector: &I) {
+        injector.define::<RetryStatus<T>>();
+    }
+
+    fn inject_retry_status_to(status: &RetryStatus<Self>, injector: &I) {
+        injector.inject(Ok(status.cast::<T>()));
+    }
+
+    fn provides(types: &mut Vec<(TypeId, &'static str)>) {
+        types.push((TypeId::of::<T>(), type_name::<T>()));
     }
 }
 
@@ -108,6 +332,10 @@ where
     fn watch_from(injector: &I) -> Self::Watch {
         OptionalWatch::new(T::watch_from(injector))
     }
+
+    fn dependencies(types: &mut Vec<(TypeId, &'static str)>) {
+        T::dependencies(types);
+    }
 }
 
 impl<I, T> InjectTo<I> for Option<T>
@@ -125,6 +353,36 @@ where
             Err(err) => T::inject_to(Err(err), injector),
         }
     }
+
+    fn inject_expiring_to(value: Self, injector: &I, expires_at: Instant) {
+        if let Some(value) = value {
+            T::inject_expiring_to(value, injector, expires_at);
+        }
+    }
+
+    fn retract_from(injector: &I) {
+        T::retract_from(injector);
+    }
+
+    fn is_expiring() -> bool {
+        T::is_expiring()
+    }
+
+    fn error_of(value: &Self) -> Option<&Error> {
+        value.as_ref().and_then(T::error_of)
+    }
+
+    fn promise_retry_status_to(injector: &I) {
+        T::promise_retry_status_to(injector);
+    }
+
+    fn inject_retry_status_to(status: &RetryStatus<Self>, injector: &I) {
+        T::inject_retry_status_to(&status.cast(), injector);
+    }
+
+    fn provides(types: &mut Vec<(TypeId, &'static str)>) {
+        T::provides(types);
+    }
 }
 
 impl<I, T> WatchFrom<I> for Result<T>
@@ -137,6 +395,10 @@ where
     fn watch_from(injector: &I) -> Self::Watch {
         ResultWatch::new(T::watch_from(injector))
     }
+
+    fn dependencies(types: &mut Vec<(TypeId, &'static str)>) {
+        T::dependencies(types);
+    }
 }
 
 impl<I, T> InjectTo<I> for Result<T>
@@ -150,6 +412,40 @@ where
     fn inject_to(result: Result<Self>, injector: &I) {
         T::inject_to(result.flatten(), injector);
     }
+
+    fn inject_expiring_to(value: Self, injector: &I, expires_at: Instant) {
+        match value {
+            Ok(value) => T::inject_expiring_to(value, injector, expires_at),
+            Err(err) => T::inject_to(Err(err), injector),
+        }
+    }
+
+    fn retract_from(injector: &I) {
+        T::retract_from(injector);
+    }
+
+    fn is_expiring() -> bool {
+        T::is_expiring()
+    }
+
+    fn error_of(value: &Self) -> Option<&Error> {
+        match value {
+            Ok(value) => T::error_of(value),
+            Err(err) => Some(err),
+        }
+    }
+
+    fn promise_retry_status_to(injector: &I) {
+        T::promise_retry_status_to(injector);
+    }
+
+    fn inject_retry_status_to(status: &RetryStatus<Self>, injector: &I) {
+        T::inject_retry_status_to(&status.cast(), injector);
+    }
+
+    fn provides(types: &mut Vec<(TypeId, &'static str)>) {
+        T::provides(types);
+    }
 }
 
 /// Ignores waiting on a value of the wrapped component.
@@ -169,6 +465,8 @@ where
     fn watch_from(injector: &I) -> Self::Watch {
         CurrentWatch::new(T::watch_from(injector))
     }
+
+    fn dependencies(_types: &mut Vec<(TypeId, &'static str)>) {}
 }
 
 /// Waits until the result of this component's evaluation is available.
@@ -185,6 +483,10 @@ where
     fn watch_from(injector: &I) -> Self::Watch {
         WaitAlwaysWatch::new(T::watch_from(injector))
     }
+
+    fn dependencies(types: &mut Vec<(TypeId, &'static str)>) {
+        T::dependencies(types);
+    }
 }
 
 /// Waits until the `Ok` value of this component's is available.
@@ -201,6 +503,34 @@ where
     fn watch_from(injector: &I) -> Self::Watch {
         WaitOkWatch::new(T::watch_from(injector))
     }
+
+    fn dependencies(types: &mut Vec<(TypeId, &'static str)>) {
+        T::dependencies(types);
+    }
+}
+
+/// Serves the last `Ok` value of this component if its latest evaluation failed.
+///
+/// This only differs from [`WaitOk`] for components whose state keeps the last `Ok` value, e.g.
+/// with [`ErrorPolicy::StaleOnError`](crate::injector::state::ErrorPolicy::StaleOnError) in
+/// [`StateMap`](crate::injector::StateMap). The error is still visible to other readers.
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct LastOk<T>(pub T);
+
+impl<I, T> WatchFrom<I> for LastOk<T>
+where
+    T: WatchFrom<I>,
+    T::Watch: Send,
+{
+    type Watch = LastOkWatch<T::Watch>;
+
+    fn watch_from(injector: &I) -> Self::Watch {
+        LastOkWatch::new(T::watch_from(injector))
+    }
+
+    fn dependencies(types: &mut Vec<(TypeId, &'static str)>) {
+        T::dependencies(types);
+    }
 }
 
 macro_rules! impl_composite_tuple {
@@ -220,6 +550,10 @@ macro_rules! impl_composite_tuple {
             fn watch_from(injector: &I) -> Self::Watch {
                 ($($ty::watch_from(injector),)*)
             }
+
+            fn dependencies(types: &mut Vec<(TypeId, &'static str)>) {
+                $($ty::dependencies(types);)*
+            }
         }
 
         #[allow(non_snake_case)]
@@ -245,6 +579,36 @@ macro_rules! impl_composite_tuple {
                     }
                 }
             }
+
+            fn inject_expiring_to(value: Self, injector: &I, expires_at: Instant) {
+                let ($($ty,)*) = value;
+                $($ty::inject_expiring_to($ty, injector, expires_at);)*
+            }
+
+            fn retract_from(injector: &I) {
+                $($ty::retract_from(injector);)*
+            }
+
+            fn is_expiring() -> bool {
+                false $(|| $ty::is_expiring())*
+            }
+
+            fn error_of(value: &Self) -> Option<&Error> {
+                let ($($ty,)*) = value;
+                None$(.or_else(|| $ty::error_of($ty)))*
+            }
+
+            fn promise_retry_status_to(injector: &I) {
+                $($ty::promise_retry_status_to(injector);)*
+            }
+
+            fn inject_retry_status_to(status: &RetryStatus<Self>, injector: &I) {
+                $($ty::inject_retry_status_to(&status.cast(), injector);)*
+            }
+
+            fn provides(types: &mut Vec<(TypeId, &'static str)>) {
+                $($ty::provides(types);)*
+            }
         }
     }
 }
@@ -295,6 +659,71 @@ where
         self.0.wait_ok().await.map(Component)
     }
 
+    fn current_last_ok(&self) -> Result<Self::Ty> {
+        self.0.current_last_ok().map(Component)
+    }
+
+    async fn wait_last_ok(&mut self) -> Result<Self::Ty> {
+        self.0.wait_last_ok().await.map(Component)
+    }
+
+    async fn changed(&mut self) -> Result<()> {
+        self.0.changed().await
+    }
+}
+
+/// Watches over values wrapped in [`Shared`].
+#[doc(hidden)]
+#[derive(Debug, Default, Clone)]
+pub struct SharedComponentWatch<W>(W);
+
+impl<W> SharedComponentWatch<W> {
+    /// Wraps a watch in a new `SharedComponentWatch`
+    pub(crate) const fn new(watch: W) -> Self {
+        Self(watch)
+    }
+}
+
+impl<W, T> Watch for SharedComponentWatch<W>
+where
+    W: Watch<Ty = Arc<T>> + Send,
+{
+    type Ty = Shared<T>;
+
+    fn current(&self) -> Result<Self::Ty> {
+        self.0.current().map(Shared)
+    }
+
+    fn current_optional(&self) -> Result<Option<Self::Ty>> {
+        let value = self.0.current_optional()?;
+        Ok(value.map(Shared))
+    }
+
+    async fn wait(&mut self) -> Result<Self::Ty> {
+        self.0.wait().await.map(Shared)
+    }
+
+    async fn wait_optional(&mut self) -> Result<Option<Self::Ty>> {
+        let value = self.0.wait_optional().await?;
+        Ok(value.map(Shared))
+    }
+
+    async fn wait_always(&mut self) -> Result<Self::Ty> {
+        self.0.wait_always().await.map(Shared)
+    }
+
+    async fn wait_ok(&mut self) -> Result<Self::Ty> {
+        self.0.wait_ok().await.map(Shared)
+    }
+
+    fn current_last_ok(&self) -> Result<Self::Ty> {
+        self.0.current_last_ok().map(Shared)
+    }
+
+    async fn wait_last_ok(&mut self) -> Result<Self::Ty> {
+        self.0.wait_last_ok().await.map(Shared)
+    }
+
     async fn changed(&mut self) -> Result<()> {
         self.0.changed().await
     }
@@ -441,6 +870,11 @@ where
     fn changed(&mut self) -> impl Future<Output = Result<()>> + Send {
         std::future::pending()
     }
+
+    // The value is read without waiting, so it never holds back the dependent.
+    fn any_pending(&self) -> bool {
+        false
+    }
 }
 
 /// Watches over [`WaitAlways`] values.
@@ -540,3 +974,63 @@ where
         self.0.changed().await
     }
 }
+
+/// Watches over [`LastOk`] values.
+#[doc(hidden)]
+#[derive(Debug, Default, Clone)]
+pub struct LastOkWatch<W>(W);
+
+impl<W> LastOkWatch<W> {
+    /// Wraps a watch in a new `LastOkWatch`
+    pub(crate) const fn new(watch: W) -> Self {
+        Self(watch)
+    }
+}
+
+impl<W> Watch for LastOkWatch<W>
+where
+    W: Watch + Send,
+{
+    type Ty = LastOk<W::Ty>;
+
+    fn current(&self) -> Result<Self::Ty> {
+        self.0.current_last_ok().map(LastOk)
+    }
+
+    fn current_optional(&self) -> Result<Option<Self::Ty>> {
+        let value = self
+            .0
+            .current_optional()
+            .or_else(|_| self.0.current_last_ok().map(Some))?;
+        Ok(value.map(LastOk))
+    }
+
+    async fn wait(&mut self) -> Result<Self::Ty> {
+        self.0.wait_last_ok().await.map(LastOk)
+    }
+
+    async fn wait_optional(&mut self) -> Result<Option<Self::Ty>> {
+        let value = self.0.wait_last_ok().await?;
+        Ok(Some(LastOk(value)))
+    }
+
+    async fn wait_always(&mut self) -> Result<Self::Ty> {
+        self.0.wait_last_ok().await.map(LastOk)
+    }
+
+    async fn wait_ok(&mut self) -> Result<Self::Ty> {
+        self.0.wait_last_ok().await.map(LastOk)
+    }
+
+    fn current_last_ok(&self) -> Result<Self::Ty> {
+        self.0.current_last_ok().map(LastOk)
+    }
+
+    async fn wait_last_ok(&mut self) -> Result<Self::Ty> {
+        self.0.wait_last_ok().await.map(LastOk)
+    }
+
+    async fn changed(&mut self) -> Result<()> {
+        self.0.changed().await
+    }
+}
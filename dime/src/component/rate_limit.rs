@@ -0,0 +1,77 @@
+use std::pin::pin;
+use std::task::Poll;
+use std::time::{Duration, Instant};
+
+use crate::Result;
+use crate::component::timer::SharedTimer;
+use crate::injector::Watch;
+use crate::runtime::Timer;
+
+/// Limits how often a constructor is run in response to changes of its dependencies.
+///
+/// Changes that happen while the constructor is held back are coalesced, so the constructor will
+/// only see the latest values of its dependencies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimit {
+    /// Runs the constructor only after the dependencies stop changing for the given duration.
+    Debounce(Duration),
+    /// Runs the constructor at most once per the given duration.
+    Throttle(Duration),
+}
+
+/// Applies a [`RateLimit`] using timers from a runtime.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    limit: RateLimit,
+    timer: SharedTimer,
+    last_run: Option<Instant>,
+}
+
+impl RateLimiter {
+    pub fn new<Tm>(limit: RateLimit, timer: Tm) -> Self
+    where
+        Tm: Timer,
+    {
+        Self {
+            limit,
+            timer: SharedTimer::new(timer),
+            last_run: None,
+        }
+    }
+
+    /// Records that the constructor is about to run.
+    pub fn mark_run(&mut self) {
+        self.last_run = Some(self.timer.now());
+    }
+
+    /// Holds back after a change of the watched value until the constructor is allowed to run.
+    pub async fn settle<W>(&self, watch: &mut W) -> Result<()>
+    where
+        W: Watch,
+    {
+        match self.limit {
+            RateLimit::Debounce(window) => loop {
+                let mut sleep = self.timer.sleep(window);
+                let mut changed = pin!(watch.changed());
+
+                let quiet = std::future::poll_fn(|cx| {
+                    if let Poll::Ready(res) = changed.as_mut().poll(cx) {
+                        return Poll::Ready(res.map(|()| false));
+                    }
+                    sleep.as_mut().poll(cx).map(|()| Ok(true))
+                })
+                .await?;
+
+                if quiet {
+                    return Ok(());
+                }
+            },
+            RateLimit::Throttle(period) => {
+                if let Some(last_run) = self.last_run {
+                    self.timer.sleep_until(last_run + period).await;
+                }
+                Ok(())
+            }
+        }
+    }
+}
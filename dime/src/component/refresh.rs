@@ -0,0 +1,45 @@
+use std::pin::pin;
+use std::task::Poll;
+use std::time::Duration;
+
+use crate::Result;
+use crate::component::timer::SharedTimer;
+use crate::injector::Watch;
+use crate::runtime::Timer;
+
+/// Re-runs a constructor on a schedule, using timers from a runtime.
+#[derive(Debug, Clone)]
+pub struct Refresher {
+    interval: Duration,
+    timer: SharedTimer,
+}
+
+impl Refresher {
+    pub fn new<Tm>(interval: Duration, timer: Tm) -> Self
+    where
+        Tm: Timer,
+    {
+        Self {
+            interval,
+            timer: SharedTimer::new(timer),
+        }
+    }
+
+    /// Waits until the watched value changes or the refresh interval elapses, whichever comes
+    /// first. Returns `true` if the watched value changed.
+    pub async fn changed_or_due<W>(&self, watch: &mut W) -> Result<bool>
+    where
+        W: Watch,
+    {
+        let mut sleep = self.timer.sleep(self.interval);
+        let mut changed = pin!(watch.changed());
+
+        std::future::poll_fn(|cx| {
+            if let Poll::Ready(res) = changed.as_mut().poll(cx) {
+                return Poll::Ready(res.map(|()| true));
+            }
+            sleep.as_mut().poll(cx).map(|()| Ok(false))
+        })
+        .await
+    }
+}
@@ -0,0 +1,347 @@
+use std::future::poll_fn;
+use std::hash::{BuildHasher, Hasher, RandomState};
+use std::marker::PhantomData;
+use std::pin::pin;
+use std::sync::Arc;
+use std::task::Poll;
+use std::time::Duration;
+
+#[cfg(feature = "tracing")]
+use tracing::field;
+
+use crate::component::timer::SharedTimer;
+use crate::component::unwind::catch_unwind_future;
+use crate::component::{AsyncConstructor, InjectTo, WatchFrom};
+use crate::injector::Watch;
+use crate::runtime::Timer;
+use crate::{Error, Result};
+
+/// Describes how a failing async constructor is retried.
+///
+/// The delay before the `n`-th retry is `initial * multiplier^(n - 1)`, capped at `max`, of which
+/// up to the `jitter` fraction is randomly subtracted.
+#[derive(Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    multiplier: f64,
+    jitter: f64,
+    retryable: Arc<dyn Fn(&Error) -> bool + Send + Sync>,
+}
+
+impl RetryPolicy {
+    /// Creates a policy that runs the constructor at most `max_attempts` times per change of its
+    /// dependencies.
+    ///
+    /// The constructor always runs at least once, so a `max_attempts` of 0 is treated as 1.
+    ///
+    /// By default, every error is retryable, and the backoff starts at 100 milliseconds, doubles
+    /// after each attempt up to 30 seconds, with a jitter of 0.5.
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts: max_attempts.max(1),
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(30),
+            multiplier: 2.0,
+            jitter: 0.5,
+            retryable: Arc::new(|_| true),
+        }
+    }
+
+    /// Sets the delay before the first retry and the maximum delay between retries.
+    #[must_use]
+    pub const fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Sets the factor the delay is multiplied by after each retry.
+    #[must_use]
+    pub const fn with_multiplier(mut self, multiplier: f64) -> Self {
+        self.multiplier = multiplier.max(1.0);
+        self
+    }
+
+    /// Sets the fraction of the delay that is randomized, between `0.0` and `1.0`.
+    #[must_use]
+    pub const fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Only retries errors for which `predicate` returns `true`.
+    #[must_use]
+    pub fn retry_if<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&Error) -> bool + Send + Sync + 'static,
+    {
+        self.retryable = Arc::new(predicate);
+        self
+    }
+
+    /// Returns the maximum number of attempts per change of the dependencies.
+    pub const fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// Returns `true` if `error` should be retried.
+    pub fn is_retryable(&self, error: &Error) -> bool {
+        (self.retryable)(error)
+    }
+
+    /// Returns the delay after the given failed attempt, before jitter is applied.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = i32::try_from(attempt.saturating_sub(1)).unwrap_or(i32::MAX);
+        let secs = self.initial_backoff.as_secs_f64() * self.multiplier.powi(exponent);
+        Duration::try_from_secs_f64(secs)
+            .unwrap_or(self.max_backoff)
+            .min(self.max_backoff)
+    }
+
+    /// Returns the delay after the given failed attempt, with jitter applied.
+    fn delay(&self, attempt: u32, rng: &mut Rng) -> Duration {
+        let backoff = self.backoff(attempt);
+        backoff.mul_f64(self.jitter.mul_add(-rng.next_f64(), 1.0))
+    }
+}
+
+impl std::fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("initial_backoff", &self.initial_backoff)
+            .field("max_backoff", &self.max_backoff)
+            .field("multiplier", &self.multiplier)
+            .field("jitter", &self.jitter)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A small xorshift generator, good enough for jitter.
+struct Rng(u64);
+
+impl Rng {
+    fn new() -> Self {
+        Self(RandomState::new().build_hasher().finish() | 1)
+    }
+
+    fn next_f64(&mut self) -> f64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        let bits = u32::try_from(self.0 >> 32).unwrap_or(u32::MAX);
+        f64::from(bits) / (f64::from(u32::MAX) + 1.0)
+    }
+}
+
+/// What a retrying constructor is currently doing.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum RetryPhase {
+    /// The constructor is running.
+    Attempting,
+    /// The last attempt failed, and the constructor will run again after `delay`.
+    Retrying {
+        /// The error of the last attempt.
+        error: Error,
+        /// The delay before the next attempt.
+        delay: Duration,
+    },
+    /// The last attempt succeeded.
+    Succeeded,
+    /// The last attempt failed, and the constructor will not run again until its dependencies
+    /// change.
+    Failed(Error),
+}
+
+/// The status of the retrying constructor of component `T`, e.g. `RetryStatus<Database>` for a
+/// constructor of `Component<Database>`.
+///
+/// The status is injected alongside the constructed component, so it can be watched like any
+/// other component. A constructor of several components injects a status for each of them.
+pub struct RetryStatus<T> {
+    attempt: u32,
+    phase: RetryPhase,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> RetryStatus<T> {
+    const fn new(attempt: u32, phase: RetryPhase) -> Self {
+        Self {
+            attempt,
+            phase,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the same status for component `U`.
+    pub(crate) fn cast<U>(&self) -> RetryStatus<U> {
+        RetryStatus::new(self.attempt, self.phase.clone())
+    }
+
+    /// Returns the number of the current or last attempt, starting from 1.
+    pub const fn attempt(&self) -> u32 {
+        self.attempt
+    }
+
+    /// Returns what the constructor is currently doing.
+    pub const fn phase(&self) -> &RetryPhase {
+        &self.phase
+    }
+
+    /// Returns `true` if the constructor is waiting to be retried.
+    pub const fn is_retrying(&self) -> bool {
+        matches!(self.phase, RetryPhase::Retrying { .. })
+    }
+}
+
+impl<T> Clone for RetryStatus<T> {
+    fn clone(&self) -> Self {
+        self.cast()
+    }
+}
+
+impl<T> std::fmt::Debug for RetryStatus<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RetryStatus")
+            .field("attempt", &self.attempt)
+            .field("phase", &self.phase)
+            .finish()
+    }
+}
+
+/// Retries failed attempts of an async constructor according to a [`RetryPolicy`].
+#[derive(Debug, Clone)]
+pub struct Retrier {
+    policy: RetryPolicy,
+    timer: SharedTimer,
+}
+
+impl Retrier {
+    pub fn new<Tm>(policy: RetryPolicy, timer: Tm) -> Self
+    where
+        Tm: Timer,
+    {
+        Self {
+            policy,
+            timer: SharedTimer::new(timer),
+        }
+    }
+
+    /// Runs the constructor until an attempt succeeds or the policy gives up, then injects its
+    /// output. The [`RetryStatus`] of the constructed components is injected after every attempt.
+    ///
+    /// Returns `false` if the dependencies changed while waiting to retry, in which case nothing
+    /// is injected and the constructor should be run again from the new values.
+    pub async fn construct<I, C, T>(
+        &self,
+        constructor: &C,
+        mut input: T,
+        watch: &mut T::Watch,
+        injector: I,
+    ) -> Result<bool>
+    where
+        I: Send,
+        T: WatchFrom<I> + Send,
+        T::Watch: Send,
+        C: AsyncConstructor<T> + Clone + Sync,
+        C::Constructed: InjectTo<I>,
+    {
+        let mut rng = Rng::new();
+        let mut attempt = 1;
+        loop {
+            let status = |phase| RetryStatus::<C::Constructed>::new(attempt, phase);
+            C::Constructed::inject_retry_status_to(&status(RetryPhase::Attempting), &injector);
+
+            // The output is dropped before waiting to retry, as it need not be `Send`.
+            let error = {
+                let output = catch_unwind_future(constructor.clone().construct(input)).await;
+                let error = match &output {
+                    Ok(output) => C::Constructed::error_of(output),
+                    Err(err) => Some(err),
+                };
+                trace!(attempt, error = error.map(field::display), "constructed");
+
+                let Some(error) = error.cloned() else {
+                    C::Constructed::inject_to(output, &injector);
+                    let status = status(RetryPhase::Succeeded);
+                    C::Constructed::inject_retry_status_to(&status, &injector);
+                    return Ok(true);
+                };
+
+                if attempt >= self.policy.max_attempts() || !self.policy.is_retryable(&error) {
+                    C::Constructed::inject_to(output, &injector);
+                    let status = status(RetryPhase::Failed(error));
+                    C::Constructed::inject_retry_status_to(&status, &injector);
+                    return Ok(true);
+                }
+                error
+            };
+
+            let delay = self.policy.delay(attempt, &mut rng);
+            C::Constructed::inject_retry_status_to(
+                &status(RetryPhase::Retrying { error, delay }),
+                &injector,
+            );
+
+            // Give up on retrying if the dependencies change in the meantime.
+            let changed = {
+                let mut sleep = self.timer.sleep(delay);
+                let mut changed = pin!(watch.changed());
+                poll_fn(|cx| {
+                    if let Poll::Ready(res) = changed.as_mut().poll(cx) {
+                        return Poll::Ready(res.map(|()| true));
+                    }
+                    sleep.as_mut().poll(cx).map(|()| Ok(false))
+                })
+                .await?
+            };
+
+            if changed {
+                trace!("changed while retrying");
+                return Ok(false);
+            }
+
+            // The dependencies are unchanged, so this reads the same values again.
+            input = match watch.wait().await {
+                Ok(input) => input,
+                Err(err) => {
+                    C::Constructed::inject_to(Err(err), &injector);
+                    return Ok(true);
+                }
+            };
+            attempt += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy::new(5)
+            .with_backoff(Duration::from_secs(1), Duration::from_secs(5))
+            .with_jitter(0.0);
+
+        let mut rng = Rng::new();
+        let delays: Vec<_> = (1..=5).map(|n| policy.delay(n, &mut rng)).collect();
+        assert_eq!(delays, [1, 2, 4, 5, 5].map(Duration::from_secs));
+    }
+
+    #[test]
+    fn test_jitter() {
+        let policy = RetryPolicy::new(5)
+            .with_backoff(Duration::from_secs(4), Duration::from_secs(4))
+            .with_jitter(0.5);
+
+        let mut rng = Rng::new();
+        for _ in 0..100 {
+            let delay = policy.delay(1, &mut rng);
+            assert!(delay > Duration::from_secs(2) && delay <= Duration::from_secs(4));
+        }
+    }
+}
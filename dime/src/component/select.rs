@@ -0,0 +1,40 @@
+use std::future::{Ready, ready};
+use std::marker::PhantomData;
+
+use crate::Result;
+use crate::injector::{InjectorTask, SelectInjector};
+
+/// A projection from values of component `T` into another component.
+///
+/// Unlike [`ConstructorTask`](crate::component::ConstructorTask), a selector does not run in its
+/// own task. The projection is run inline every time a value of `T` is injected, and the projected
+/// value is only propagated if it differs from the current one. See [`SelectInjector::select`].
+pub struct Select<T, F> {
+    selector: F,
+    _marker: PhantomData<fn(&T)>,
+}
+
+impl<T, F> Select<T, F> {
+    /// Creates a new [`Select`] from a projection function.
+    pub const fn new(selector: F) -> Self {
+        Self {
+            selector,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<I, T, U, F> InjectorTask<I> for Select<T, F>
+where
+    I: SelectInjector,
+    T: Send + Sync + 'static,
+    U: PartialEq + Send + Sync + 'static,
+    F: Fn(&T) -> U + Send + Sync + 'static,
+{
+    type Future = Ready<Result<()>>;
+
+    fn run(self, injector: I) -> Self::Future {
+        injector.select(self.selector);
+        ready(Ok(()))
+    }
+}
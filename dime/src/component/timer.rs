@@ -0,0 +1,56 @@
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::runtime::Timer;
+
+/// A dyn-compatible subset of [`Timer`].
+trait ErasedTimer: Send + Sync + 'static {
+    fn now(&self) -> Instant;
+
+    fn sleep_until(&self, deadline: Instant) -> Pin<Box<dyn Future<Output = ()> + Send>>;
+}
+
+impl<Tm> ErasedTimer for Tm
+where
+    Tm: Timer,
+{
+    fn now(&self) -> Instant {
+        Timer::now(self)
+    }
+
+    fn sleep_until(&self, deadline: Instant) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        Box::pin(Timer::sleep_until(self, deadline))
+    }
+}
+
+/// A type-erased [`Timer`], so that tasks using timers need not be generic over the runtime.
+#[derive(Clone)]
+pub struct SharedTimer(Arc<dyn ErasedTimer>);
+
+impl SharedTimer {
+    pub fn new<Tm>(timer: Tm) -> Self
+    where
+        Tm: Timer,
+    {
+        Self(Arc::new(timer))
+    }
+
+    pub fn now(&self) -> Instant {
+        self.0.now()
+    }
+
+    pub fn sleep_until(&self, deadline: Instant) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        self.0.sleep_until(deadline)
+    }
+
+    pub fn sleep(&self, duration: Duration) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+        self.sleep_until(self.now() + duration)
+    }
+}
+
+impl std::fmt::Debug for SharedTimer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SharedTimer").finish_non_exhaustive()
+    }
+}
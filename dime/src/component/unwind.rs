@@ -0,0 +1,28 @@
+use std::future::poll_fn;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::pin;
+use std::task::Poll;
+
+use crate::{Error, Result};
+
+/// Calls `f`, converting a panic into [`Error::Panicked`].
+pub fn catch_unwind<F, T>(f: F) -> Result<T>
+where
+    F: FnOnce() -> T,
+{
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|payload| Error::from_panic(&*payload))
+}
+
+/// Awaits `fut`, converting a panic while polling it into [`Error::Panicked`].
+pub async fn catch_unwind_future<F>(fut: F) -> Result<F::Output>
+where
+    F: Future,
+{
+    let mut fut = pin!(fut);
+    poll_fn(|cx| match catch_unwind(|| fut.as_mut().poll(cx)) {
+        Ok(Poll::Ready(output)) => Poll::Ready(Ok(output)),
+        Ok(Poll::Pending) => Poll::Pending,
+        Err(err) => Poll::Ready(Err(err)),
+    })
+    .await
+}
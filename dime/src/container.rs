@@ -1,14 +1,22 @@
 //! Container types.
 
-use std::sync::Arc;
+use std::any::{TypeId, type_name};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
 
-use crate::Result;
 use crate::component::{
-    AsyncConstructor, AsyncConstructorTask, Component, Constructor, ConstructorTask, InjectTo,
-    WatchFrom,
+    AsyncConstructor, AsyncConstructorTask, BlockingConstructor, Component, Constructor,
+    ConstructorTask, InjectTo, RateLimit, Retrier, RetryPolicy, Select, Shared, WatchFrom,
 };
-use crate::injector::{Injector, InjectorTask, InjectorTaskObject, StateMap, Watch};
-use crate::runtime::Runtime;
+use crate::injector::{
+    Injector, InjectorTask, InjectorTaskObject, SelectInjector, StateMap, Watch,
+};
+use crate::runtime::{Runtime, Task, Timer};
+use crate::{Error, Result};
+
+mod local;
+pub use local::{LocalContainer, LocalContainerBuilder};
 
 /// A simple container of injected components.
 ///
@@ -78,26 +86,194 @@ use crate::runtime::Runtime;
 /// # }
 /// ```
 pub struct SimpleContainer<R, I = Arc<StateMap>> {
-    #[expect(dead_code)]
     rt: R,
     injector: I,
+    providers: SharedProviders,
 }
 
 /// A builder for [`SimpleContainer`]
 pub struct SimpleContainerBuilder<R, I = Arc<StateMap>> {
     rt: R,
     injector: I,
-    tasks: Vec<InjectorTaskObject<I>>,
+    tasks: Vec<Registration<I>>,
+    expiry: bool,
+}
+
+/// How a registration is resolved against other registrations that provide the same component.
+///
+/// Variants are ordered from the lowest to the highest precedence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Precedence {
+    /// Yields to explicit and overriding registrations.
+    Default,
+    /// Conflicts with other explicit registrations.
+    Explicit,
+    /// Replaces every other registration, including earlier overriding ones.
+    Overriding,
+}
+
+/// The components a registration provides and waits on, and its precedence over other
+/// registrations that provide the same components.
+#[derive(Debug, Clone)]
+struct Provider {
+    provides: Vec<(TypeId, &'static str)>,
+    dependencies: Vec<(TypeId, &'static str)>,
+    precedence: Precedence,
+    expiring: bool,
+}
+
+impl Provider {
+    /// Returns the provider of a task that waits on `T` and injects `O`.
+    fn of<I, T, O>() -> Self
+    where
+        T: WatchFrom<I>,
+        O: InjectTo<I>,
+    {
+        let mut provides = Vec::new();
+        O::provides(&mut provides);
+        let mut dependencies = Vec::new();
+        T::dependencies(&mut dependencies);
+        Self {
+            provides,
+            dependencies,
+            precedence: Precedence::Explicit,
+            expiring: O::is_expiring(),
+        }
+    }
+
+    /// Returns the provider of a task that provides and waits on no known components.
+    const fn none() -> Self {
+        Self {
+            provides: Vec::new(),
+            dependencies: Vec::new(),
+            precedence: Precedence::Explicit,
+            expiring: false,
+        }
+    }
+}
+
+/// A task registered to [`SimpleContainerBuilder`], along with its [`Provider`].
+struct Registration<I> {
+    task: RegisteredTask<I>,
+    provider: Provider,
+    retrier: Option<Retrier>,
+}
+
+/// A task registered to [`SimpleContainerBuilder`], or a function building it for registrations
+/// that can be made [`retrying`](SimpleContainerBuilder::retrying).
+enum RegisteredTask<I> {
+    Task(InjectorTaskObject<I>),
+    Retryable(Box<dyn FnOnce(Option<Retrier>) -> InjectorTaskObject<I> + Send>),
+}
+
+impl<I> Registration<I> {
+    const fn new(task: InjectorTaskObject<I>, provider: Provider) -> Self {
+        Self {
+            task: RegisteredTask::Task(task),
+            provider,
+            retrier: None,
+        }
+    }
+
+    /// Returns the task, made retrying if [`retrying`](SimpleContainerBuilder::retrying) was
+    /// applied to it, along with its provider.
+    fn into_parts(self) -> (InjectorTaskObject<I>, Provider) {
+        let task = match self.task {
+            RegisteredTask::Task(task) => task,
+            RegisteredTask::Retryable(build) => build(self.retrier),
+        };
+        (task, self.provider)
+    }
+
+    /// Returns `true` if [`retrying`](SimpleContainerBuilder::retrying) was applied to a task
+    /// that can not be retried.
+    const fn is_misapplied_retry(&self) -> bool {
+        self.retrier.is_some() && matches!(self.task, RegisteredTask::Task(_))
+    }
+}
+
+/// The providers of the tasks running in a [`SimpleContainer`], in registration order.
+#[derive(Debug, Default)]
+struct Providers {
+    running: BTreeMap<u64, Provider>,
+    next_id: u64,
+}
+
+impl Providers {
+    /// Adds the provider of a task registered to a running container, returning its id.
+    ///
+    /// Running tasks are never stopped in favor of a new one, so the provider is rejected if it
+    /// provides a component that a running task already provides.
+    fn add(&mut self, provider: Provider) -> Result<u64> {
+        let mut candidates: Vec<_> = self.running.values().cloned().collect();
+        candidates.push(provider);
+
+        let kept = resolve(&candidates)?;
+        let provider = candidates.last().expect("candidates should not be empty");
+        if kept.contains(&false)
+            && let Some(&(type_id, type_name)) = provider
+                .provides
+                .iter()
+                .find(|(type_id, _)| self.supplies(*type_id))
+        {
+            return Err(Error::Conflict(type_id, type_name));
+        }
+        check_cycles(&candidates, &kept)?;
+
+        let id = self.insert(candidates.pop().expect("candidates should not be empty"));
+        Ok(id)
+    }
+
+    fn insert(&mut self, provider: Provider) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.running.insert(id, provider);
+        id
+    }
+
+    /// Removes a provider, returning `true` if none of its components is provided by another
+    /// running task, so they can be retracted.
+    fn remove(&mut self, id: u64) -> bool {
+        let Some(provider) = self.running.remove(&id) else {
+            return false;
+        };
+        !provider
+            .provides
+            .iter()
+            .any(|(type_id, _)| self.supplies(*type_id))
+    }
+
+    /// Returns `true` if a running task provides the component.
+    fn supplies(&self, type_id: TypeId) -> bool {
+        self.running
+            .values()
+            .any(|provider| provider.provides.iter().any(|(id, _)| *id == type_id))
+    }
 }
 
+/// [`Providers`] shared between a [`SimpleContainer`] and its [`RegistrationHandle`]s.
+type SharedProviders = Arc<Mutex<Providers>>;
+
 impl<R> SimpleContainer<R> {
     /// Returns a new builder for `SimpleContainer`.
     #[must_use]
     pub fn builder(rt: R) -> SimpleContainerBuilder<R> {
+        Self::builder_with(rt, Arc::default())
+    }
+}
+
+impl<R, I> SimpleContainer<R, I> {
+    /// Returns a new builder for `SimpleContainer` that uses `injector` as the underlying
+    /// injector.
+    ///
+    /// The injector must uphold the contract documented on [`Injector`].
+    #[must_use]
+    pub const fn builder_with(rt: R, injector: I) -> SimpleContainerBuilder<R, I> {
         SimpleContainerBuilder {
             rt,
-            injector: Arc::default(),
+            injector,
             tasks: Vec::new(),
+            expiry: false,
         }
     }
 }
@@ -108,12 +284,19 @@ where
     I: Injector + Clone + Send + 'static,
 {
     /// Registers an [`InjectorTask`] to be run on the underlying injector of the container.
+    ///
+    /// The container does not know which components the task provides, so the task never
+    /// conflicts with constructors, and [`overriding`](Self::overriding) and
+    /// [`as_default`](Self::as_default) can not be applied to it.
     #[must_use]
     pub fn with_task<T>(mut self, task: T) -> Self
     where
         T: InjectorTask<I> + Send + 'static,
     {
-        self.tasks.push(InjectorTaskObject::new(task));
+        self.tasks.push(Registration::new(
+            InjectorTaskObject::new(task),
+            Provider::none(),
+        ));
         self
     }
 
@@ -127,6 +310,19 @@ where
         self.with_constructor(|| Component(component))
     }
 
+    /// Registers a component to the container that is stored and retrieved as [`Shared`].
+    ///
+    /// Unlike [`with_component`](Self::with_component), the component does not need to implement
+    /// [`Clone`].
+    #[must_use]
+    pub fn with_shared_component<T>(self, component: T) -> Self
+    where
+        T: Send + Sync + 'static,
+    {
+        let component = Shared::new(component);
+        self.with_constructor(move || component.clone())
+    }
+
     /// Registers a component constructor to the container.
     #[must_use]
     pub fn with_constructor<C, T>(mut self, constructor: C) -> Self
@@ -137,7 +333,7 @@ where
         C::Constructed: InjectTo<I>,
     {
         let task = ConstructorTask::new(constructor);
-        self.tasks.push(InjectorTaskObject::from_boxed_future(task));
+        self.push::<T, C::Constructed>(InjectorTaskObject::from_boxed_future(task));
         self
     }
 
@@ -151,28 +347,447 @@ where
         C::Constructed: InjectTo<I>,
         C::Future: Send,
     {
-        let task = AsyncConstructorTask::new(constructor);
-        self.tasks.push(InjectorTaskObject::from_boxed_future(task));
+        self.push_async(AsyncConstructorTask::new(constructor));
+        self
+    }
+
+    /// Registers a component constructor to the container that is run on a blocking thread of the
+    /// runtime.
+    ///
+    /// Use this for constructors that are CPU-heavy or call blocking functions.
+    #[must_use]
+    pub fn with_blocking_constructor<C, T>(self, constructor: C) -> Self
+    where
+        T: WatchFrom<I> + Send + 'static,
+        T::Watch: Send + 'static,
+        C: Constructor<T> + Clone + Send + Sync + 'static,
+        C::Constructed: Send + 'static,
+        Result<C::Constructed>: InjectTo<I>,
+    {
+        let constructor = BlockingConstructor::new(constructor, self.rt.clone());
+        self.with_async_constructor(constructor)
+    }
+
+    /// Registers a component constructor to the container, limiting how often it is run in
+    /// response to changes of its dependencies.
+    #[must_use]
+    pub fn with_rate_limited_constructor<C, T>(mut self, limit: RateLimit, constructor: C) -> Self
+    where
+        R: Timer,
+        T: WatchFrom<I> + Send + 'static,
+        T::Watch: Send + 'static,
+        C: Constructor<T> + Clone + Send + Sync + 'static,
+        C::Constructed: InjectTo<I>,
+    {
+        let task = ConstructorTask::new(constructor).with_rate_limit(limit, self.rt.clone());
+        self.push::<T, C::Constructed>(InjectorTaskObject::from_boxed_future(task));
+        self
+    }
+
+    /// Registers an async component constructor to the container, limiting how often it is run
+    /// in response to changes of its dependencies.
+    #[must_use]
+    pub fn with_rate_limited_async_constructor<C, T>(
+        mut self,
+        limit: RateLimit,
+        constructor: C,
+    ) -> Self
+    where
+        R: Timer,
+        T: WatchFrom<I> + Send + 'static,
+        T::Watch: Send + 'static,
+        C: AsyncConstructor<T> + Clone + Send + Sync + 'static,
+        C::Constructed: InjectTo<I>,
+        C::Future: Send,
+    {
+        let task = AsyncConstructorTask::new(constructor).with_rate_limit(limit, self.rt.clone());
+        self.push_async(task);
+        self
+    }
+
+    /// Registers an async component constructor to the container that is re-run every
+    /// `interval`, even if its dependencies are unchanged.
+    ///
+    /// The interval restarts whenever the constructor is run, including in response to changes of
+    /// its dependencies.
+    #[must_use]
+    pub fn with_refreshing_constructor<C, T>(mut self, interval: Duration, constructor: C) -> Self
+    where
+        R: Timer,
+        T: WatchFrom<I> + Send + 'static,
+        T::Watch: Send + 'static,
+        C: AsyncConstructor<T> + Clone + Send + Sync + 'static,
+        C::Constructed: InjectTo<I>,
+        C::Future: Send,
+    {
+        let task = AsyncConstructorTask::new(constructor).with_refresh(interval, self.rt.clone());
+        self.push_async(task);
+        self
+    }
+
+    /// Registers a projection that derives a component from another component.
+    ///
+    /// The projection is run inline whenever a value of `T` is injected, and the derived
+    /// component is only updated when the projected value changes. This requires the injector
+    /// of the container to implement [`SelectInjector`].
+    /// If the projection panics, [`Error::Panicked`] is injected as the
+    /// derived component instead.
+    #[must_use]
+    pub fn with_selector<T, U, F>(mut self, selector: F) -> Self
+    where
+        I: SelectInjector,
+        T: 'static,
+        U: 'static,
+        F: Fn(&T) -> U + Send + Sync + 'static,
+        Select<T, F>: InjectorTask<I> + Send + 'static,
+    {
+        self.tasks.push(Registration::new(
+            InjectorTaskObject::new(Select::new(selector)),
+            Provider {
+                provides: vec![(TypeId::of::<U>(), type_name::<U>())],
+                dependencies: vec![(TypeId::of::<T>(), type_name::<T>())],
+                precedence: Precedence::Explicit,
+                expiring: false,
+            },
+        ));
+        self
+    }
+
+    /// Makes the last registered constructor replace every other registered constructor of the
+    /// same components, e.g. to swap a real implementation for a fake in tests.
+    ///
+    /// If several overriding constructors provide the same component, the last one wins.
+    #[must_use]
+    pub fn overriding(self) -> Self {
+        self.with_precedence(Precedence::Overriding)
+    }
+
+    /// Makes the last registered constructor a default, which yields to any other registered
+    /// constructor of the same components.
+    #[must_use]
+    pub fn as_default(self) -> Self {
+        self.with_precedence(Precedence::Default)
+    }
+
+    /// Makes the last registered async constructor retry failed attempts according to `policy`.
+    ///
+    /// This applies to constructors registered with
+    /// [`with_async_constructor`](Self::with_async_constructor) and its variants, including
+    /// blocking, rate-limited and refreshing constructors.
+    ///
+    /// Errors of failed attempts are not injected while the constructor is being retried.
+    /// Instead, the progress of the constructor can be watched as
+    /// [`RetryStatus`](crate::component::RetryStatus) of each constructed component, e.g.
+    /// `RetryStatus<Database>` for a constructor of `Component<Database>`.
+    #[must_use]
+    pub fn retrying(mut self, policy: RetryPolicy) -> Self
+    where
+        R: Timer,
+    {
+        if let Some(registration) = self.tasks.last_mut() {
+            registration.retrier = Some(Retrier::new(policy, self.rt.clone()));
+        }
         self
     }
 
     /// Finalizes the building process and returns the built container.
     ///
     /// This will spawn the registered tasks on the underlying injector of the container.
+    /// Registrations are resolved like in [`try_build`](Self::try_build).
+    ///
+    /// # Panics
+    ///
+    /// This method panics with the error returned by [`try_build`](Self::try_build) if the
+    /// registrations are invalid, e.g. when two constructors of a component conflict.
     #[must_use]
     pub fn build(self) -> SimpleContainer<R, I> {
+        match self.try_build() {
+            Ok(container) => container,
+            Err(error) => panic!("invalid container registrations: {error}"),
+        }
+    }
+
+    /// Finalizes the building process and returns the built container.
+    ///
+    /// When several constructors provide the same component, overriding constructors take
+    /// precedence over the others, and default constructors yield to the others. Constructors
+    /// that yield a component are not run at all, even if they provide other components.
+    ///
+    /// # Errors
+    ///
+    /// This method returns [`Error::Conflict`] if no constructor of a component takes precedence
+    /// over the others, [`Error::Cycle`] if the constructors that would be run depend on each
+    /// other in a cycle, or [`Error::Other`] if [`overriding`](Self::overriding) or
+    /// [`as_default`](Self::as_default) is applied to a registration that provides no
+    /// components, [`retrying`](Self::retrying) to a registration that is not an async
+    /// constructor, or if a constructor of [`Expiring`](crate::component::Expiring) components
+    /// is registered without [`with_expiry`](Self::with_expiry) or
+    /// [`with_expiry_task`](Self::with_expiry_task).
+    pub fn try_build(self) -> Result<SimpleContainer<R, I>> {
+        let providers = self.providers();
+        let kept = resolve(&providers)?;
+        check_cycles(&providers, &kept)?;
+        self.check_retries()?;
+        self.check_expiry()?;
+        Ok(self.spawn(kept))
+    }
+
+    /// Registers a task that reverts expiring components to pending once they expire, e.g. by
+    /// calling [`StateMap::run_expiry`] on a custom injector wrapping a [`StateMap`].
+    ///
+    /// See [`with_expiry`](SimpleContainerBuilder::with_expiry) for the default injector.
+    #[must_use]
+    pub fn with_expiry_task<T>(mut self, task: T) -> Self
+    where
+        T: InjectorTask<I> + Send + 'static,
+    {
+        self.expiry = true;
+        self.with_task(task)
+    }
+
+    /// Expiring components would never expire without a task driving their expiry.
+    fn check_expiry(&self) -> Result<()> {
+        let expiring = self
+            .tasks
+            .iter()
+            .any(|registration| registration.provider.expiring);
+        if expiring && !self.expiry {
+            return Err(Error::other(
+                "expiring components registered without `with_expiry` or `with_expiry_task`",
+            ));
+        }
+        Ok(())
+    }
+
+    fn check_retries(&self) -> Result<()> {
+        if self.tasks.iter().any(Registration::is_misapplied_retry) {
+            return Err(Error::other(
+                "`retrying` applied to a registration that is not an async constructor",
+            ));
+        }
+        Ok(())
+    }
+
+    fn providers(&self) -> Vec<Provider> {
+        self.tasks
+            .iter()
+            .map(|registration| registration.provider.clone())
+            .collect()
+    }
+
+    fn spawn(self, kept: Vec<bool>) -> SimpleContainer<R, I> {
         let Self {
             rt,
             injector,
             tasks,
+            ..
         } = self;
 
-        for task in tasks {
-            let cloned = injector.clone();
-            rt.spawn(task.run(cloned));
+        let mut providers = Providers::default();
+        for (registration, kept) in tasks.into_iter().zip(kept) {
+            if kept {
+                let cloned = injector.clone();
+                let (task, provider) = registration.into_parts();
+                rt.spawn(task.run(cloned));
+                providers.insert(provider);
+            }
+        }
+
+        SimpleContainer {
+            rt,
+            injector,
+            providers: Arc::new(Mutex::new(providers)),
+        }
+    }
+
+    fn push<T, O>(&mut self, task: InjectorTaskObject<I>)
+    where
+        T: WatchFrom<I>,
+        O: InjectTo<I>,
+    {
+        self.tasks
+            .push(Registration::new(task, Provider::of::<I, T, O>()));
+    }
+
+    fn push_async<C, T>(&mut self, task: AsyncConstructorTask<C, T>)
+    where
+        T: WatchFrom<I> + Send + 'static,
+        T::Watch: Send + 'static,
+        C: AsyncConstructor<T> + Clone + Send + Sync + 'static,
+        C::Constructed: InjectTo<I>,
+        C::Future: Send,
+    {
+        let build = move |retrier: Option<Retrier>| {
+            let task = match retrier {
+                Some(retrier) => task.with_retrier(retrier),
+                None => task,
+            };
+            InjectorTaskObject::from_boxed_future(task)
+        };
+        self.tasks.push(Registration {
+            task: RegisteredTask::Retryable(Box::new(build)),
+            provider: Provider::of::<I, T, C::Constructed>(),
+            retrier: None,
+        });
+    }
+
+    fn with_precedence(mut self, precedence: Precedence) -> Self {
+        if let Some(registration) = self.tasks.last_mut() {
+            registration.provider.precedence = precedence;
+        }
+        self
+    }
+}
+
+/// Decides which registrations are run, given the components they provide.
+///
+/// Returns [`Error::Conflict`] if no registration of a component takes precedence over the others.
+fn resolve(providers: &[Provider]) -> Result<Vec<bool>> {
+    let mut candidates_by_type: Vec<(TypeId, &'static str, Vec<usize>)> = Vec::new();
+    let mut indices = HashMap::new();
+    for (i, provider) in providers.iter().enumerate() {
+        if provider.provides.is_empty() && provider.precedence != Precedence::Explicit {
+            return Err(Error::other(
+                "`overriding` or `as_default` applied to a registration that provides no components",
+            ));
+        }
+        for &(type_id, type_name) in &provider.provides {
+            let index = *indices.entry(type_id).or_insert_with(|| {
+                candidates_by_type.push((type_id, type_name, Vec::new()));
+                candidates_by_type.len() - 1
+            });
+            candidates_by_type[index].2.push(i);
+        }
+    }
+
+    let mut kept = vec![true; providers.len()];
+    for (type_id, type_name, candidates) in candidates_by_type {
+        let highest = candidates
+            .iter()
+            .map(|&i| providers[i].precedence)
+            .max()
+            .unwrap_or(Precedence::Explicit);
+        let winners: Vec<_> = candidates
+            .iter()
+            .copied()
+            .filter(|&i| providers[i].precedence == highest)
+            .collect();
+
+        if highest != Precedence::Overriding && winners.len() > 1 {
+            return Err(Error::Conflict(type_id, type_name));
+        }
+        let Some(&winner) = winners.last() else {
+            continue;
+        };
+        for i in candidates {
+            if i != winner {
+                kept[i] = false;
+            }
+        }
+    }
+
+    Ok(kept)
+}
+
+/// Returns [`Error::Cycle`] if the kept registrations depend on each other in a cycle.
+fn check_cycles(providers: &[Provider], kept: &[bool]) -> Result<()> {
+    let mut edges: HashMap<TypeId, Vec<(TypeId, &'static str)>> = HashMap::new();
+    let mut nodes = Vec::new();
+    for (provider, _) in providers.iter().zip(kept).filter(|(_, kept)| **kept) {
+        for &provided in &provider.provides {
+            nodes.push(provided);
+            edges
+                .entry(provided.0)
+                .or_default()
+                .extend_from_slice(&provider.dependencies);
         }
+    }
+
+    let mut visited = HashSet::new();
+    let mut path = Vec::new();
+    for node in nodes {
+        visit(node, &edges, &mut visited, &mut path)?;
+    }
+    Ok(())
+}
+
+/// Visits the dependencies of `node` depth-first, where `path` holds the components that
+/// transitively depend on `node`.
+fn visit(
+    node: (TypeId, &'static str),
+    edges: &HashMap<TypeId, Vec<(TypeId, &'static str)>>,
+    visited: &mut HashSet<TypeId>,
+    path: &mut Vec<(TypeId, &'static str)>,
+) -> Result<()> {
+    if let Some(start) = path.iter().position(|(type_id, _)| *type_id == node.0) {
+        let cycle = path[start..].iter().map(|(_, type_name)| *type_name);
+        return Err(Error::Cycle(cycle.collect()));
+    }
+    if !visited.insert(node.0) {
+        return Ok(());
+    }
+
+    path.push(node);
+    for &dependency in edges.get(&node.0).into_iter().flatten() {
+        visit(dependency, edges, visited, path)?;
+    }
+    path.pop();
+    Ok(())
+}
+
+impl<R> SimpleContainerBuilder<R>
+where
+    R: Runtime,
+{
+    /// Validates every value of the given component type injected into the container.
+    ///
+    /// See [`StateMap::with_validator`]. Validators are set on the underlying [`StateMap`], so
+    /// this is only available for containers with the default `Arc<StateMap>` injector.
+    #[must_use]
+    pub fn with_validator<T, F>(self, validator: F) -> Self
+    where
+        T: Send + Sync + 'static,
+        F: Fn(&T) -> Result<()> + Send + Sync + 'static,
+    {
+        self.injector.set_validator(validator, false);
+        self
+    }
+
+    /// Validates every value of the given component type injected into the container, dropping
+    /// invalid values.
+    ///
+    /// See [`StateMap::with_rejecting_validator`] and [`with_validator`](Self::with_validator).
+    #[must_use]
+    pub fn with_rejecting_validator<T, F>(self, validator: F) -> Self
+    where
+        T: Send + Sync + 'static,
+        F: Fn(&T) -> Result<()> + Send + Sync + 'static,
+    {
+        self.injector.set_validator(validator, true);
+        self
+    }
+}
 
-        SimpleContainer { rt, injector }
+impl<R> SimpleContainerBuilder<R>
+where
+    R: Runtime + Timer,
+{
+    /// Reverts components injected with [`Injector::inject_expiring`] to pending once they
+    /// expire, using the timers of the runtime.
+    ///
+    /// Constructors can inject expiring components by returning them wrapped in
+    /// [`Expiring`](crate::component::Expiring).
+    ///
+    /// Expiry is driven by [`StateMap::run_expiry`], so this is only available for containers
+    /// with the default `Arc<StateMap>` injector. Other injectors have to drive their own expiry
+    /// with [`with_expiry_task`](Self::with_expiry_task).
+    #[must_use]
+    pub fn with_expiry(self) -> Self {
+        let timer = self.rt.clone();
+        self.with_expiry_task(async move |injector: Arc<StateMap>| {
+            injector.run_expiry(timer).await;
+            Ok(())
+        })
     }
 }
 
@@ -224,36 +839,191 @@ where
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use std::sync::Arc;
-    use std::sync::atomic::{AtomicBool, Ordering};
-    use std::time::Duration;
-
-    use tokio::time::timeout;
-
-    use dime_util::runtime::TokioRuntime;
-
-    use crate::component::{Component, Current};
-    use crate::injector::Watch;
-
-    use super::*;
-
-    const TIMEOUT: Duration = Duration::from_millis(500);
-
-    #[derive(Clone, Debug, Default, PartialEq, Eq)]
-    struct Address(&'static str);
-
-    #[derive(Clone, Debug)]
-    struct Database(Arc<DatabaseInner>);
-
-    #[derive(Debug)]
-    struct DatabaseInner {
-        address: Address,
-        connected: AtomicBool,
+impl<R, I> SimpleContainer<R, I>
+where
+    R: Runtime,
+    I: Injector + Clone + Send + 'static,
+{
+    /// Registers an [`InjectorTask`] to be run on the underlying injector of the running
+    /// container.
+    ///
+    /// Removing the registration aborts the task, but does not retract anything it injected.
+    pub fn register_task<T>(&self, task: T) -> RegistrationHandle<R>
+    where
+        T: InjectorTask<I> + Send + 'static,
+    {
+        // A task provides nothing, so it can not conflict with anything.
+        let id = self
+            .providers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(Provider::none());
+        self.spawn(InjectorTaskObject::new(task), id, Box::new(|| {}))
     }
 
-    impl Database {
+    /// Registers a component constructor to the running container.
+    ///
+    /// Removing the registration aborts the constructor and retracts the components it
+    /// constructed.
+    ///
+    /// # Errors
+    ///
+    /// Running constructors are never stopped in favor of a registered one, so this method
+    /// returns [`Error::Conflict`] if a running constructor already provides one of the
+    /// components, regardless of its precedence, or [`Error::Cycle`] if the constructor would
+    /// close a dependency cycle.
+    pub fn register_constructor<C, T>(&self, constructor: C) -> Result<RegistrationHandle<R>>
+    where
+        T: WatchFrom<I> + Send + 'static,
+        T::Watch: Send + 'static,
+        C: Constructor<T> + Clone + Send + Sync + 'static,
+        C::Constructed: InjectTo<I>,
+    {
+        let task = ConstructorTask::new(constructor);
+        let injector = self.injector.clone();
+        self.register(
+            InjectorTaskObject::from_boxed_future(task),
+            Provider::of::<I, T, C::Constructed>(),
+            Box::new(move || C::Constructed::retract_from(&injector)),
+        )
+    }
+
+    /// Registers an async component constructor to the running container.
+    ///
+    /// Removing the registration aborts the constructor and retracts the components it
+    /// constructed.
+    ///
+    /// # Errors
+    ///
+    /// See [`register_constructor`](Self::register_constructor).
+    pub fn register_async_constructor<C, T>(&self, constructor: C) -> Result<RegistrationHandle<R>>
+    where
+        T: WatchFrom<I> + Send + 'static,
+        T::Watch: Send + 'static,
+        C: AsyncConstructor<T> + Clone + Send + Sync + 'static,
+        C::Constructed: InjectTo<I>,
+        C::Future: Send,
+    {
+        let task = AsyncConstructorTask::new(constructor);
+        let injector = self.injector.clone();
+        self.register(
+            InjectorTaskObject::from_boxed_future(task),
+            Provider::of::<I, T, C::Constructed>(),
+            Box::new(move || C::Constructed::retract_from(&injector)),
+        )
+    }
+
+    fn register(
+        &self,
+        task: InjectorTaskObject<I>,
+        provider: Provider,
+        retract: Retract,
+    ) -> Result<RegistrationHandle<R>> {
+        let id = self
+            .providers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .add(provider)?;
+        Ok(self.spawn(task, id, retract))
+    }
+
+    fn spawn(
+        &self,
+        task: InjectorTaskObject<I>,
+        id: u64,
+        retract: Retract,
+    ) -> RegistrationHandle<R> {
+        let task = self.rt.spawn(task.run(self.injector.clone()));
+        RegistrationHandle {
+            task,
+            retract,
+            providers: Arc::clone(&self.providers),
+            id,
+        }
+    }
+}
+
+type Retract = Box<dyn FnOnce() + Send>;
+
+/// A handle to a constructor or task registered to a running [`SimpleContainer`].
+///
+/// Dropping the handle does not unregister it, use [`remove`](Self::remove) instead.
+pub struct RegistrationHandle<R>
+where
+    R: Runtime,
+{
+    task: R::Task<Result<()>>,
+    retract: Retract,
+    providers: SharedProviders,
+    id: u64,
+}
+
+impl<R> RegistrationHandle<R>
+where
+    R: Runtime,
+{
+    /// Unregisters the constructor or task, aborting it and retracting the components it
+    /// constructed.
+    ///
+    /// The components are retracted once the task has stopped, so they are not injected again
+    /// afterwards.
+    ///
+    /// # Errors
+    ///
+    /// This method returns how the task stopped: [`Error::Cancelled`] if it was still running and
+    /// got aborted, which is the usual case, or the error it failed with if it stopped on its own.
+    pub async fn remove(self) -> Result<()> {
+        let Self {
+            task,
+            retract,
+            providers,
+            id,
+        } = self;
+        task.abort();
+        let result = task.join().await.map_err(R::Task::join_error).flatten();
+        let unsupplied = providers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(id);
+        if unsupplied {
+            retract();
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::any::{TypeId, type_name};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::time::{Duration, Instant};
+
+    use tokio::time::timeout;
+
+    use dime_util::runtime::{TestRuntime, ThreadPoolRuntime, TokioRuntime};
+
+    use crate::Error;
+    use crate::component::{Component, Current, Expiring};
+    use crate::injector::{StateEvent, Watch};
+
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_millis(500);
+
+    #[derive(Clone, Debug, Default, PartialEq, Eq)]
+    struct Address(&'static str);
+
+    #[derive(Clone, Debug)]
+    struct Database(Arc<DatabaseInner>);
+
+    #[derive(Debug)]
+    struct DatabaseInner {
+        address: Address,
+        connected: AtomicBool,
+    }
+
+    impl Database {
         fn connect(address: Address) -> Self {
             Self(Arc::new(DatabaseInner {
                 address,
@@ -274,18 +1044,22 @@ mod tests {
         }
     }
 
-    #[tokio::test]
-    async fn test_db_constructor() {
-        let (tx, mut rx) = tokio::sync::mpsc::channel::<Address>(2);
+    #[test]
+    fn test_db_constructor() {
+        let rt = TestRuntime::new();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<Address>();
 
-        let container = SimpleContainer::builder(TokioRuntime::new())
+        // `Address` is defined up front so that the constructor never reads its own error
+        // through `Current`.
+        let injector = Arc::new(StateMap::new());
+        injector.define::<Address>();
+
+        let container = SimpleContainer::builder_with(rt.clone(), injector)
             .with_task(async move |injector: Arc<StateMap>| {
-                injector.define::<Address>();
-                loop {
-                    if let Some(address) = rx.recv().await {
-                        injector.inject(Ok(address));
-                    }
+                while let Some(address) = rx.recv().await {
+                    injector.inject(Ok(address));
                 }
+                Ok(())
             })
             .with_constructor(
                 |Component(address): Component<Address>,
@@ -299,23 +1073,1092 @@ mod tests {
             )
             .build();
 
-        let mut watch_db = container.watch::<Database>();
+        let watch_db = container.watch::<Database>();
 
-        tx.send(Address("foo")).await.unwrap();
-        let db1 = timeout(TIMEOUT, async { watch_db.wait_always().await.unwrap() })
-            .await
-            .unwrap();
+        tx.send(Address("foo")).unwrap();
+        rt.run_until_stalled();
+        let db1 = watch_db.current().unwrap();
         assert_eq!(db1.address(), &Address("foo"));
         assert!(db1.is_connected());
 
-        tx.send(Address("bar")).await.unwrap();
-        let db2 = timeout(TIMEOUT, async {
-            watch_db.changed().await.unwrap();
-            watch_db.wait_always().await.unwrap()
-        })
-        .await
-        .unwrap();
+        tx.send(Address("bar")).unwrap();
+        rt.run_until_stalled();
+        let db2 = watch_db.current().unwrap();
         assert_eq!(db2.address(), &Address("bar"));
         assert!(!db1.is_connected());
+        rt.assert_idle();
+    }
+
+    #[tokio::test]
+    async fn test_tokio_runtime() {
+        let container = SimpleContainer::builder(TokioRuntime::new())
+            .with_component(Address("foo"))
+            .with_constructor(|Component(address): Component<Address>| {
+                Component(Database::connect(address))
+            })
+            .build();
+
+        let mut watch_db = container.watch::<Database>();
+        let db = timeout(TIMEOUT, watch_db.wait_always())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(db.address(), &Address("foo"));
+    }
+
+    #[test]
+    fn test_builder_with_dyn_injector() {
+        use crate::injector::DynInjector;
+
+        let rt = TestRuntime::new();
+        let injector: Arc<dyn DynInjector> = Arc::new(StateMap::new());
+
+        let container = SimpleContainer::builder_with(rt.clone(), injector)
+            .with_constructor(|Component(address): Component<Address>| {
+                Component(Database::connect(address))
+            })
+            .with_component(Address("foo"))
+            .build();
+
+        rt.run_until_stalled();
+        let db = container.watch::<Database>().current().unwrap();
+        assert_eq!(db.address(), &Address("foo"));
+    }
+
+    #[test]
+    fn test_error_propagation_path() {
+        #[derive(Clone, Debug)]
+        struct Service(#[expect(dead_code)] Database);
+
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        struct Healthy(bool);
+
+        let rt = TestRuntime::new();
+        let container = SimpleContainer::builder(rt.clone())
+            .with_constructor(|| -> Result<Component<Address>> {
+                Err(Error::other("connection refused"))
+            })
+            .with_constructor(|Component(address): Component<Address>| {
+                Component(Database::connect(address))
+            })
+            .with_constructor(|Component(db): Component<Database>| Component(Service(db)))
+            .with_selector(|_: &Service| Healthy(true))
+            .build();
+
+        rt.run_until_stalled();
+        let err = container.watch::<Service>().current().unwrap_err();
+
+        assert!(err.is_other());
+        assert_eq!(
+            err.path(),
+            [
+                type_name::<Address>(),
+                type_name::<Database>(),
+                type_name::<Service>()
+            ]
+        );
+        assert_eq!(err.root_component(), Some(type_name::<Address>()));
+        assert_eq!(err.root_cause().to_string(), "connection refused");
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "{} ← {} ← {}: connection refused",
+                type_name::<Service>(),
+                type_name::<Database>(),
+                type_name::<Address>(),
+            )
+        );
+
+        // Errors forwarded by a selector record the selected component too.
+        let err = container.watch::<Healthy>().current().unwrap_err();
+        assert_eq!(
+            err.path(),
+            [
+                type_name::<Address>(),
+                type_name::<Database>(),
+                type_name::<Service>(),
+                type_name::<Healthy>()
+            ]
+        );
+
+        // A panic raised by a selector originates from the selected component.
+        let rt = TestRuntime::new();
+        let container = SimpleContainer::builder(rt.clone())
+            .with_component(Address("foo"))
+            .with_selector(|_: &Address| -> Healthy { panic!("unhealthy") })
+            .build();
+        rt.run_until_stalled();
+        let err = container.watch::<Healthy>().current().unwrap_err();
+        assert!(err.is_panicked());
+        assert_eq!(err.root_component(), Some(type_name::<Healthy>()));
+    }
+
+    #[test]
+    fn test_constructor_panic() {
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        struct Port(u16);
+
+        let rt = TestRuntime::new();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<Address>();
+
+        let container = SimpleContainer::builder(rt.clone())
+            .with_task(async move |injector: Arc<StateMap>| {
+                injector.define::<Address>();
+                while let Some(address) = rx.recv().await {
+                    injector.inject(Ok(address));
+                }
+                Ok(())
+            })
+            .with_constructor(|Component(address): Component<Address>| {
+                assert_ne!(address, Address("bad"), "bad address");
+                Component(Database::connect(address))
+            })
+            .with_async_constructor(async |Component(address): Component<Address>| {
+                assert_ne!(address, Address("bad"), "bad address");
+                Component(Port(80))
+            })
+            .build();
+
+        let watch_db = container.watch::<Database>();
+        let watch_port = container.watch::<Port>();
+
+        tx.send(Address("bad")).unwrap();
+        rt.run_until_stalled();
+        for err in [
+            watch_db.current().map(drop).unwrap_err(),
+            watch_port.current().map(drop).unwrap_err(),
+        ] {
+            assert!(err.is_panicked());
+            assert!(err.root_cause().to_string().contains("bad address"));
+        }
+
+        tx.send(Address("foo")).unwrap();
+        rt.run_until_stalled();
+        assert_eq!(watch_db.current().unwrap().address(), &Address("foo"));
+        assert_eq!(watch_port.current().unwrap(), Port(80));
+        rt.assert_idle();
+    }
+
+    #[test]
+    fn test_thread_pool_runtime() {
+        let rt = ThreadPoolRuntime::new(2);
+
+        let container = SimpleContainer::builder(rt.clone())
+            .with_component(Address("foo"))
+            .with_constructor(|Component(address): Component<Address>| {
+                Component(Database::connect(address))
+            })
+            .build();
+
+        let mut watch_db = container.watch::<Database>();
+        let db = rt
+            .block_on(rt.timeout(TIMEOUT, watch_db.wait_always()))
+            .unwrap()
+            .unwrap();
+        assert_eq!(db.address(), &Address("foo"));
+    }
+
+    #[test]
+    fn test_blocking_constructor() {
+        let rt = TestRuntime::new();
+        let container = SimpleContainer::builder(rt.clone())
+            .with_component(Address("foo"))
+            .with_blocking_constructor(|Component(address): Component<Address>| {
+                Component(Database::connect(address))
+            })
+            .build();
+
+        rt.run_until_stalled();
+        let db = container.watch::<Database>().current().unwrap();
+        assert_eq!(db.address(), &Address("foo"));
+    }
+
+    #[test]
+    fn test_blocking_constructor_panic() {
+        let rt = TestRuntime::new();
+        let container = SimpleContainer::builder(rt.clone())
+            .with_component(Address("bad"))
+            .with_blocking_constructor(|Component(address): Component<Address>| {
+                assert_ne!(address, Address("bad"), "bad address");
+                Component(Database::connect(address))
+            })
+            .build();
+
+        rt.run_until_stalled();
+        let err = container.watch::<Database>().current().unwrap_err();
+        assert!(err.is_panicked());
+        assert!(err.root_cause().to_string().contains("bad address"));
+    }
+
+    #[test]
+    fn test_selector_panic() {
+        let rt = TestRuntime::new();
+        let container = SimpleContainer::builder(rt.clone())
+            .with_task(async |injector: Arc<StateMap>| {
+                injector.inject(Ok(AppConfig {
+                    address: Address("bad"),
+                    verbose: false,
+                }));
+                Ok(())
+            })
+            .with_selector(|config: &AppConfig| {
+                assert_ne!(config.address, Address("bad"), "bad address");
+                config.address.clone()
+            })
+            .build();
+
+        rt.run_until_stalled();
+        let err = container.watch::<Address>().current().unwrap_err();
+        assert!(err.is_panicked());
+        assert!(err.root_cause().to_string().contains("bad address"));
+
+        // The selector keeps running after a panic.
+        let _handle = container.register_task(async |injector: Arc<StateMap>| {
+            injector.inject(Ok(AppConfig {
+                address: Address("foo"),
+                verbose: false,
+            }));
+            Ok(())
+        });
+        rt.run_until_stalled();
+        assert_eq!(
+            container.watch::<Address>().current().unwrap(),
+            Address("foo")
+        );
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    struct AppConfig {
+        address: Address,
+        verbose: bool,
+    }
+
+    #[test]
+    fn test_debounced_constructor() {
+        let rt = TestRuntime::new();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<Address>();
+        let constructed = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+
+        let cloned = Arc::clone(&constructed);
+        let container = SimpleContainer::builder(rt.clone())
+            .with_task(async move |injector: Arc<StateMap>| {
+                injector.define::<Address>();
+                while let Some(address) = rx.recv().await {
+                    injector.inject(Ok(address));
+                }
+                Ok(())
+            })
+            .with_rate_limited_constructor(
+                RateLimit::Debounce(Duration::from_secs(10)),
+                move |Component(address): Component<Address>| {
+                    cloned.fetch_add(1, Ordering::Relaxed);
+                    Component(Database::connect(address))
+                },
+            )
+            .build();
+
+        let watch_db = container.watch::<Database>();
+
+        tx.send(Address("foo")).unwrap();
+        rt.advance(Duration::from_secs(10));
+        assert_eq!(watch_db.current().unwrap().address(), &Address("foo"));
+        rt.assert_idle();
+
+        for address in ["bar", "baz", "qux"] {
+            tx.send(Address(address)).unwrap();
+            rt.advance(Duration::from_secs(1));
+        }
+        assert_eq!(watch_db.current().unwrap().address(), &Address("foo"));
+
+        rt.advance(Duration::from_secs(10));
+        assert_eq!(watch_db.current().unwrap().address(), &Address("qux"));
+        assert_eq!(constructed.load(Ordering::Relaxed), 2);
+        rt.assert_idle();
+    }
+
+    #[test]
+    fn test_throttled_constructor() {
+        let rt = TestRuntime::new();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<Address>();
+        let constructed = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+
+        let cloned = Arc::clone(&constructed);
+        let container = SimpleContainer::builder(rt.clone())
+            .with_task(async move |injector: Arc<StateMap>| {
+                injector.define::<Address>();
+                while let Some(address) = rx.recv().await {
+                    injector.inject(Ok(address));
+                }
+                Ok(())
+            })
+            .with_rate_limited_constructor(
+                RateLimit::Throttle(Duration::from_secs(10)),
+                move |Component(address): Component<Address>| {
+                    cloned.fetch_add(1, Ordering::Relaxed);
+                    Component(Database::connect(address))
+                },
+            )
+            .build();
+
+        let watch_db = container.watch::<Database>();
+
+        // Let the window of the initial run, before `Address` is defined, pass.
+        rt.advance(Duration::from_secs(10));
+
+        // The first value is built right away.
+        tx.send(Address("foo")).unwrap();
+        rt.run_until_stalled();
+        assert_eq!(watch_db.current().unwrap().address(), &Address("foo"));
+        assert_eq!(constructed.load(Ordering::Relaxed), 1);
+
+        // Changes within the window are held back and coalesced.
+        for address in ["bar", "baz"] {
+            rt.advance(Duration::from_secs(3));
+            tx.send(Address(address)).unwrap();
+            rt.run_until_stalled();
+        }
+        rt.advance(Duration::from_secs(3));
+        assert_eq!(watch_db.current().unwrap().address(), &Address("foo"));
+        assert_eq!(constructed.load(Ordering::Relaxed), 1);
+
+        // The latest value is built once the window since the last build has passed.
+        rt.advance(Duration::from_secs(1));
+        assert_eq!(watch_db.current().unwrap().address(), &Address("baz"));
+        assert_eq!(constructed.load(Ordering::Relaxed), 2);
+
+        // A change after the window has passed is built right away again.
+        rt.advance(Duration::from_secs(20));
+        tx.send(Address("qux")).unwrap();
+        rt.run_until_stalled();
+        assert_eq!(watch_db.current().unwrap().address(), &Address("qux"));
+        assert_eq!(constructed.load(Ordering::Relaxed), 3);
+        rt.assert_idle();
+    }
+
+    #[test]
+    fn test_retrying_async_constructor() {
+        use crate::component::{RetryPhase, RetryStatus};
+
+        let rt = TestRuntime::new();
+        let attempts = Arc::new(std::sync::atomic::AtomicU32::new(0));
+
+        let cloned = Arc::clone(&attempts);
+        let container = SimpleContainer::builder(rt.clone())
+            .with_component(Address("foo"))
+            .with_async_constructor(async move |Component(address): Component<Address>| {
+                if cloned.fetch_add(1, Ordering::Relaxed) < 2 {
+                    return Err(Error::other(std::io::Error::other("connection refused")));
+                }
+                Ok(Component(Database::connect(address)))
+            })
+            .retrying(
+                RetryPolicy::new(5)
+                    .with_backoff(Duration::from_secs(1), Duration::from_secs(10))
+                    .with_jitter(0.0)
+                    .retry_if(|err| !err.is_panicked()),
+            )
+            .build();
+
+        let watch_db = container.watch::<Database>();
+        let watch_status = container.watch::<RetryStatus<Database>>();
+
+        rt.run_until_stalled();
+        let status = watch_status.current().unwrap();
+        assert!(status.is_retrying());
+        assert_eq!(status.attempt(), 1);
+        assert!(matches!(
+            status.phase(),
+            RetryPhase::Retrying { delay, .. } if *delay == Duration::from_secs(1)
+        ));
+        assert!(watch_db.current().unwrap_err().is_pending());
+
+        rt.advance(Duration::from_secs(1));
+        let status = watch_status.current().unwrap();
+        assert_eq!(status.attempt(), 2);
+        assert!(matches!(
+            status.phase(),
+            RetryPhase::Retrying { delay, .. } if *delay == Duration::from_secs(2)
+        ));
+
+        rt.advance(Duration::from_secs(2));
+        assert_eq!(watch_db.current().unwrap().address(), &Address("foo"));
+        let status = watch_status.current().unwrap();
+        assert_eq!(status.attempt(), 3);
+        assert!(matches!(status.phase(), RetryPhase::Succeeded));
+        assert_eq!(attempts.load(Ordering::Relaxed), 3);
+        rt.assert_idle();
+    }
+
+    #[test]
+    fn test_retrying_async_constructor_gives_up() {
+        use crate::component::{RetryPhase, RetryStatus};
+
+        let rt = TestRuntime::new();
+
+        let container = SimpleContainer::builder(rt.clone())
+            .with_component(Address("foo"))
+            .with_async_constructor(
+                async |Component(_): Component<Address>| -> Result<Component<Database>> {
+                    Err(Error::other(std::io::Error::other("connection refused")))
+                },
+            )
+            .retrying(
+                RetryPolicy::new(3)
+                    .with_backoff(Duration::from_secs(1), Duration::from_secs(10))
+                    .with_jitter(0.0),
+            )
+            .build();
+
+        let watch_db = container.watch::<Database>();
+        let watch_status = container.watch::<RetryStatus<Database>>();
+
+        rt.advance(Duration::from_secs(3));
+        assert!(watch_db.current().unwrap_err().is_other());
+        let status = watch_status.current().unwrap();
+        assert_eq!(status.attempt(), 3);
+        assert!(matches!(status.phase(), RetryPhase::Failed(err) if err.is_other()));
+        rt.assert_idle();
+    }
+
+    #[test]
+    fn test_retrying_tuple_constructor() {
+        use crate::component::{RetryPhase, RetryStatus};
+
+        let rt = TestRuntime::new();
+        let attempts = Arc::new(std::sync::atomic::AtomicU32::new(0));
+
+        let cloned = Arc::clone(&attempts);
+        let container = SimpleContainer::builder(rt.clone())
+            .with_component(Address("foo"))
+            .with_async_constructor(async move |Component(address): Component<Address>| {
+                if cloned.fetch_add(1, Ordering::Relaxed) < 1 {
+                    return Err(Error::other(std::io::Error::other("connection refused")));
+                }
+                Ok((
+                    Component(address.0.len()),
+                    Component(Database::connect(address)),
+                ))
+            })
+            .retrying(RetryPolicy::new(2).with_jitter(0.0))
+            .build();
+
+        let watch_len = container.watch::<RetryStatus<usize>>();
+        let watch_db = container.watch::<RetryStatus<Database>>();
+
+        rt.run_until_stalled();
+        assert!(watch_len.current().unwrap().is_retrying());
+        assert!(watch_db.current().unwrap().is_retrying());
+
+        rt.advance(Duration::from_millis(100));
+        let len_status = watch_len.current().unwrap();
+        let db_status = watch_db.current().unwrap();
+        assert!(matches!(len_status.phase(), RetryPhase::Succeeded));
+        assert!(matches!(db_status.phase(), RetryPhase::Succeeded));
+        assert_eq!(db_status.attempt(), 2);
+        assert_eq!(container.watch::<usize>().current().unwrap(), 3);
+        rt.assert_idle();
+
+        // Only async constructors can be retried.
+        let err = SimpleContainer::builder(TestRuntime::new())
+            .with_constructor(|| Ok(Component(Address("foo"))))
+            .retrying(RetryPolicy::new(2))
+            .try_build()
+            .err()
+            .unwrap();
+        assert!(err.is_other());
+    }
+
+    #[test]
+    fn test_refreshing_constructor() {
+        let rt = TestRuntime::new();
+        let constructed = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+
+        let cloned = Arc::clone(&constructed);
+        let container = SimpleContainer::builder(rt.clone())
+            .with_component(Address("foo"))
+            .with_refreshing_constructor(
+                Duration::from_secs(10),
+                async move |Component(address): Component<Address>| {
+                    cloned.fetch_add(1, Ordering::Relaxed);
+                    Component(Database::connect(address))
+                },
+            )
+            .build();
+
+        let watch_db = container.watch::<Database>();
+
+        rt.run_until_stalled();
+        let db = watch_db.current().unwrap();
+        assert_eq!(constructed.load(Ordering::Relaxed), 1);
+
+        rt.advance(Duration::from_secs(5));
+        assert_eq!(constructed.load(Ordering::Relaxed), 1);
+
+        rt.advance(Duration::from_secs(5));
+        assert_eq!(constructed.load(Ordering::Relaxed), 2);
+        assert!(!Arc::ptr_eq(&db.0, &watch_db.current().unwrap().0));
+
+        rt.advance(Duration::from_secs(20));
+        assert_eq!(constructed.load(Ordering::Relaxed), 4);
+    }
+
+    #[test]
+    fn test_expiry() {
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        struct Token(u32);
+
+        let rt = TestRuntime::new();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<Token>();
+
+        let injector = Arc::new(StateMap::new());
+        let mut events = injector.events();
+
+        let timer = rt.clone();
+        let container = SimpleContainer::builder_with(rt.clone(), injector)
+            .with_expiry()
+            .with_task(async move |injector: Arc<StateMap>| {
+                injector.define::<Token>();
+                while let Some(token) = rx.recv().await {
+                    let expires_at = timer.now() + Duration::from_secs(10);
+                    injector.inject_expiring(Ok(token), expires_at);
+                }
+                Ok(())
+            })
+            .build();
+
+        let watch_token = container.watch::<Token>();
+
+        tx.send(Token(1)).unwrap();
+        rt.advance(Duration::from_secs(5));
+        assert_eq!(watch_token.current().unwrap(), Token(1));
+
+        tx.send(Token(2)).unwrap();
+        rt.advance(Duration::from_secs(5));
+        assert_eq!(watch_token.current().unwrap(), Token(2));
+        assert!(events.try_next().is_none());
+
+        rt.advance(Duration::from_secs(5));
+        assert!(watch_token.current().unwrap_err().is_pending());
+        assert!(matches!(
+            events.try_next(),
+            Some(StateEvent::Expired { type_id, .. }) if type_id == TypeId::of::<Token>()
+        ));
+        rt.assert_idle();
+    }
+
+    #[test]
+    fn test_validator() {
+        fn validate(address: &Address) -> Result<()> {
+            if address.0.is_empty() {
+                return Err(Error::other("empty address"));
+            }
+            Ok(())
+        }
+
+        let rt = TestRuntime::new();
+        let injector = Arc::new(StateMap::new());
+        let mut events = injector.events();
+
+        let container = SimpleContainer::builder_with(rt.clone(), injector)
+            .with_validator::<Address, _>(validate)
+            .with_rejecting_validator::<Database, _>(|db: &Database| validate(db.address()))
+            .with_component(Address(""))
+            .with_component(Database::connect(Address("")))
+            .build();
+
+        let watch_address = container.watch::<Address>();
+        let watch_db = container.watch::<Database>();
+
+        rt.run_until_stalled();
+        assert!(watch_address.current().unwrap_err().is_invalid());
+        assert!(watch_db.current().unwrap_err().is_pending());
+
+        let mut invalid = [events.try_next(), events.try_next()].map(|event| match event {
+            Some(StateEvent::Invalid {
+                type_id, rejected, ..
+            }) => (type_id == TypeId::of::<Database>(), rejected),
+            event => panic!("unexpected event: {event:?}"),
+        });
+        invalid.sort_unstable();
+        assert_eq!(invalid, [(false, false), (true, true)]);
+        assert!(events.try_next().is_none());
+        rt.assert_idle();
+    }
+
+    #[test]
+    fn test_expiring_constructor() {
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        struct Token(&'static str);
+
+        let rt = TestRuntime::new();
+
+        let timer = rt.clone();
+        let container = SimpleContainer::builder(rt.clone())
+            .with_expiry()
+            .with_component(Address("foo"))
+            .with_refreshing_constructor(
+                Duration::from_secs(5),
+                async move |Component(address): Component<Address>| {
+                    let expires_at = timer.now() + Duration::from_secs(10);
+                    Expiring::new(Component(Token(address.0)), expires_at)
+                },
+            )
+            .build();
+
+        let watch_token = container.watch::<Token>();
+
+        rt.run_until_stalled();
+        assert_eq!(watch_token.current().unwrap(), Token("foo"));
+
+        rt.advance(Duration::from_secs(30));
+        assert_eq!(watch_token.current().unwrap(), Token("foo"));
+
+        let timer = rt.clone();
+        let container = SimpleContainer::builder(rt.clone())
+            .with_expiry()
+            .with_component(Address("foo"))
+            .with_async_constructor(async move |Component(address): Component<Address>| {
+                let expires_at = timer.now() + Duration::from_secs(10);
+                Expiring::new(Component(Token(address.0)), expires_at)
+            })
+            .build();
+
+        let watch_token = container.watch::<Token>();
+
+        rt.advance(Duration::from_secs(5));
+        assert_eq!(watch_token.current().unwrap(), Token("foo"));
+
+        rt.advance(Duration::from_secs(5));
+        assert!(watch_token.current().unwrap_err().is_pending());
+
+        // Nothing would expire the components without `with_expiry`.
+        let err = SimpleContainer::builder(TestRuntime::new())
+            .with_async_constructor(async || Expiring::new(Component(Token("foo")), Instant::now()))
+            .try_build()
+            .err()
+            .unwrap();
+        assert!(err.is_other());
+    }
+
+    #[test]
+    fn test_retract_cascade() {
+        #[derive(Clone, Debug)]
+        struct Service(Database);
+
+        let rt = TestRuntime::new();
+        let injector = Arc::new(StateMap::new());
+
+        let container = SimpleContainer::builder_with(rt.clone(), Arc::clone(&injector))
+            .with_constructor(|Component(address): Component<Address>| {
+                Component(Database::connect(address))
+            })
+            .with_async_constructor(async |Component(db): Component<Database>| {
+                Component(Service(db))
+            })
+            .with_component(Address("foo"))
+            .build();
+
+        let watch_db = container.watch::<Database>();
+        let watch_service = container.watch::<Service>();
+
+        rt.run_until_stalled();
+        assert_eq!(
+            watch_service.current().unwrap().0.address(),
+            &Address("foo")
+        );
+
+        injector.retract::<Address>();
+        rt.run_until_stalled();
+        assert!(watch_db.current().unwrap_err().is_pending());
+        assert!(watch_service.current().unwrap_err().is_pending());
+
+        injector.inject(Ok(Address("bar")));
+        rt.run_until_stalled();
+        assert_eq!(watch_db.current().unwrap().address(), &Address("bar"));
+        assert_eq!(
+            watch_service.current().unwrap().0.address(),
+            &Address("bar")
+        );
+        rt.assert_idle();
+    }
+
+    #[test]
+    fn test_retract_cascade_tuple() {
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        struct Port(u16);
+
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        struct Url(&'static str, u16);
+
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        struct Socket(&'static str, u16);
+
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        struct Endpoint(&'static str, u16);
+
+        let rt = TestRuntime::new();
+        let injector = Arc::new(StateMap::new());
+
+        let container = SimpleContainer::builder_with(rt.clone(), Arc::clone(&injector))
+            .with_constructor(
+                |(Component(address), Component(port)): (Component<Address>, Component<Port>)| {
+                    Component(Url(address.0, port.0))
+                },
+            )
+            .with_async_constructor(
+                async |(Component(address), Component(port)): (
+                    Component<Address>,
+                    Component<Port>,
+                )| { Component(Socket(address.0, port.0)) },
+            )
+            .with_async_constructor(
+                async |(Component(address), Component(port)): (
+                    Component<Address>,
+                    Component<Port>,
+                )| { Ok(Component(Endpoint(address.0, port.0))) },
+            )
+            .retrying(RetryPolicy::new(1))
+            .with_component(Port(80))
+            .with_task(async |injector: Arc<StateMap>| {
+                injector.define::<Address>();
+                Ok(())
+            })
+            .build();
+
+        let watch_url = container.watch::<Url>();
+        let watch_socket = container.watch::<Socket>();
+        let watch_endpoint = container.watch::<Endpoint>();
+
+        injector.inject::<Address>(Err(Error::other("unreachable")));
+        rt.run_until_stalled();
+        assert!(watch_url.current().unwrap_err().is_other());
+        assert!(watch_socket.current().unwrap_err().is_other());
+        assert!(watch_endpoint.current().unwrap_err().is_other());
+
+        // `Address` is still in error, but `Port` is pending, so the outputs are retracted rather
+        // than kept as errors.
+        injector.retract::<Port>();
+        rt.run_until_stalled();
+        assert!(watch_url.current().unwrap_err().is_pending());
+        assert!(watch_socket.current().unwrap_err().is_pending());
+        assert!(watch_endpoint.current().unwrap_err().is_pending());
+        rt.assert_idle();
+    }
+
+    #[test]
+    fn test_retract_cascade_current() {
+        #[derive(Clone, Debug)]
+        struct Port(#[expect(dead_code)] u16);
+
+        let rt = TestRuntime::new();
+        let injector = Arc::new(StateMap::new());
+        injector.define::<Port>();
+
+        let container = SimpleContainer::builder_with(rt.clone(), injector)
+            .with_component(Address("foo"))
+            .with_constructor(
+                |Component(address): Component<Address>, Current(_): Current<Component<Port>>| {
+                    Component(Database::connect(address))
+                },
+            )
+            .build();
+
+        // `Port` is read without waiting, so its pending state is propagated as an error instead
+        // of holding back the constructor.
+        rt.run_until_stalled();
+        let err = container.watch::<Database>().current().unwrap_err();
+        assert!(err.is_pending());
+        assert_eq!(err.path(), [type_name::<Database>()]);
+        rt.assert_idle();
+    }
+
+    #[test]
+    fn test_register_constructor() {
+        let rt = TestRuntime::new();
+        let container = SimpleContainer::builder(rt.clone())
+            .with_component(Address("foo"))
+            .build();
+
+        let watch_db = container.watch::<Database>();
+        assert!(watch_db.current().unwrap_err().is_not_defined());
+
+        let handle = container
+            .register_constructor(|Component(address): Component<Address>| {
+                Component(Database::connect(address))
+            })
+            .unwrap();
+        rt.run_until_stalled();
+        assert_eq!(watch_db.current().unwrap().address(), &Address("foo"));
+
+        let err = rt.block_on(handle.remove()).unwrap_err();
+        assert!(err.is_cancelled());
+        assert!(watch_db.current().unwrap_err().is_pending());
+
+        let handle = container
+            .register_async_constructor(async |Component(address): Component<Address>| {
+                Component(Database::connect(address))
+            })
+            .unwrap();
+        rt.run_until_stalled();
+        assert_eq!(watch_db.current().unwrap().address(), &Address("foo"));
+
+        let err = rt.block_on(handle.remove()).unwrap_err();
+        assert!(err.is_cancelled());
+        assert!(watch_db.current().unwrap_err().is_pending());
+    }
+
+    #[test]
+    fn test_register_conflict() {
+        #[derive(Clone, Debug)]
+        struct A;
+        #[derive(Clone, Debug)]
+        struct B;
+
+        let rt = TestRuntime::new();
+        let container = SimpleContainer::builder(rt.clone())
+            .with_component(Address("foo"))
+            .as_default()
+            .build();
+
+        // Running constructors are not replaced, even default ones.
+        let err = container
+            .register_constructor(|| Component(Address("bar")))
+            .err()
+            .unwrap();
+        assert!(matches!(err, Error::Conflict(type_id, _) if type_id == TypeId::of::<Address>()));
+
+        let handle = container
+            .register_constructor(|Component(address): Component<Address>| {
+                Component(Database::connect(address))
+            })
+            .unwrap();
+        let err = container
+            .register_async_constructor(async |Component(address): Component<Address>| {
+                Component(Database::connect(address))
+            })
+            .err()
+            .unwrap();
+        assert!(err.is_conflict());
+
+        let _handle = container
+            .register_constructor(|Component(B): Component<B>| Component(A))
+            .unwrap();
+        let err = container
+            .register_constructor(|Component(A): Component<A>| Component(B))
+            .err()
+            .unwrap();
+        assert!(err.is_cycle());
+
+        // Removing the constructor frees the component for another one.
+        rt.block_on(handle.remove()).unwrap_err();
+        let handle = container
+            .register_constructor(|Component(address): Component<Address>| {
+                Component(Database::connect(address))
+            })
+            .unwrap();
+        rt.run_until_stalled();
+        let db = container.watch::<Database>().current().unwrap();
+        assert_eq!(db.address(), &Address("foo"));
+        rt.block_on(handle.remove()).unwrap_err();
+    }
+
+    #[test]
+    fn test_provider_precedence() {
+        let rt = TestRuntime::new();
+
+        let container = SimpleContainer::builder(rt.clone())
+            .with_component(Address("default"))
+            .as_default()
+            .with_component(Address("real"))
+            .with_constructor(|Component(address): Component<Address>| {
+                Component(Database::connect(address))
+            })
+            .with_component(Address("fake"))
+            .overriding()
+            .build();
+
+        rt.run_until_stalled();
+        let db = container.watch::<Database>().current().unwrap();
+        assert_eq!(db.address(), &Address("fake"));
+
+        let container = SimpleContainer::builder(rt.clone())
+            .with_component(Address("default"))
+            .as_default()
+            .with_component(Address("real"))
+            .build();
+
+        rt.run_until_stalled();
+        let address = container.watch::<Address>().current().unwrap();
+        assert_eq!(address, Address("real"));
+        rt.assert_idle();
+    }
+
+    #[test]
+    fn test_provider_conflict() {
+        let err = SimpleContainer::builder(TestRuntime::new())
+            .with_component(Address("foo"))
+            .with_component(Address("bar"))
+            .try_build()
+            .err()
+            .unwrap();
+        assert!(err.is_conflict());
+        assert!(matches!(err, Error::Conflict(type_id, _) if type_id == TypeId::of::<Address>()));
+    }
+
+    #[test]
+    #[should_panic(expected = "conflicting providers")]
+    fn test_build_conflict() {
+        let _ = SimpleContainer::builder(TestRuntime::new())
+            .with_component(Address("foo"))
+            .with_component(Address("bar"))
+            .build();
+    }
+
+    #[test]
+    fn test_selector_precedence() {
+        let config = AppConfig {
+            address: Address("selected"),
+            verbose: false,
+        };
+
+        let err = SimpleContainer::builder(TestRuntime::new())
+            .with_component(config.clone())
+            .with_component(Address("foo"))
+            .with_selector(|config: &AppConfig| config.address.clone())
+            .try_build()
+            .err()
+            .unwrap();
+        assert!(err.is_conflict());
+
+        let rt = TestRuntime::new();
+        let container = SimpleContainer::builder(rt.clone())
+            .with_component(config)
+            .with_component(Address("foo"))
+            .with_selector(|config: &AppConfig| config.address.clone())
+            .overriding()
+            .try_build()
+            .unwrap();
+        rt.run_until_stalled();
+        let address = container.watch::<Address>().current().unwrap();
+        assert_eq!(address, Address("selected"));
+
+        // Tasks provide nothing the container knows of, so they can not take precedence.
+        let err = SimpleContainer::builder(TestRuntime::new())
+            .with_task(async |_: Arc<StateMap>| Ok(()))
+            .overriding()
+            .try_build()
+            .err()
+            .unwrap();
+        assert!(err.is_other());
+    }
+
+    #[test]
+    fn test_dependency_cycle() {
+        #[derive(Clone, Debug)]
+        struct A;
+        #[derive(Clone, Debug)]
+        struct B;
+
+        let err = SimpleContainer::builder(TestRuntime::new())
+            .with_constructor(|Component(B): Component<B>| Component(A))
+            .with_constructor(|Component(A): Component<A>| Component(B))
+            .try_build()
+            .err()
+            .unwrap();
+        assert!(err.is_cycle());
+        assert!(
+            matches!(&err, Error::Cycle(path) if **path == [type_name::<A>(), type_name::<B>()])
+        );
+
+        // Reading the current value of a component does not wait on it.
+        let container = SimpleContainer::builder(TestRuntime::new())
+            .with_constructor(|Current(a): Current<Option<Component<A>>>| {
+                Component(a.map_or(A, |Component(a)| a))
+            })
+            .try_build();
+        assert!(container.is_ok());
+    }
+
+    #[test]
+    fn test_shared_component() {
+        use std::sync::Mutex;
+
+        // A component that does not implement `Clone`.
+        #[derive(Debug)]
+        struct Counter(Mutex<i32>);
+
+        let rt = TestRuntime::new();
+        let container = SimpleContainer::builder(rt.clone())
+            .with_shared_component(Counter(Mutex::new(0)))
+            .with_constructor(|Shared(counter): Shared<Counter>| {
+                let mut count = counter.0.lock().unwrap();
+                *count += 1;
+                Component(*count)
+            })
+            .build();
+
+        rt.run_until_stalled();
+        assert_eq!(container.watch::<i32>().current().unwrap(), 1);
+
+        let first = rt
+            .block_on(container.call(|counter: Shared<Counter>| counter))
+            .unwrap();
+        let second = rt
+            .block_on(container.call(|counter: Shared<Counter>| counter))
+            .unwrap();
+        assert!(Arc::ptr_eq(&first.0, &second.0));
+    }
+
+    #[test]
+    fn test_selector() {
+        let rt = TestRuntime::new();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<AppConfig>();
+
+        let container = SimpleContainer::builder(rt.clone())
+            .with_task(async move |injector: Arc<StateMap>| {
+                injector.define::<AppConfig>();
+                while let Some(config) = rx.recv().await {
+                    injector.inject(Ok(config));
+                }
+                Ok(())
+            })
+            .with_selector(|config: &AppConfig| config.address.clone())
+            .build();
+
+        let watch_address = container.watch::<Address>();
+
+        tx.send(AppConfig {
+            address: Address("foo"),
+            verbose: false,
+        })
+        .unwrap();
+        rt.run_until_stalled();
+        assert_eq!(watch_address.current().unwrap(), Address("foo"));
+
+        // Changing an unrelated field does not propagate.
+        let changed = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&changed);
+        let mut watch = container.watch::<Address>();
+        rt.spawn(async move {
+            watch.changed().await.unwrap();
+            flag.store(true, Ordering::Relaxed);
+        });
+        tx.send(AppConfig {
+            address: Address("foo"),
+            verbose: true,
+        })
+        .unwrap();
+        rt.run_until_stalled();
+        assert!(!changed.load(Ordering::Relaxed));
+
+        tx.send(AppConfig {
+            address: Address("bar"),
+            verbose: true,
+        })
+        .unwrap();
+        rt.run_until_stalled();
+        assert!(changed.load(Ordering::Relaxed));
+        assert_eq!(watch_address.current().unwrap(), Address("bar"));
+        rt.assert_idle();
     }
 }
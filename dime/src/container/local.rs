@@ -0,0 +1,479 @@
+use std::rc::Rc;
+
+use crate::Result;
+use crate::component::{
+    Component, Constructor, LocalAsyncConstructor, LocalAsyncConstructorTask, LocalConstructorTask,
+    LocalInjectTo, LocalWatchFrom,
+};
+use crate::injector::{
+    LocalInjector, LocalInjectorTask, LocalInjectorTaskObject, LocalStateMap, LocalWatch,
+};
+use crate::runtime::LocalRuntime;
+
+/// A single-threaded container of injected components.
+///
+/// This is the thread-local counterpart of [`SimpleContainer`](super::SimpleContainer): components
+/// and the futures of constructors do not need to implement [`Send`] or [`Sync`], and tasks are
+/// spawned on a [`LocalRuntime`].
+///
+/// # Example
+///
+/// ```
+/// use std::cell::Cell;
+/// use std::rc::Rc;
+///
+/// use dime::component::Component;
+/// use dime::container::LocalContainer;
+/// use dime::injector::LocalWatch;
+/// use dime_util::runtime::TokioLocalRuntime;
+///
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+/// # let local = tokio::task::LocalSet::new();
+/// # local.run_until(async {
+/// let counter = Rc::new(Cell::new(0));
+///
+/// let container = LocalContainer::builder(TokioLocalRuntime::new())
+///     .with_component(Rc::clone(&counter))
+///     .with_constructor(|counter: Rc<Cell<i32>>| {
+///         counter.set(counter.get() + 1);
+///         Component(counter.get())
+///     })
+///     .build();
+///
+/// let mut watch = container.watch::<i32>();
+/// assert_eq!(watch.wait_always().await?, 1);
+/// # Ok::<(), dime::Error>(())
+/// # }).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct LocalContainer<R, I = Rc<LocalStateMap>> {
+    rt: R,
+    injector: I,
+}
+
+/// A builder for [`LocalContainer`]
+pub struct LocalContainerBuilder<R, I = Rc<LocalStateMap>> {
+    rt: R,
+    injector: I,
+    tasks: Vec<LocalInjectorTaskObject<I>>,
+}
+
+impl<R> LocalContainer<R> {
+    /// Returns a new builder for `LocalContainer`.
+    #[must_use]
+    pub fn builder(rt: R) -> LocalContainerBuilder<R> {
+        Self::builder_with(rt, Rc::default())
+    }
+}
+
+impl<R, I> LocalContainer<R, I> {
+    /// Returns a new builder for `LocalContainer` that uses `injector` as the underlying
+    /// injector.
+    ///
+    /// The injector must uphold the contract documented on [`LocalInjector`].
+    #[must_use]
+    pub const fn builder_with(rt: R, injector: I) -> LocalContainerBuilder<R, I> {
+        LocalContainerBuilder {
+            rt,
+            injector,
+            tasks: Vec::new(),
+        }
+    }
+}
+
+impl<R, I> LocalContainerBuilder<R, I>
+where
+    R: LocalRuntime,
+    I: LocalInjector + Clone + 'static,
+{
+    /// Registers a [`LocalInjectorTask`] to be run on the underlying injector of the container.
+    #[must_use]
+    pub fn with_task<T>(mut self, task: T) -> Self
+    where
+        T: LocalInjectorTask<I> + 'static,
+    {
+        self.tasks.push(LocalInjectorTaskObject::new(task));
+        self
+    }
+
+    /// Registers a component to the container.
+    #[must_use]
+    pub fn with_component<T>(self, component: T) -> Self
+    where
+        T: Clone + 'static,
+    {
+        self.with_constructor(move || Component(component))
+    }
+
+    /// Registers a component constructor to the container.
+    #[must_use]
+    pub fn with_constructor<C, T>(mut self, constructor: C) -> Self
+    where
+        T: LocalWatchFrom<I> + 'static,
+        T::Watch: 'static,
+        C: Constructor<T> + Clone + 'static,
+        C::Constructed: LocalInjectTo<I>,
+    {
+        let task = LocalConstructorTask::new(constructor);
+        self.tasks
+            .push(LocalInjectorTaskObject::from_boxed_future(task));
+        self
+    }
+
+    /// Registers an async component constructor to the container.
+    ///
+    /// Unlike [`SimpleContainerBuilder::with_async_constructor`], the future returned by the
+    /// constructor does not need to be [`Send`].
+    ///
+    /// [`SimpleContainerBuilder::with_async_constructor`]:
+    ///     super::SimpleContainerBuilder::with_async_constructor
+    #[must_use]
+    pub fn with_async_constructor<C, T>(mut self, constructor: C) -> Self
+    where
+        T: LocalWatchFrom<I> + 'static,
+        T::Watch: 'static,
+        C: LocalAsyncConstructor<T> + Clone + 'static,
+        C::Constructed: LocalInjectTo<I>,
+    {
+        let task = LocalAsyncConstructorTask::new(constructor);
+        self.tasks
+            .push(LocalInjectorTaskObject::from_boxed_future(task));
+        self
+    }
+
+    /// Finalizes the building process and returns the built container.
+    ///
+    /// This will spawn the registered tasks on the underlying injector of the container.
+    #[must_use]
+    pub fn build(self) -> LocalContainer<R, I> {
+        let Self {
+            rt,
+            injector,
+            tasks,
+        } = self;
+
+        for task in tasks {
+            let cloned = injector.clone();
+            rt.spawn_local(task.run(cloned));
+        }
+
+        LocalContainer { rt, injector }
+    }
+}
+
+impl<R, I> LocalContainer<R, I>
+where
+    R: LocalRuntime,
+    I: LocalInjector,
+{
+    /// Watches for values of a component type in the container.
+    pub fn watch<T>(&self) -> I::Watch<T>
+    where
+        T: Clone + 'static,
+    {
+        self.injector.watch()
+    }
+
+    /// Calls a function using component dependencies as its arguments.
+    ///
+    /// # Errors
+    ///
+    /// This method might encounter an error while waiting on the required components, in which
+    /// case it will return the error.
+    #[expect(
+        clippy::future_not_send,
+        reason = "local containers are not meant to be sent between threads"
+    )]
+    pub async fn call<F, T>(&self, f: F) -> Result<F::Constructed>
+    where
+        T: LocalWatchFrom<I>,
+        F: Constructor<T>,
+    {
+        let mut watch = T::watch_from(&self.injector);
+        let input = watch.wait().await?;
+        Ok(f.construct(input))
+    }
+
+    /// Calls an asynchronous function using component dependencies as its arguments.
+    ///
+    /// # Errors
+    ///
+    /// This method might encounter an error while waiting on the required components, in which
+    /// case it will return the error.
+    #[expect(
+        clippy::future_not_send,
+        reason = "local containers are not meant to be sent between threads"
+    )]
+    pub async fn call_async<F, T>(&self, f: F) -> Result<F::Constructed>
+    where
+        T: LocalWatchFrom<I>,
+        F: LocalAsyncConstructor<T>,
+    {
+        let mut watch = T::watch_from(&self.injector);
+        let input = watch.wait().await?;
+        Ok(f.construct(input).await)
+    }
+}
+
+impl<R, I> LocalContainer<R, I>
+where
+    R: LocalRuntime,
+    I: LocalInjector + Clone + 'static,
+{
+    /// Registers a [`LocalInjectorTask`] to be run on the underlying injector of the running
+    /// container.
+    ///
+    /// Aborting the returned task stops it, but does not retract anything it injected.
+    pub fn register_task<T>(&self, task: T) -> R::Task<Result<()>>
+    where
+        T: LocalInjectorTask<I> + 'static,
+    {
+        let task = LocalInjectorTaskObject::new(task);
+        self.rt.spawn_local(task.run(self.injector.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::any::type_name;
+    use std::cell::RefCell;
+
+    use dime_util::runtime::TestRuntime;
+
+    use crate::Error;
+    use crate::component::Current;
+    use crate::runtime::Task;
+
+    use super::*;
+
+    #[derive(Clone, Debug, Default, PartialEq, Eq)]
+    struct Address(&'static str);
+
+    /// A `!Send` database client.
+    #[derive(Clone, Debug)]
+    struct Database(Rc<DatabaseInner>);
+
+    #[derive(Debug)]
+    struct DatabaseInner {
+        address: Address,
+        log: RefCell<Vec<String>>,
+    }
+
+    impl Database {
+        #[expect(clippy::future_not_send, reason = "testing `!Send` futures")]
+        async fn connect(address: Address) -> Self {
+            let db = Self(Rc::new(DatabaseInner {
+                address,
+                log: RefCell::default(),
+            }));
+            // Hold a `!Send` value across an await point.
+            let cloned = db.clone();
+            tokio::task::yield_now().await;
+            cloned.0.log.borrow_mut().push("connected".to_string());
+            db
+        }
+    }
+
+    #[test]
+    fn test_local_async_constructor() {
+        let rt = TestRuntime::new();
+        let container = LocalContainer::builder(rt.clone())
+            .with_component(Address("foo"))
+            .with_async_constructor(async |Component(address): Component<Address>| {
+                Component(Database::connect(address).await)
+            })
+            .build();
+
+        let log = rt
+            .block_on(container.call(|Component(db): Component<Database>| {
+                assert_eq!(db.0.address, Address("foo"));
+                db.0.log.borrow().clone()
+            }))
+            .unwrap();
+        assert_eq!(log, ["connected"]);
+        rt.assert_idle();
+    }
+
+    #[test]
+    fn test_local_db_constructor() {
+        let rt = TestRuntime::new();
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<Address>();
+
+        // `Address` is defined up front so that the constructor never reads its own error
+        // through `Current`.
+        let injector = Rc::new(LocalStateMap::new());
+        injector.define::<Address>();
+
+        let container = LocalContainer::builder_with(rt.clone(), injector)
+            .with_task(async move |injector: Rc<LocalStateMap>| {
+                while let Some(address) = rx.recv().await {
+                    injector.inject(Ok(address));
+                }
+                Ok(())
+            })
+            .with_async_constructor(
+                async |Component(address): Component<Address>,
+                       Current(old_db): Current<Option<Component<Database>>>| {
+                    if let Some(Component(db)) = old_db {
+                        db.0.log.borrow_mut().push("disconnected".to_string());
+                    }
+
+                    Component(Database::connect(address).await)
+                },
+            )
+            .build();
+
+        let watch_db = container.watch::<Database>();
+
+        tx.send(Address("foo")).unwrap();
+        rt.run_until_stalled();
+        let db1 = watch_db.current().unwrap();
+        assert_eq!(db1.0.address, Address("foo"));
+
+        tx.send(Address("bar")).unwrap();
+        rt.run_until_stalled();
+        let db2 = watch_db.current().unwrap();
+        assert_eq!(db2.0.address, Address("bar"));
+        assert_eq!(*db1.0.log.borrow(), ["connected", "disconnected"]);
+        rt.assert_idle();
+    }
+
+    #[test]
+    fn test_local_register_task() {
+        let rt = TestRuntime::new();
+        let container = LocalContainer::builder(rt.clone())
+            .with_async_constructor(async |Component(address): Component<Address>| {
+                Component(Database::connect(address).await)
+            })
+            .build();
+
+        let watch_db = container.watch::<Database>();
+        rt.run_until_stalled();
+        assert!(
+            watch_db
+                .current()
+                .unwrap_err()
+                .is_not_defined_for::<Address>()
+        );
+
+        let task = container.register_task(async |injector: Rc<LocalStateMap>| {
+            injector.define::<Address>();
+            injector.inject(Ok(Address("foo")));
+            Ok(())
+        });
+        rt.run_until_stalled();
+        assert_eq!(watch_db.current().unwrap().0.address, Address("foo"));
+        rt.block_on(task.join()).unwrap().unwrap();
+        rt.assert_idle();
+    }
+
+    #[test]
+    fn test_local_error_propagation_path() {
+        #[derive(Clone, Debug)]
+        struct Service(#[expect(dead_code)] Database);
+
+        let rt = TestRuntime::new();
+        let container = LocalContainer::builder(rt.clone())
+            .with_constructor(|| -> Result<Component<Address>> {
+                Err(Error::other("connection refused"))
+            })
+            .with_async_constructor(async |Component(address): Component<Address>| {
+                Component(Database::connect(address).await)
+            })
+            .with_constructor(|Component(db): Component<Database>| Component(Service(db)))
+            .build();
+
+        rt.run_until_stalled();
+        let err = container.watch::<Service>().current().unwrap_err();
+
+        assert!(err.is_other());
+        assert_eq!(
+            err.path(),
+            [
+                type_name::<Address>(),
+                type_name::<Database>(),
+                type_name::<Service>()
+            ]
+        );
+        assert_eq!(err.root_cause().to_string(), "connection refused");
+        rt.assert_idle();
+    }
+
+    #[test]
+    fn test_local_constructor_panic() {
+        let rt = TestRuntime::new();
+        let container = LocalContainer::builder(rt.clone())
+            .with_component(Address("bad"))
+            .with_constructor(|Component(address): Component<Address>| {
+                assert_ne!(address, Address("bad"), "bad address");
+                Component(address.0.len())
+            })
+            .with_async_constructor(async |Component(address): Component<Address>| {
+                assert_ne!(address, Address("bad"), "bad address");
+                Component(Database::connect(address).await)
+            })
+            .build();
+
+        rt.run_until_stalled();
+        let len = container.watch::<usize>().current();
+        let db = container.watch::<Database>().current();
+        for err in [len.map(drop).unwrap_err(), db.map(drop).unwrap_err()] {
+            assert!(err.is_panicked());
+            assert!(err.root_cause().to_string().contains("bad address"));
+        }
+        rt.assert_idle();
+    }
+
+    #[test]
+    fn test_local_retract_cascade_tuple() {
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        struct Port(u16);
+
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        struct Url(&'static str, u16);
+
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        struct Socket(&'static str, u16);
+
+        let rt = TestRuntime::new();
+        let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+
+        let container = LocalContainer::builder(rt.clone())
+            .with_constructor(
+                |(Component(address), Component(port)): (Component<Address>, Component<Port>)| {
+                    Component(Url(address.0, port.0))
+                },
+            )
+            .with_async_constructor(
+                async |(Component(address), Component(port)): (
+                    Component<Address>,
+                    Component<Port>,
+                )| { Component(Socket(address.0, port.0)) },
+            )
+            .with_task(async move |injector: Rc<LocalStateMap>| {
+                injector.inject::<Address>(Err(Error::other("unreachable")));
+                injector.inject(Ok(Port(80)));
+                let _ = rx.await;
+                injector.retract::<Port>();
+                Ok(())
+            })
+            .build();
+
+        let watch_url = container.watch::<Url>();
+        let watch_socket = container.watch::<Socket>();
+
+        rt.run_until_stalled();
+        assert!(watch_url.current().unwrap_err().is_other());
+        assert!(watch_socket.current().unwrap_err().is_other());
+
+        // `Address` is still in error, but `Port` is pending, so the outputs are retracted
+        // rather than kept as errors.
+        tx.send(()).unwrap();
+        rt.run_until_stalled();
+        assert!(watch_url.current().unwrap_err().is_pending());
+        assert!(watch_socket.current().unwrap_err().is_pending());
+        rt.assert_idle();
+    }
+}
@@ -0,0 +1,131 @@
+use std::any::TypeId;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError, Weak};
+
+use crate::Error;
+use crate::sync::event::Event;
+
+/// Something that happened to a state in an injector, received through [`Events`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum StateEvent {
+    /// A value injected into the state of a type failed validation.
+    Invalid {
+        type_id: TypeId,
+        type_name: &'static str,
+        /// The [`Error::Invalid`] describing the failure.
+        error: Error,
+        /// Whether the value was rejected, keeping the previous value of the state, instead of
+        /// being replaced by `error`.
+        rejected: bool,
+    },
+    /// The value of the state of a type has expired, and the state reverted to pending.
+    Expired {
+        type_id: TypeId,
+        type_name: &'static str,
+    },
+}
+
+#[derive(Debug, Default)]
+struct Queue {
+    events: Mutex<VecDeque<StateEvent>>,
+    closed: AtomicBool,
+    event: Event,
+}
+
+impl Queue {
+    fn pop(&self) -> Option<StateEvent> {
+        self.events
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .pop_front()
+    }
+}
+
+/// Receives [`StateEvent`]s from an injector, e.g. from [`StateMap::events`].
+///
+/// Events are buffered from the time the receiver is created until they are received, so the
+/// receiver should be polled regularly or dropped.
+///
+/// [`StateMap::events`]: crate::injector::StateMap::events
+#[derive(Debug)]
+pub struct Events {
+    queue: Arc<Queue>,
+}
+
+impl Events {
+    /// Receives the next event if one is already available.
+    pub fn try_next(&mut self) -> Option<StateEvent> {
+        self.queue.pop()
+    }
+
+    /// Waits for the next event.
+    ///
+    /// Returns `None` once the injector has been dropped and every buffered event is received.
+    pub async fn next(&mut self) -> Option<StateEvent> {
+        loop {
+            let listener = self.queue.event.listen();
+
+            if let Some(event) = self.queue.pop() {
+                return Some(event);
+            }
+            if self.queue.closed.load(Ordering::Acquire) {
+                return None;
+            }
+
+            listener.await;
+        }
+    }
+}
+
+/// Sends [`StateEvent`]s to every live [`Events`] receiver.
+#[derive(Debug, Default)]
+pub struct EventHub {
+    subscribers: Mutex<Vec<Weak<Queue>>>,
+}
+
+impl EventHub {
+    /// Creates a new receiver of the events sent from now on.
+    pub fn subscribe(&self) -> Events {
+        let queue = Arc::new(Queue::default());
+        self.subscribers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(Arc::downgrade(&queue));
+        Events { queue }
+    }
+
+    /// Sends an event to every live receiver.
+    pub fn emit(&self, event: &StateEvent) {
+        let mut subscribers = self
+            .subscribers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        subscribers.retain(|queue| {
+            let Some(queue) = queue.upgrade() else {
+                return false;
+            };
+            queue
+                .events
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push_back(event.clone());
+            queue.event.notify_all();
+            true
+        });
+    }
+}
+
+impl Drop for EventHub {
+    fn drop(&mut self) {
+        let subscribers = self
+            .subscribers
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner);
+        for queue in subscribers.iter().filter_map(Weak::upgrade) {
+            queue.closed.store(true, Ordering::Release);
+            queue.event.notify_all();
+        }
+    }
+}
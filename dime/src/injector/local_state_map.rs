@@ -0,0 +1,117 @@
+use std::any::{TypeId, type_name};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+use crate::injector::LocalInjector;
+use crate::injector::state::{LocalWatch, RawState};
+use crate::{LocalErased, Result};
+
+/// A single-threaded injector backed by [`BTreeMap`].
+///
+/// Unlike [`StateMap`](crate::injector::StateMap), values stored in this injector do not need to
+/// implement [`Send`] or [`Sync`], but the injector itself can not be shared between threads.
+#[derive(Debug, Default)]
+pub struct LocalStateMap {
+    states: RefCell<BTreeMap<TypeId, RawState<LocalErased>>>,
+}
+
+impl LocalStateMap {
+    /// Creates a new `LocalStateMap`.
+    pub const fn new() -> Self {
+        Self {
+            states: RefCell::new(BTreeMap::new()),
+        }
+    }
+
+    fn raw_state<T>(&self) -> RawState<LocalErased>
+    where
+        T: 'static,
+    {
+        self.states
+            .borrow_mut()
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| RawState::new(TypeId::of::<T>(), type_name::<T>()))
+            .clone()
+    }
+}
+
+impl LocalInjector for LocalStateMap {
+    type Watch<T: Clone + 'static> = LocalWatch<T>;
+
+    fn define<T>(&self)
+    where
+        T: 'static,
+    {
+        trace!("type" = type_name::<T>(), "define");
+        self.raw_state::<T>().define();
+    }
+
+    fn inject<T>(&self, value: Result<T>)
+    where
+        T: 'static,
+    {
+        trace!(
+            "type" = type_name::<T>(),
+            error = value.as_ref().err().map(tracing::field::debug),
+            "inject"
+        );
+        self.raw_state::<T>().inject(value.map(LocalErased::new));
+    }
+
+    fn retract<T>(&self)
+    where
+        T: 'static,
+    {
+        trace!("type" = type_name::<T>(), "retract");
+        self.raw_state::<T>().retract();
+    }
+
+    fn undefine<T>(&self)
+    where
+        T: 'static,
+    {
+        trace!("type" = type_name::<T>(), "undefine");
+        self.raw_state::<T>().undefine();
+    }
+
+    fn watch<T>(&self) -> Self::Watch<T>
+    where
+        T: Clone + 'static,
+    {
+        LocalWatch::from_raw(self.raw_state::<T>().watch())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use dime_util::runtime::TestRuntime;
+
+    use crate::injector::LocalWatch;
+
+    use super::*;
+
+    #[test]
+    fn test_inject_rc() {
+        let rt = TestRuntime::new();
+        let injector = LocalStateMap::new();
+        let mut watch = injector.watch::<Rc<String>>();
+
+        let err = rt.block_on(watch.wait()).unwrap_err();
+        assert!(err.is_not_defined_for::<Rc<String>>());
+
+        injector.define::<Rc<String>>();
+        injector.inject(Ok(Rc::new("foo".to_string())));
+        let value = rt.block_on(watch.wait()).unwrap();
+        assert_eq!(*value, "foo");
+
+        injector.inject(Ok(Rc::new("bar".to_string())));
+        let value = rt.block_on(async {
+            watch.changed().await.unwrap();
+            watch.wait().await.unwrap()
+        });
+        assert_eq!(*value, "bar");
+        rt.assert_idle();
+    }
+}
@@ -4,15 +4,25 @@ use std::any::type_name;
 use std::pin::Pin;
 
 #[doc(inline)]
-pub use dime_core::injector::{Injector, InjectorTask, Watch};
+pub use dime_core::injector::{
+    DynInjector, DynSharedWatch, DynTypedWatch, DynWatch, ErasedInjector, ErasedSelector, Injector,
+    InjectorTask, LocalInjector, LocalInjectorTask, LocalWatch, SelectInjector, Watch,
+    erase_selector,
+};
 
 use crate::Result;
 
 pub mod state;
 
+mod events;
+pub use events::{Events, StateEvent};
+
 mod state_map;
 pub use state_map::StateMap;
 
+mod local_state_map;
+pub use local_state_map::LocalStateMap;
+
 /// A dispatchable [`InjectorTask`] trait object.
 ///
 /// Use this instead of `Box<dyn InjectorTask>` to dynamically dispatch [`InjectorTask::run`].
@@ -72,6 +82,66 @@ impl<I> InjectorTask<I> for InjectorTaskObject<I> {
 
     #[inline]
     fn run(self, injector: I) -> Self::Future {
-        self.boxed.run(injector)
+        InjectorTask::run(self.boxed, injector)
+    }
+}
+
+/// A dispatchable [`LocalInjectorTask`] trait object.
+///
+/// This is the thread-local counterpart of [`InjectorTaskObject`].
+pub struct LocalInjectorTaskObject<I> {
+    #[allow(clippy::type_complexity)]
+    boxed: Box<dyn FnOnce(I) -> Pin<Box<dyn Future<Output = Result<()>>>>>,
+    concrete_type: &'static str,
+}
+
+impl<I> LocalInjectorTaskObject<I> {
+    /// Creates a new `LocalInjectorTaskObject` from a concrete task.
+    pub fn new<T>(task: T) -> Self
+    where
+        T: LocalInjectorTask<I> + 'static,
+        T::Future: 'static,
+    {
+        let wrapped_fn =
+            |injector: I| Box::pin(task.run(injector)) as Pin<Box<dyn Future<Output = Result<()>>>>;
+
+        Self {
+            boxed: Box::new(wrapped_fn),
+            concrete_type: type_name::<T>(),
+        }
+    }
+
+    /// Creates a new `LocalInjectorTaskObject` from a concrete task that returns future that
+    /// implements `Into<Pin<Box<dyn Future<Output = Result<()>>>>>`.
+    ///
+    /// This is useful to avoid extra allocation and indirection.
+    pub fn from_boxed_future<T>(task: T) -> Self
+    where
+        T: LocalInjectorTask<I> + 'static,
+        T::Future: Into<Pin<Box<dyn Future<Output = Result<()>>>>>,
+    {
+        let wrapped_fn = |injector: I| task.run(injector).into();
+
+        Self {
+            boxed: Box::new(wrapped_fn),
+            concrete_type: type_name::<T>(),
+        }
+    }
+}
+
+impl<I> std::fmt::Debug for LocalInjectorTaskObject<I> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LocalInjectorTaskObject")
+            .field("concrete_type", &self.concrete_type)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<I> LocalInjectorTask<I> for LocalInjectorTaskObject<I> {
+    type Future = Pin<Box<dyn Future<Output = Result<()>>>>;
+
+    #[inline]
+    fn run(self, injector: I) -> Self::Future {
+        (self.boxed)(injector)
     }
 }
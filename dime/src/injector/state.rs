@@ -1,21 +1,24 @@
 //! Type value states.
 
 use std::any::{TypeId, type_name};
+use std::collections::VecDeque;
 use std::marker::PhantomData;
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+use std::time::Instant;
 
-use tokio::sync::watch;
-
-use crate::{Erased, Error, Result};
+use crate::erased::{downcast, downcast_arc, downcast_local};
+use crate::sync::watch;
+use crate::{Erased, Error, LocalErased, Result};
 
 #[derive(Clone, Debug, Default)]
-enum Inner {
+pub(crate) enum Inner<E = Erased> {
     #[default]
     Undefined,
     Pending,
-    Ready(Result<Erased>),
+    Ready(Result<E>),
 }
 
-impl Inner {
+impl<E> Inner<E> {
     fn define(&mut self) -> bool {
         if matches!(self, Self::Undefined) {
             *self = Self::Pending;
@@ -25,9 +28,9 @@ impl Inner {
         }
     }
 
-    fn is_ready_and<F>(&self, f: F) -> bool
+    pub(crate) fn is_ready_and<F>(&self, f: F) -> bool
     where
-        F: FnOnce(&Result<Erased>) -> bool,
+        F: FnOnce(&Result<E>) -> bool,
     {
         match self {
             Self::Ready(result) => f(result),
@@ -36,24 +39,213 @@ impl Inner {
     }
 }
 
+/// A callback invoked with every value injected into a state, and every time the state is
+/// reverted to pending or undefined.
+type Observer<E> = Arc<dyn Fn(&Inner<E>) + Send + Sync>;
+
+/// States waiting to be passed to the observers of a state, along with the observers to pass
+/// each of them to.
+struct Dispatch<E> {
+    queue: VecDeque<(Inner<E>, Vec<Observer<E>>)>,
+    running: bool,
+}
+
+impl<E> Default for Dispatch<E> {
+    fn default() -> Self {
+        Self {
+            queue: VecDeque::new(),
+            running: false,
+        }
+    }
+}
+
+struct ObserversInner<E> {
+    list: RwLock<Vec<Observer<E>>>,
+    dispatch: Mutex<Dispatch<E>>,
+}
+
+/// The [`Observer`]s of a state, shared between clones of a [`RawState`].
+///
+/// States are queued while the state is locked and passed to the observers after it is
+/// unlocked, one at a time. This way, observers see states in the order they were set, and may
+/// inject into or observe any state, including their own.
+struct Observers<E>(Arc<ObserversInner<E>>);
+
+impl<E> Clone for Observers<E> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+
+impl<E> Default for Observers<E> {
+    fn default() -> Self {
+        Self(Arc::new(ObserversInner {
+            list: RwLock::default(),
+            dispatch: Mutex::default(),
+        }))
+    }
+}
+
+impl<E> std::fmt::Debug for Observers<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // TODO: use non-poisoning alternative
+        let len = self.0.list.read().unwrap().len();
+        f.debug_struct("Observers")
+            .field("len", &len)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<E> Observers<E>
+where
+    E: Clone,
+{
+    /// Queues `value` for every current observer. Must be called while the state is locked.
+    fn enqueue(&self, value: &Inner<E>) {
+        // TODO: use non-poisoning alternative
+        let observers = self.0.list.read().unwrap().clone();
+        if !observers.is_empty() {
+            let item = (value.clone(), observers);
+            self.0.dispatch.lock().unwrap().queue.push_back(item);
+        }
+    }
+
+    /// Adds an observer, queueing `current` for it if the state holds a value. Must be called
+    /// while the state is locked.
+    fn add(&self, observer: Observer<E>, current: Option<Inner<E>>) {
+        // TODO: use non-poisoning alternative
+        self.0.list.write().unwrap().push(Arc::clone(&observer));
+        if let Some(current) = current {
+            let item = (current, vec![observer]);
+            self.0.dispatch.lock().unwrap().queue.push_back(item);
+        }
+    }
+
+    /// Passes the queued values to their observers, unless another call is already doing so, in
+    /// which case that call also passes the values queued by this one.
+    fn dispatch(&self) {
+        /// Releases the dispatch even if an observer panics.
+        struct Running<'a, E>(&'a Mutex<Dispatch<E>>);
+
+        impl<E> Drop for Running<'_, E> {
+            fn drop(&mut self) {
+                self.0
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .running = false;
+            }
+        }
+
+        {
+            // TODO: use non-poisoning alternative
+            let mut dispatch = self.0.dispatch.lock().unwrap();
+            if dispatch.running {
+                return;
+            }
+            dispatch.running = true;
+        }
+        let _running = Running(&self.0.dispatch);
+
+        loop {
+            // TODO: use non-poisoning alternative
+            let Some((value, observers)) = self.0.dispatch.lock().unwrap().queue.pop_front() else {
+                break;
+            };
+            for observer in observers {
+                observer(&value);
+            }
+        }
+    }
+}
+
+/// What a state serves to readers after an error is injected into it.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorPolicy {
+    /// The error replaces the previous value for every reader.
+    #[default]
+    Replace,
+    /// The error is visible to every reader, but the last `Ok` value is kept and still served
+    /// to readers that opt in with [`current_last_ok`](crate::injector::Watch::current_last_ok)
+    /// or [`wait_last_ok`](crate::injector::Watch::wait_last_ok).
+    StaleOnError,
+}
+
+/// The last `Ok` value of a state, kept according to its [`ErrorPolicy`].
+#[derive(Debug)]
+struct LastOkSlot<E> {
+    policy: ErrorPolicy,
+    value: Option<E>,
+}
+
+impl<E> Default for LastOkSlot<E> {
+    fn default() -> Self {
+        Self {
+            policy: ErrorPolicy::default(),
+            value: None,
+        }
+    }
+}
+
+/// A [`LastOkSlot`] shared between a [`RawState`] and its watches.
+type SharedLastOk<E> = Arc<RwLock<LastOkSlot<E>>>;
+
+/// A callback that checks a value injected into a state.
+type Check<E> = Box<dyn Fn(&E) -> Result<()> + Send + Sync>;
+
+/// A check run on every `Ok` value injected into a state.
+pub(crate) struct Validator<E> {
+    check: Check<E>,
+    reject: bool,
+}
+
+impl<E> Validator<E> {
+    /// Creates a validator. If `reject` is `true`, invalid values are dropped instead of being
+    /// replaced by [`Error::Invalid`].
+    pub(crate) fn new<F>(check: F, reject: bool) -> Self
+    where
+        F: Fn(&E) -> Result<()> + Send + Sync + 'static,
+    {
+        Self {
+            check: Box::new(check),
+            reject,
+        }
+    }
+}
+
+impl<E> std::fmt::Debug for Validator<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Validator")
+            .field("reject", &self.reject)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The [`Validator`] of a state, shared between clones of a [`RawState`].
+type SharedValidator<E> = Arc<RwLock<Option<Arc<Validator<E>>>>>;
+
 /// A state of a given type in [`Injector`](crate::injector::Injector).
 ///
-/// This is a *raw* version of the state, which works with [`Erased`] values.
+/// This is a *raw* version of the state, which works with [`Erased`] (or [`LocalErased`]) values.
 /// To work with values of concrete types, consider using [`State`].
 #[derive(Debug, Clone)]
-pub(crate) struct RawState {
-    inner: watch::Sender<Inner>,
+pub(crate) struct RawState<E = Erased> {
+    inner: watch::Sender<Inner<E>>,
+    observers: Observers<E>,
+    last_ok: SharedLastOk<E>,
+    validator: SharedValidator<E>,
+    expires_at: Arc<Mutex<Option<Instant>>>,
     type_id: TypeId,
     type_name: &'static str,
 }
 
 /// Watches for type-erased values of a given type in [`Injector`](crate::injector::Injector).
 ///
-/// This is a *raw* version of the watch, which works with [`Erased`] values.
+/// This is a *raw* version of the watch, which works with [`Erased`] (or [`LocalErased`]) values.
 /// To work with values of concrete types, consider using [`Watch`].
 #[derive(Debug, Clone)]
-pub(crate) struct RawWatch {
-    inner: watch::Receiver<Inner>,
+pub(crate) struct RawWatch<E = Erased> {
+    inner: watch::Receiver<Inner<E>>,
+    last_ok: SharedLastOk<E>,
     type_id: TypeId,
     type_name: &'static str,
 }
@@ -79,12 +271,43 @@ pub struct Watch<T> {
     _marker: PhantomData<T>,
 }
 
-impl RawState {
-    fn new_inner(inner: Inner, type_id: TypeId, type_name: &'static str) -> Self {
+/// Watches for shared values of a given type in [`Injector`](crate::injector::Injector).
+///
+/// Unlike [`Watch`], the values are returned as [`Arc`]s, so `T` does not need to implement
+/// [`Clone`].
+#[derive(Debug, Clone)]
+pub struct SharedWatch<T> {
+    raw: RawWatch,
+    _marker: PhantomData<T>,
+}
+
+/// Watches for type-erased values of a given type in
+/// [`ErasedInjector`](crate::injector::ErasedInjector).
+#[derive(Debug, Clone)]
+pub struct ErasedWatch {
+    raw: RawWatch,
+}
+
+/// Watches for values of a given type in [`LocalInjector`](crate::injector::LocalInjector).
+#[derive(Debug, Clone)]
+pub struct LocalWatch<T> {
+    raw: RawWatch<LocalErased>,
+    _marker: PhantomData<T>,
+}
+
+impl<E> RawState<E>
+where
+    E: Clone,
+{
+    fn new_inner(inner: Inner<E>, type_id: TypeId, type_name: &'static str) -> Self {
         let (tx, _) = watch::channel(inner);
 
         Self {
             inner: tx,
+            observers: Observers::default(),
+            last_ok: SharedLastOk::default(),
+            validator: SharedValidator::default(),
+            expires_at: Arc::default(),
             type_id,
             type_name,
         }
@@ -97,55 +320,289 @@ impl RawState {
 
     /// Tells the state a type might be injected to it.
     pub(crate) fn define(&self) {
-        self.inner.send_if_modified(Inner::define);
+        let modified = self.inner.send_if_modified(|inner| {
+            let modified = inner.define();
+            if modified {
+                self.observers.enqueue(inner);
+            }
+            modified
+        });
+
+        if modified {
+            self.observers.dispatch();
+        }
+    }
+
+    /// Reverts the state to pending if it holds a value.
+    pub(crate) fn retract(&self) {
+        self.reset(Inner::Pending, |inner| matches!(inner, Inner::Ready(_)));
+    }
+
+    /// Reverts the state to undefined.
+    pub(crate) fn undefine(&self) {
+        self.reset(Inner::Undefined, |inner| !matches!(inner, Inner::Undefined));
+    }
+
+    /// Replaces the state with `to` if `f` returns `true` for the current state, dropping the
+    /// kept `Ok` value and the expiry of the current value.
+    fn reset<F>(&self, to: Inner<E>, f: F)
+    where
+        F: FnOnce(&Inner<E>) -> bool,
+    {
+        let mut to = Some(to);
+        let modified = self.inner.send_if_modified(|inner| {
+            if !f(inner) {
+                return false;
+            }
+            // TODO: use non-poisoning alternative
+            self.last_ok.write().unwrap().value = None;
+            *self.expires_at.lock().unwrap() = None;
+            let to = to.take().expect("state should only be taken once");
+            self.observers.enqueue(&to);
+            *inner = to;
+            true
+        });
+
+        if modified {
+            self.observers.dispatch();
+        }
     }
 
     /// Injects a value into the state.
     ///
-    /// # Panics
+    /// The type of the value is not checked. See
+    /// [`ErasedInjector::inject_erased`](crate::injector::ErasedInjector::inject_erased) for a
+    /// checked alternative.
+    pub(crate) fn inject(&self, value: Result<E>) {
+        self.inject_if(value, |_| true);
+    }
+
+    /// Injects a value into the state if `f` returns `true` for the current state.
+    ///
+    /// Observers are only notified if the value is actually injected.
+    pub(crate) fn inject_if<F>(&self, value: Result<E>, f: F)
+    where
+        F: FnOnce(&Inner<E>) -> bool,
+    {
+        self.inject_inner(value, f, None);
+    }
+
+    /// Injects a value into the state that reverts the state to pending at `expires_at`, unless
+    /// another value is injected before then.
+    ///
+    /// Returns `true` if the value is actually injected. The state is only reverted by calling
+    /// [`expire`](Self::expire).
+    pub(crate) fn inject_expiring(&self, value: Result<E>, expires_at: Instant) -> bool {
+        self.inject_inner(value, |_| true, Some(expires_at))
+    }
+
+    /// Reverts the state to pending if it holds a value injected with `expires_at` as its expiry.
+    ///
+    /// Returns `true` if the state is reverted.
+    pub(crate) fn expire(&self, expires_at: Instant) -> bool {
+        let modified = self.inner.send_if_modified(|inner| {
+            if !matches!(inner, Inner::Ready(_)) {
+                return false;
+            }
+            // TODO: use non-poisoning alternative
+            let mut current = self.expires_at.lock().unwrap();
+            if *current != Some(expires_at) {
+                return false;
+            }
+            *current = None;
+            drop(current);
+            self.observers.enqueue(&Inner::Pending);
+            *inner = Inner::Pending;
+            true
+        });
+
+        if modified {
+            self.observers.dispatch();
+        }
+
+        modified
+    }
+
+    fn inject_inner<F>(&self, value: Result<E>, f: F, expires_at: Option<Instant>) -> bool
+    where
+        F: FnOnce(&Inner<E>) -> bool,
+    {
+        let Some(value) = self.validate(value) else {
+            return false;
+        };
+
+        let mut value = Some(value);
+        let modified = self.inner.send_if_modified(|inner| {
+            if f(inner) {
+                let value = value.take().expect("value should only be taken once");
+                // Update the slot while the state is locked, so that readers that see an error
+                // also see the last `Ok` value before it.
+                if let Ok(value) = &value {
+                    // TODO: use non-poisoning alternative
+                    let mut last_ok = self.last_ok.write().unwrap();
+                    if last_ok.policy == ErrorPolicy::StaleOnError {
+                        last_ok.value = Some(value.clone());
+                    }
+                }
+                // TODO: use non-poisoning alternative
+                *self.expires_at.lock().unwrap() = expires_at;
+                // Queue the value for the observers while the state is locked, so that they see
+                // values in the order they were injected.
+                let value = Inner::Ready(value);
+                self.observers.enqueue(&value);
+                *inner = value;
+                true
+            } else {
+                false
+            }
+        });
+
+        if modified {
+            self.observers.dispatch();
+        }
+
+        modified
+    }
+
+    /// Registers a callback to be invoked with every value injected into the state, and every
+    /// time the state is reverted to pending or undefined.
+    ///
+    /// If the state already holds a value, the callback is invoked with it first. The callback is
+    /// invoked after the state is unlocked, either by the caller or by a call that is already
+    /// invoking the observers of the state.
+    pub(crate) fn observe<F>(&self, observer: F)
+    where
+        F: Fn(&Inner<E>) + Send + Sync + 'static,
+    {
+        // Hold the state lock while adding the observer so that an injection can not slip between
+        // reading the current value and adding the observer without being observed.
+        let inner = self.inner.borrow();
+        let current = match &*inner {
+            Inner::Ready(_) => Some(inner.clone()),
+            _ => None,
+        };
+        self.observers.add(Arc::new(observer), current);
+        drop(inner);
+
+        self.observers.dispatch();
+    }
+
+    /// Sets the validator run on every `Ok` value injected into the state, replacing the previous
+    /// one.
+    pub(crate) fn set_validator(&self, validator: Validator<E>) {
+        // TODO: use non-poisoning alternative
+        *self.validator.write().unwrap() = Some(Arc::new(validator));
+    }
+
+    /// Runs the validator of the state on `value`, returning `None` if the value is rejected.
+    fn validate(&self, value: Result<E>) -> Option<Result<E>> {
+        // Do not hold the lock while running the validator, as it may run arbitrary code.
+        // TODO: use non-poisoning alternative
+        let validator = self.validator.read().unwrap().clone();
+        let (Some(validator), Ok(inner)) = (validator, &value) else {
+            return Some(value);
+        };
+
+        match (validator.check)(inner) {
+            Ok(()) => Some(value),
+            Err(_) if validator.reject => None,
+            Err(reason) => Some(Err(Error::Invalid {
+                type_name: self.type_name,
+                reason: Arc::new(reason),
+            })),
+        }
+    }
+
+    /// Sets what the state serves to readers after an error is injected into it.
     ///
-    /// See [`Injector::inject_by_type_id`](crate::injector::Injector::inject_by_type_id).
-    pub(crate) fn inject(&self, value: Result<Erased>) {
-        self.inner.send_replace(Inner::Ready(value));
+    /// Switching to [`ErrorPolicy::StaleOnError`] keeps the current value if it is `Ok`, while
+    /// switching to [`ErrorPolicy::Replace`] drops the kept value.
+    pub(crate) fn set_error_policy(&self, policy: ErrorPolicy) {
+        let inner = self.inner.borrow();
+        // TODO: use non-poisoning alternative
+        let mut last_ok = self.last_ok.write().unwrap();
+        last_ok.policy = policy;
+        last_ok.value = match (policy, &*inner) {
+            (ErrorPolicy::Replace, _) => None,
+            (ErrorPolicy::StaleOnError, Inner::Ready(Ok(value))) => Some(value.clone()),
+            (ErrorPolicy::StaleOnError, _) => last_ok.value.take(),
+        };
+    }
+
+    /// Returns what the state serves to readers after an error is injected into it.
+    pub(crate) fn error_policy(&self) -> ErrorPolicy {
+        // TODO: use non-poisoning alternative
+        self.last_ok.read().unwrap().policy
+    }
+
+    /// Returns the name of the type of the state.
+    pub(crate) const fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// Returns the current value of the state if it holds an `Ok` value.
+    pub(crate) fn current_ok(&self) -> Option<E> {
+        match &*self.inner.borrow() {
+            Inner::Ready(Ok(value)) => Some(value.clone()),
+            _ => None,
+        }
     }
 
     /// Returns a watch for this state.
-    pub(crate) fn watch(&self) -> RawWatch {
+    pub(crate) fn watch(&self) -> RawWatch<E> {
         let rx = self.inner.subscribe();
-        RawWatch::new(rx, self.type_id, self.type_name)
+        RawWatch::new(rx, Arc::clone(&self.last_ok), self.type_id, self.type_name)
     }
 }
 
-impl RawWatch {
-    const fn new(inner: watch::Receiver<Inner>, type_id: TypeId, type_name: &'static str) -> Self {
+/// Returns `true` if the error only tells that a value is not available (yet), which
+/// [`wait_always`](crate::injector::Watch::wait_always) should wait past.
+const fn is_unavailable(err: &Error) -> bool {
+    err.is_not_defined() || err.is_pending()
+}
+
+#[expect(
+    clippy::future_not_send,
+    reason = "the futures are `Send` if `E` is `Send` and `Sync`"
+)]
+impl<E> RawWatch<E>
+where
+    E: Clone,
+{
+    const fn new(
+        inner: watch::Receiver<Inner<E>>,
+        last_ok: SharedLastOk<E>,
+        type_id: TypeId,
+        type_name: &'static str,
+    ) -> Self {
         Self {
             inner,
+            last_ok,
             type_id,
             type_name,
         }
     }
 
-    pub(crate) fn current(&self) -> Result<Erased> {
+    pub(crate) fn current(&self) -> Result<E> {
         match &*self.inner.borrow() {
-            Inner::Undefined | Inner::Pending => {
-                Err(Error::NotDefined(self.type_id, self.type_name))
-            }
+            Inner::Undefined => Err(Error::NotDefined(self.type_id, self.type_name)),
+            Inner::Pending => Err(Error::Pending(self.type_id, self.type_name)),
             Inner::Ready(erased) => erased.clone(),
         }
     }
 
-    pub(crate) fn current_optional(&self) -> Result<Option<Erased>> {
+    pub(crate) fn current_optional(&self) -> Result<Option<E>> {
         match &*self.inner.borrow() {
             Inner::Undefined | Inner::Pending => Ok(None),
             Inner::Ready(erased) => erased.clone().map(Some),
         }
     }
 
-    pub(crate) async fn wait(&mut self) -> Result<Erased> {
+    pub(crate) async fn wait(&mut self) -> Result<E> {
         self.inner
             .wait_for(|state| !matches!(state, Inner::Pending))
             .await
-            .map_err(Error::other)
+            .map_err(Error::from)
             .and_then(|state| match &*state {
                 Inner::Undefined => Err(Error::NotDefined(self.type_id, self.type_name)),
                 Inner::Pending => unreachable!(),
@@ -153,11 +610,11 @@ impl RawWatch {
             })
     }
 
-    pub(crate) async fn wait_optional(&mut self) -> Result<Option<Erased>> {
+    pub(crate) async fn wait_optional(&mut self) -> Result<Option<E>> {
         self.inner
             .wait_for(|state| !matches!(state, Inner::Pending))
             .await
-            .map_err(Error::other)
+            .map_err(Error::from)
             .and_then(|state| match &*state {
                 Inner::Undefined => Ok(None),
                 Inner::Pending => unreachable!(),
@@ -165,32 +622,68 @@ impl RawWatch {
             })
     }
 
-    pub(crate) async fn wait_always(&mut self) -> Result<Erased> {
+    pub(crate) async fn wait_always(&mut self) -> Result<E> {
         self.inner
             .wait_for(|state| {
-                state.is_ready_and(|result| !matches!(result, Err(err) if err.is_not_defined()))
+                state.is_ready_and(|result| !matches!(result, Err(err) if is_unavailable(err)))
             })
             .await
-            .map_err(Error::other)
+            .map_err(Error::from)
             .and_then(|state| match &*state {
                 Inner::Ready(result) => result.clone(),
                 _ => unreachable!(),
             })
     }
 
-    pub(crate) async fn wait_ok(&mut self) -> Result<Erased> {
+    pub(crate) async fn wait_ok(&mut self) -> Result<E> {
         self.inner
             .wait_for(|state| state.is_ready_and(Result::is_ok))
             .await
-            .map_err(Error::other)
+            .map_err(Error::from)
             .and_then(|state| match &*state {
                 Inner::Ready(Ok(value)) => Ok(value.clone()),
                 _ => unreachable!(),
             })
     }
 
+    /// Returns the kept `Ok` value if the state holds an error.
+    fn stale(last_ok: &SharedLastOk<E>, state: &Inner<E>) -> Option<E> {
+        match state {
+            // TODO: use non-poisoning alternative
+            Inner::Ready(Err(_)) => last_ok.read().unwrap().value.clone(),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn current_last_ok(&self) -> Result<E> {
+        let state = self.inner.borrow();
+        if let Some(value) = Self::stale(&self.last_ok, &state) {
+            return Ok(value);
+        }
+
+        match &*state {
+            Inner::Undefined => Err(Error::NotDefined(self.type_id, self.type_name)),
+            Inner::Pending => Err(Error::Pending(self.type_id, self.type_name)),
+            Inner::Ready(result) => result.clone(),
+        }
+    }
+
+    pub(crate) async fn wait_last_ok(&mut self) -> Result<E> {
+        let last_ok = Arc::clone(&self.last_ok);
+        self.inner
+            .wait_for(|state| {
+                state.is_ready_and(Result::is_ok) || Self::stale(&last_ok, state).is_some()
+            })
+            .await
+            .map_err(Error::from)
+            .map(|state| match &*state {
+                Inner::Ready(Ok(value)) => value.clone(),
+                _ => Self::stale(&last_ok, &state).expect("a value should have been kept"),
+            })
+    }
+
     pub(crate) async fn changed(&mut self) -> Result<()> {
-        self.inner.changed().await.map_err(Error::other)?;
+        self.inner.changed().await?;
 
         Ok(())
     }
@@ -198,7 +691,7 @@ impl RawWatch {
 
 impl<T> Default for State<T>
 where
-    T: Clone + Send + Sync + 'static,
+    T: Send + Sync + 'static,
 {
     fn default() -> Self {
         Self::new()
@@ -207,7 +700,7 @@ where
 
 impl<T> State<T>
 where
-    T: Clone + Send + Sync + 'static,
+    T: Send + Sync + 'static,
 {
     /// Creates a new, undefined state
     pub fn new() -> Self {
@@ -248,12 +741,46 @@ where
         self.raw.inject(value.map(Erased::new));
     }
 
+    /// Injects a value that is already behind an [`Arc`] into the state.
+    #[inline]
+    pub fn inject_shared(&self, value: Result<Arc<T>>) {
+        trace!(
+            "type" = type_name::<T>(),
+            error = value.as_ref().err().map(tracing::field::debug),
+            "inject"
+        );
+        self.raw.inject(value.map(Erased::from_arc));
+    }
+
+    /// Reverts the state to pending if it holds a value.
+    #[inline]
+    pub fn retract(&self) {
+        trace!("type" = type_name::<T>(), "retract");
+        self.raw.retract();
+    }
+
+    /// Reverts the state to undefined.
+    #[inline]
+    pub fn undefine(&self) {
+        trace!("type" = type_name::<T>(), "undefine");
+        self.raw.undefine();
+    }
+
     /// Returns a watch for this state.
     #[inline]
-    pub fn watch(&self) -> Watch<T> {
+    pub fn watch(&self) -> Watch<T>
+    where
+        T: Clone,
+    {
         Watch::from_raw(self.raw.watch())
     }
 
+    /// Returns a watch for shared values of this state.
+    #[inline]
+    pub fn watch_shared(&self) -> SharedWatch<T> {
+        SharedWatch::from_raw(self.raw.watch())
+    }
+
     /// Returns a reference to this state.
     #[inline]
     pub fn as_ref(&self) -> StateRef<'_, T> {
@@ -263,7 +790,7 @@ where
 
 impl<'a, T> StateRef<'a, T>
 where
-    T: Clone + Send + Sync + 'static,
+    T: Send + Sync + 'static,
 {
     /// Creates a state from [`RawState`].
     ///
@@ -298,11 +825,45 @@ where
         self.raw.inject(value.map(Erased::new));
     }
 
+    /// Injects a value that is already behind an [`Arc`] into the state.
+    #[inline]
+    pub fn inject_shared(&self, value: Result<Arc<T>>) {
+        trace!(
+            "type" = type_name::<T>(),
+            error = value.as_ref().err().map(tracing::field::debug),
+            "inject"
+        );
+        self.raw.inject(value.map(Erased::from_arc));
+    }
+
+    /// Reverts the state to pending if it holds a value.
+    #[inline]
+    pub fn retract(&self) {
+        trace!("type" = type_name::<T>(), "retract");
+        self.raw.retract();
+    }
+
+    /// Reverts the state to undefined.
+    #[inline]
+    pub fn undefine(&self) {
+        trace!("type" = type_name::<T>(), "undefine");
+        self.raw.undefine();
+    }
+
     /// Returns a watch for this state.
     #[inline]
-    pub fn watch(&self) -> Watch<T> {
+    pub fn watch(&self) -> Watch<T>
+    where
+        T: Clone,
+    {
         Watch::from_raw(self.raw.watch())
     }
+
+    /// Returns a watch for shared values of this state.
+    #[inline]
+    pub fn watch_shared(&self) -> SharedWatch<T> {
+        SharedWatch::from_raw(self.raw.watch())
+    }
 }
 
 impl<T> Watch<T>
@@ -311,13 +872,9 @@ where
 {
     /// Creates a watch from [`RawWatch`].
     ///
-    /// # Panics
-    ///
-    /// Panic may occur if `T` and the underlying type of the values observed by [`RawWatch`] does
-    /// not match.
-    pub(crate) fn from_raw(raw: RawWatch) -> Self {
-        debug_assert_eq!(TypeId::of::<T>(), raw.type_id);
-
+    /// If `T` and the underlying type of the values observed by [`RawWatch`] do not match, reading
+    /// from the watch returns [`Error::TypeMismatch`].
+    pub(crate) const fn from_raw(raw: RawWatch) -> Self {
         Self {
             raw,
             _marker: PhantomData,
@@ -325,30 +882,72 @@ where
     }
 }
 
+impl<T> Watch<T>
+where
+    T: Send + Sync + 'static,
+{
+    /// Returns the current value as an [`Arc`], without cloning the value itself.
+    ///
+    /// # Errors
+    ///
+    /// See [`current`](crate::injector::Watch::current).
+    pub fn borrow(&self) -> Result<Arc<T>> {
+        self.raw.current().and_then(downcast_arc::<T>)
+    }
+
+    /// Waits until a value is available like [`wait`](crate::injector::Watch::wait), and returns
+    /// it as an [`Arc`].
+    ///
+    /// # Errors
+    ///
+    /// See [`wait`](crate::injector::Watch::wait).
+    pub async fn wait_ref(&mut self) -> Result<Arc<T>> {
+        trace!("type" = type_name::<T>(), "wait_ref");
+        self.raw.wait().await.and_then(downcast_arc::<T>)
+    }
+
+    /// Waits until a result is available like
+    /// [`wait_always`](crate::injector::Watch::wait_always), and returns it as an [`Arc`].
+    ///
+    /// # Errors
+    ///
+    /// See [`wait_always`](crate::injector::Watch::wait_always).
+    pub async fn wait_always_ref(&mut self) -> Result<Arc<T>> {
+        trace!("type" = type_name::<T>(), "wait_always_ref");
+        self.raw.wait_always().await.and_then(downcast_arc::<T>)
+    }
+
+    /// Waits until an `Ok` value is available like [`wait_ok`](crate::injector::Watch::wait_ok),
+    /// and returns it as an [`Arc`].
+    ///
+    /// # Errors
+    ///
+    /// See [`wait_ok`](crate::injector::Watch::wait_ok).
+    pub async fn wait_ok_ref(&mut self) -> Result<Arc<T>> {
+        trace!("type" = type_name::<T>(), "wait_ok_ref");
+        self.raw.wait_ok().await.and_then(downcast_arc::<T>)
+    }
+}
+
 impl<T> crate::injector::Watch for Watch<T>
 where
-    T: 'static + Send,
+    T: Clone + Send + Sync + 'static,
 {
     type Ty = T;
 
     fn current(&self) -> Result<T> {
-        self.raw
-            .current()
-            .map(|value| value.downcast::<T>().unwrap())
+        self.raw.current().and_then(downcast::<T>)
     }
 
     fn current_optional(&self) -> Result<Option<T>> {
         self.raw
             .current_optional()
-            .map(|value| value.map(|value| value.downcast::<T>().unwrap()))
+            .and_then(|value| value.map(downcast::<T>).transpose())
     }
 
     async fn wait(&mut self) -> Result<T> {
         trace!("type" = type_name::<T>(), "wait");
-        self.raw
-            .wait()
-            .await
-            .map(|value| value.downcast::<T>().unwrap())
+        self.raw.wait().await.and_then(downcast::<T>)
     }
 
     async fn wait_optional(&mut self) -> Result<Option<T>> {
@@ -356,23 +955,223 @@ where
         self.raw
             .wait_optional()
             .await
-            .map(|value| value.map(|value| value.downcast::<T>().unwrap()))
+            .and_then(|value| value.map(downcast::<T>).transpose())
     }
 
     async fn wait_always(&mut self) -> Result<T> {
         trace!("type" = type_name::<T>(), "wait_always");
+        self.raw.wait_always().await.and_then(downcast::<T>)
+    }
+
+    async fn wait_ok(&mut self) -> Result<Self::Ty> {
+        trace!("type" = type_name::<T>(), "wait_ok");
+        self.raw.wait_ok().await.and_then(downcast::<T>)
+    }
+
+    fn current_last_ok(&self) -> Result<T> {
+        self.raw.current_last_ok().and_then(downcast::<T>)
+    }
+
+    async fn wait_last_ok(&mut self) -> Result<T> {
+        trace!("type" = type_name::<T>(), "wait_last_ok");
+        self.raw.wait_last_ok().await.and_then(downcast::<T>)
+    }
+
+    async fn changed(&mut self) -> Result<()> {
+        trace!("type" = type_name::<T>(), "wait_changed");
+        self.raw.changed().await
+    }
+}
+
+impl<T> SharedWatch<T>
+where
+    T: 'static,
+{
+    /// Creates a watch from [`RawWatch`].
+    ///
+    /// If `T` and the underlying type of the values observed by [`RawWatch`] do not match, reading
+    /// from the watch returns [`Error::TypeMismatch`].
+    pub(crate) const fn from_raw(raw: RawWatch) -> Self {
+        Self {
+            raw,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> crate::injector::Watch for SharedWatch<T>
+where
+    T: Send + Sync + 'static,
+{
+    type Ty = Arc<T>;
+
+    fn current(&self) -> Result<Arc<T>> {
+        self.raw.current().and_then(downcast_arc::<T>)
+    }
+
+    fn current_optional(&self) -> Result<Option<Arc<T>>> {
+        self.raw
+            .current_optional()
+            .and_then(|value| value.map(downcast_arc::<T>).transpose())
+    }
+
+    async fn wait(&mut self) -> Result<Arc<T>> {
+        trace!("type" = type_name::<T>(), "wait");
+        self.raw.wait().await.and_then(downcast_arc::<T>)
+    }
+
+    async fn wait_optional(&mut self) -> Result<Option<Arc<T>>> {
+        trace!("type" = type_name::<T>(), "wait_optional");
         self.raw
-            .wait_always()
+            .wait_optional()
             .await
-            .map(|value| value.downcast::<T>().unwrap())
+            .and_then(|value| value.map(downcast_arc::<T>).transpose())
     }
 
-    async fn wait_ok(&mut self) -> Result<Self::Ty> {
+    async fn wait_always(&mut self) -> Result<Arc<T>> {
+        trace!("type" = type_name::<T>(), "wait_always");
+        self.raw.wait_always().await.and_then(downcast_arc::<T>)
+    }
+
+    async fn wait_ok(&mut self) -> Result<Arc<T>> {
         trace!("type" = type_name::<T>(), "wait_ok");
+        self.raw.wait_ok().await.and_then(downcast_arc::<T>)
+    }
+
+    fn current_last_ok(&self) -> Result<Arc<T>> {
+        self.raw.current_last_ok().and_then(downcast_arc::<T>)
+    }
+
+    async fn wait_last_ok(&mut self) -> Result<Arc<T>> {
+        trace!("type" = type_name::<T>(), "wait_last_ok");
+        self.raw.wait_last_ok().await.and_then(downcast_arc::<T>)
+    }
+
+    async fn changed(&mut self) -> Result<()> {
+        trace!("type" = type_name::<T>(), "wait_changed");
+        self.raw.changed().await
+    }
+}
+
+impl ErasedWatch {
+    pub(crate) const fn from_raw(raw: RawWatch) -> Self {
+        Self { raw }
+    }
+
+    /// Returns the [`TypeId`] of the values observed by the watch.
+    pub const fn type_id(&self) -> TypeId {
+        self.raw.type_id
+    }
+
+    /// Returns the name of the type of the values observed by the watch.
+    pub const fn type_name(&self) -> &'static str {
+        self.raw.type_name
+    }
+}
+
+impl crate::injector::Watch for ErasedWatch {
+    type Ty = Erased;
+
+    fn current(&self) -> Result<Erased> {
+        self.raw.current()
+    }
+
+    fn current_optional(&self) -> Result<Option<Erased>> {
+        self.raw.current_optional()
+    }
+
+    async fn wait(&mut self) -> Result<Erased> {
+        trace!("type" = self.raw.type_name, "wait");
+        self.raw.wait().await
+    }
+
+    async fn wait_optional(&mut self) -> Result<Option<Erased>> {
+        trace!("type" = self.raw.type_name, "wait_optional");
+        self.raw.wait_optional().await
+    }
+
+    async fn wait_always(&mut self) -> Result<Erased> {
+        trace!("type" = self.raw.type_name, "wait_always");
+        self.raw.wait_always().await
+    }
+
+    async fn wait_ok(&mut self) -> Result<Erased> {
+        trace!("type" = self.raw.type_name, "wait_ok");
+        self.raw.wait_ok().await
+    }
+
+    fn current_last_ok(&self) -> Result<Erased> {
+        self.raw.current_last_ok()
+    }
+
+    async fn wait_last_ok(&mut self) -> Result<Erased> {
+        trace!("type" = self.raw.type_name, "wait_last_ok");
+        self.raw.wait_last_ok().await
+    }
+
+    async fn changed(&mut self) -> Result<()> {
+        trace!("type" = self.raw.type_name, "wait_changed");
+        self.raw.changed().await
+    }
+}
+
+impl<T> LocalWatch<T>
+where
+    T: 'static,
+{
+    /// Creates a watch from [`RawWatch`].
+    ///
+    /// If `T` and the underlying type of the values observed by [`RawWatch`] do not match, reading
+    /// from the watch returns [`Error::TypeMismatch`].
+    pub(crate) const fn from_raw(raw: RawWatch<LocalErased>) -> Self {
+        Self {
+            raw,
+            _marker: PhantomData,
+        }
+    }
+}
+
+#[expect(
+    clippy::future_not_send,
+    reason = "local watches are not meant to be sent between threads"
+)]
+impl<T> crate::injector::LocalWatch for LocalWatch<T>
+where
+    T: Clone + 'static,
+{
+    type Ty = T;
+
+    fn current(&self) -> Result<T> {
+        self.raw.current().and_then(downcast_local::<T>)
+    }
+
+    fn current_optional(&self) -> Result<Option<T>> {
+        self.raw
+            .current_optional()
+            .and_then(|value| value.map(downcast_local::<T>).transpose())
+    }
+
+    async fn wait(&mut self) -> Result<T> {
+        trace!("type" = type_name::<T>(), "wait");
+        self.raw.wait().await.and_then(downcast_local::<T>)
+    }
+
+    async fn wait_optional(&mut self) -> Result<Option<T>> {
+        trace!("type" = type_name::<T>(), "wait_optional");
         self.raw
-            .wait_ok()
+            .wait_optional()
             .await
-            .map(|value| value.downcast::<T>().unwrap())
+            .and_then(|value| value.map(downcast_local::<T>).transpose())
+    }
+
+    async fn wait_always(&mut self) -> Result<T> {
+        trace!("type" = type_name::<T>(), "wait_always");
+        self.raw.wait_always().await.and_then(downcast_local::<T>)
+    }
+
+    async fn wait_ok(&mut self) -> Result<Self::Ty> {
+        trace!("type" = type_name::<T>(), "wait_ok");
+        self.raw.wait_ok().await.and_then(downcast_local::<T>)
     }
 
     async fn changed(&mut self) -> Result<()> {
@@ -380,3 +1179,62 @@ where
         self.raw.changed().await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use crate::injector::{LocalWatch as _, Watch as _};
+
+    use super::*;
+
+    fn mismatched<E: Clone>(value: E) -> RawState<E> {
+        let raw = RawState::new(TypeId::of::<u32>(), type_name::<u32>());
+        raw.inject(Ok(value));
+        raw
+    }
+
+    #[tokio::test]
+    async fn test_type_mismatch() {
+        let raw = mismatched(Erased::new("foo"));
+
+        let mut watch = Watch::<u32>::from_raw(raw.watch());
+        let err = watch.current().unwrap_err();
+        assert!(err.is_type_mismatch());
+        assert_eq!(
+            err.to_string(),
+            "expected a value of type `u32`, found `&str`"
+        );
+        assert!(watch.current_optional().unwrap_err().is_type_mismatch());
+        assert!(watch.wait().await.unwrap_err().is_type_mismatch());
+        assert!(watch.wait_ok().await.unwrap_err().is_type_mismatch());
+        assert!(watch.borrow().unwrap_err().is_type_mismatch());
+        assert!(watch.wait_ref().await.unwrap_err().is_type_mismatch());
+
+        let mut watch = SharedWatch::<u32>::from_raw(raw.watch());
+        assert!(watch.current().unwrap_err().is_type_mismatch());
+        assert!(watch.wait_always().await.unwrap_err().is_type_mismatch());
+    }
+
+    #[tokio::test]
+    async fn test_closed() {
+        let raw = RawState::<Erased>::new(TypeId::of::<u32>(), type_name::<u32>());
+        raw.define();
+
+        let mut watch = Watch::<u32>::from_raw(raw.watch());
+        assert!(watch.current().unwrap_err().is_pending());
+
+        drop(raw);
+        assert!(watch.changed().await.unwrap_err().is_closed());
+        assert!(watch.wait().await.unwrap_err().is_closed());
+    }
+
+    #[tokio::test]
+    async fn test_local_type_mismatch() {
+        let raw = mismatched(LocalErased::from_rc(Rc::new("foo")));
+
+        let mut watch = LocalWatch::<u32>::from_raw(raw.watch());
+        assert!(watch.current().unwrap_err().is_type_mismatch());
+        assert!(watch.wait().await.unwrap_err().is_type_mismatch());
+    }
+}
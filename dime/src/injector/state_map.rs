@@ -1,10 +1,22 @@
 use std::any::{TypeId, type_name};
-use std::collections::BTreeMap;
-use std::sync::RwLock;
+use std::collections::{BTreeMap, BTreeSet};
+use std::future::poll_fn;
+use std::pin::{Pin, pin};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError, RwLock};
+use std::task::Poll;
+use std::time::Instant;
 
-use crate::Result;
-use crate::injector::Injector;
-use crate::injector::state::{self, RawState, RawWatch, StateRef, Watch};
+use crate::component::unwind::catch_unwind;
+use crate::injector::events::EventHub;
+use crate::injector::state::{
+    self, ErrorPolicy, Inner, RawState, RawWatch, StateRef, Validator, Watch,
+};
+use crate::injector::{
+    ErasedInjector, ErasedSelector, Events, Injector, SelectInjector, StateEvent, erase_selector,
+};
+use crate::runtime::Timer;
+use crate::sync::event::Event;
+use crate::{Erased, Error, Result};
 
 /// A Simple injector backed by [`BTreeMap`].
 ///
@@ -139,6 +151,9 @@ use crate::injector::state::{self, RawState, RawWatch, StateRef, Watch};
 #[derive(Debug)]
 pub struct StateMap {
     states: RwLock<BTreeMap<TypeId, RawState>>,
+    events: OnceLock<Arc<EventHub>>,
+    expiries: Mutex<BTreeSet<(Instant, TypeId)>>,
+    expiries_changed: Event,
 }
 
 impl Default for StateMap {
@@ -152,9 +167,144 @@ impl StateMap {
     pub const fn new() -> Self {
         Self {
             states: RwLock::new(BTreeMap::new()),
+            events: OnceLock::new(),
+            expiries: Mutex::new(BTreeSet::new()),
+            expiries_changed: Event::new(),
         }
     }
 
+    /// Reverts the states whose values have expired by `now` to pending, and returns the instant
+    /// at which the next value expires.
+    ///
+    /// A [`StateEvent::Expired`] is sent to [`events`](Self::events) for every reverted state.
+    pub fn expire_due(&self, now: Instant) -> Option<Instant> {
+        let (due, next) = {
+            let mut expiries = self.expiries.lock().unwrap_or_else(PoisonError::into_inner);
+            let mut due = Vec::new();
+            while let Some(&(expires_at, type_id)) = expiries.first()
+                && expires_at <= now
+            {
+                expiries.pop_first();
+                due.push((expires_at, type_id));
+            }
+            (due, expiries.first().map(|&(expires_at, _)| expires_at))
+        };
+
+        for (expires_at, type_id) in due {
+            let state = self
+                .states
+                .read()
+                .unwrap_or_else(PoisonError::into_inner)
+                .get(&type_id)
+                .cloned();
+            let Some(state) = state else {
+                continue;
+            };
+            if state.expire(expires_at)
+                && let Some(events) = self.events.get()
+            {
+                events.emit(&StateEvent::Expired {
+                    type_id,
+                    type_name: state.type_name(),
+                });
+            }
+        }
+
+        next
+    }
+
+    /// Expires values injected with [`Injector::inject_expiring`] when they are due,
+    /// using `timer` to wait for them.
+    ///
+    /// The returned future never completes, so it should be spawned as a task, e.g. with
+    /// [`SimpleContainerBuilder::with_expiry`](crate::container::SimpleContainerBuilder::with_expiry).
+    pub async fn run_expiry<Tm>(&self, timer: Tm)
+    where
+        Tm: Timer,
+    {
+        loop {
+            let mut changed = self.expiries_changed.listen();
+            let Some(next) = self.expire_due(timer.now()) else {
+                changed.await;
+                continue;
+            };
+
+            let mut sleep = pin!(timer.sleep_until(next));
+            poll_fn(|cx| {
+                if Pin::new(&mut changed).poll(cx).is_ready() {
+                    return Poll::Ready(());
+                }
+                sleep.as_mut().poll(cx)
+            })
+            .await;
+        }
+    }
+
+    fn event_hub(&self) -> &Arc<EventHub> {
+        self.events.get_or_init(Arc::default)
+    }
+
+    /// Returns a receiver of the [`StateEvent`]s that happen in this map from now on.
+    pub fn events(&self) -> Events {
+        self.event_hub().subscribe()
+    }
+
+    pub(crate) fn set_validator<T, F>(&self, validator: F, reject: bool)
+    where
+        T: Send + Sync + 'static,
+        F: Fn(&T) -> Result<()> + Send + Sync + 'static,
+    {
+        let events = Arc::clone(self.event_hub());
+        let check = move |value: &Erased| {
+            let Some(value) = value.downcast_ref::<T>() else {
+                return Ok(());
+            };
+            // A panicking validator must not take down the task injecting the value.
+            catch_unwind(|| validator(value))
+                .flatten()
+                .inspect_err(|reason| {
+                    events.emit(&StateEvent::Invalid {
+                        type_id: TypeId::of::<T>(),
+                        type_name: type_name::<T>(),
+                        error: Error::invalid::<T>(reason.clone()),
+                        rejected: reject,
+                    });
+                })
+        };
+
+        self.raw_with_state_by_type_id(TypeId::of::<T>(), type_name::<T>(), |raw| {
+            raw.set_validator(Validator::new(check, reject));
+        });
+    }
+
+    /// Validates every value injected into the state of the given type.
+    ///
+    /// Values for which `validator` returns an error are replaced by [`Error::Invalid`], and a
+    /// [`StateEvent::Invalid`] is sent to [`events`](Self::events). A panic of `validator` is
+    /// treated as an [`Error::Panicked`] returned by it.
+    #[must_use]
+    pub fn with_validator<T, F>(self, validator: F) -> Self
+    where
+        T: Send + Sync + 'static,
+        F: Fn(&T) -> Result<()> + Send + Sync + 'static,
+    {
+        self.set_validator(validator, false);
+        self
+    }
+
+    /// Validates every value injected into the state of the given type, like
+    /// [`with_validator`](Self::with_validator), but drops invalid values so that the state keeps
+    /// its previous value.
+    #[must_use]
+    pub fn with_rejecting_validator<T, F>(self, validator: F) -> Self
+    where
+        T: Send + Sync + 'static,
+        F: Fn(&T) -> Result<()> + Send + Sync + 'static,
+    {
+        self.set_validator(validator, true);
+        self
+    }
+
     fn raw_with_state_by_type_id<F>(&self, type_id: TypeId, type_name: &'static str, f: F)
     where
         F: FnOnce(&RawState),
@@ -186,7 +336,7 @@ impl StateMap {
     /// exists.
     pub fn with_state<T, F>(&self, f: F)
     where
-        T: Clone + Send + Sync + 'static,
+        T: Send + Sync + 'static,
         F: FnOnce(StateRef<'_, T>),
     {
         self.raw_with_state_by_type_id(TypeId::of::<T>(), type_name::<T>(), |raw| {
@@ -242,15 +392,53 @@ impl StateMap {
 
         Watch::from_raw(raw)
     }
+
+    /// Sets what the state of the given type serves to readers after an error is injected into
+    /// it.
+    ///
+    /// With [`ErrorPolicy::StaleOnError`], a failed reconstruction is still visible to every
+    /// reader, but readers that opt in with
+    /// [`current_last_ok`](crate::injector::Watch::current_last_ok) or
+    /// [`wait_last_ok`](crate::injector::Watch::wait_last_ok) (e.g. through
+    /// [`LastOk`](crate::component::LastOk)) keep getting the last `Ok` value.
+    pub fn set_error_policy<T>(&self, policy: ErrorPolicy)
+    where
+        T: Send + Sync + 'static,
+    {
+        self.raw_with_state_by_type_id(TypeId::of::<T>(), type_name::<T>(), |raw| {
+            raw.set_error_policy(policy);
+        });
+    }
+
+    /// Returns what the state of the given type serves to readers after an error is injected
+    /// into it.
+    pub fn error_policy<T>(&self) -> ErrorPolicy
+    where
+        T: Send + Sync + 'static,
+    {
+        let mut policy = ErrorPolicy::default();
+        self.raw_with_state_by_type_id(TypeId::of::<T>(), type_name::<T>(), |raw| {
+            policy = raw.error_policy();
+        });
+        policy
+    }
+
+    fn raw_state_by_type_id(&self, type_id: TypeId, type_name: &'static str) -> RawState {
+        let mut state = None;
+        self.raw_with_state_by_type_id(type_id, type_name, |raw| state = Some(raw.clone()));
+        state.expect("state should have been created")
+    }
 }
 
 impl Injector for StateMap {
-    type Watch<T: Send + 'static> = state::Watch<T>;
+    type Watch<T: Clone + Send + Sync + 'static> = state::Watch<T>;
+
+    type SharedWatch<T: Send + Sync + 'static> = state::SharedWatch<T>;
 
     #[inline]
     fn define<T>(&self)
     where
-        T: Clone + Send + Sync + 'static,
+        T: Send + Sync + 'static,
     {
         self.with_state::<T, _>(|state| state.define());
     }
@@ -258,11 +446,67 @@ impl Injector for StateMap {
     #[inline]
     fn inject<T>(&self, value: Result<T>)
     where
-        T: Clone + Send + Sync + 'static,
+        T: Send + Sync + 'static,
     {
         self.with_state(|state| state.inject(value));
     }
 
+    #[inline]
+    fn inject_shared<T>(&self, value: Result<Arc<T>>)
+    where
+        T: Send + Sync + 'static,
+    {
+        self.with_state(|state| state.inject_shared(value));
+    }
+
+    /// Injects a value of a given type that expires at `expires_at`.
+    ///
+    /// Expiry is driven by [`run_expiry`](Self::run_expiry), or manually by
+    /// [`expire_due`](Self::expire_due).
+    #[inline]
+    fn inject_expiring<T>(&self, value: Result<T>, expires_at: Instant)
+    where
+        T: Send + Sync + 'static,
+    {
+        // This can not fail, as the value is always of type `T`.
+        let _ = self.inject_expiring_erased(
+            TypeId::of::<T>(),
+            type_name::<T>(),
+            value.map(Erased::new),
+            expires_at,
+        );
+    }
+
+    #[inline]
+    fn inject_shared_expiring<T>(&self, value: Result<Arc<T>>, expires_at: Instant)
+    where
+        T: Send + Sync + 'static,
+    {
+        // This can not fail, as the value is always of type `T`.
+        let _ = self.inject_expiring_erased(
+            TypeId::of::<T>(),
+            type_name::<T>(),
+            value.map(Erased::from_arc),
+            expires_at,
+        );
+    }
+
+    #[inline]
+    fn retract<T>(&self)
+    where
+        T: Send + Sync + 'static,
+    {
+        self.with_state::<T, _>(|state| state.retract());
+    }
+
+    #[inline]
+    fn undefine<T>(&self)
+    where
+        T: Send + Sync + 'static,
+    {
+        self.with_state::<T, _>(|state| state.undefine());
+    }
+
     #[inline]
     fn watch<T>(&self) -> Self::Watch<T>
     where
@@ -272,6 +516,144 @@ impl Injector for StateMap {
             self.raw_with_state_and_watch_by_type_id(TypeId::of::<T>(), type_name::<T>(), |_| {});
         Watch::from_raw(raw)
     }
+
+    #[inline]
+    fn watch_shared<T>(&self) -> Self::SharedWatch<T>
+    where
+        T: Send + Sync + 'static,
+    {
+        let raw =
+            self.raw_with_state_and_watch_by_type_id(TypeId::of::<T>(), type_name::<T>(), |_| {});
+        state::SharedWatch::from_raw(raw)
+    }
+}
+
+impl SelectInjector for StateMap {
+    #[inline]
+    fn select<T, U, F>(&self, selector: F)
+    where
+        T: Send + Sync + 'static,
+        U: PartialEq + Send + Sync + 'static,
+        F: Fn(&T) -> U + Send + Sync + 'static,
+    {
+        self.select_erased(
+            (TypeId::of::<T>(), type_name::<T>()),
+            (TypeId::of::<U>(), type_name::<U>()),
+            erase_selector(selector),
+        );
+    }
+}
+
+impl ErasedInjector for StateMap {
+    type ErasedWatch = state::ErasedWatch;
+
+    #[inline]
+    fn define_erased(&self, type_id: TypeId, type_name: &'static str) {
+        self.raw_with_state_by_type_id(type_id, type_name, RawState::define);
+    }
+
+    fn inject_erased(
+        &self,
+        type_id: TypeId,
+        type_name: &'static str,
+        value: Result<Erased>,
+    ) -> Result<()> {
+        if let Ok(value) = &value
+            && value.as_any().type_id() != type_id
+        {
+            return Err(Error::TypeMismatch {
+                expected: type_name,
+                found: value.type_name(),
+            });
+        }
+
+        self.raw_with_state_by_type_id(type_id, type_name, |raw| raw.inject(value));
+        Ok(())
+    }
+
+    fn inject_expiring_erased(
+        &self,
+        type_id: TypeId,
+        type_name: &'static str,
+        value: Result<Erased>,
+        expires_at: Instant,
+    ) -> Result<()> {
+        if let Ok(value) = &value
+            && value.as_any().type_id() != type_id
+        {
+            return Err(Error::TypeMismatch {
+                expected: type_name,
+                found: value.type_name(),
+            });
+        }
+
+        let mut injected = false;
+        self.raw_with_state_by_type_id(type_id, type_name, |raw| {
+            injected = raw.inject_expiring(value, expires_at);
+        });
+
+        if injected {
+            self.expiries
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .insert((expires_at, type_id));
+            self.expiries_changed.notify_all();
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn retract_erased(&self, type_id: TypeId, type_name: &'static str) {
+        self.raw_with_state_by_type_id(type_id, type_name, RawState::retract);
+    }
+
+    #[inline]
+    fn undefine_erased(&self, type_id: TypeId, type_name: &'static str) {
+        self.raw_with_state_by_type_id(type_id, type_name, RawState::undefine);
+    }
+
+    #[inline]
+    fn watch_erased(&self, type_id: TypeId, type_name: &'static str) -> Self::ErasedWatch {
+        let raw = self.raw_with_state_and_watch_by_type_id(type_id, type_name, |_| {});
+        state::ErasedWatch::from_raw(raw)
+    }
+
+    fn select_erased(
+        &self,
+        source: (TypeId, &'static str),
+        target: (TypeId, &'static str),
+        selector: ErasedSelector,
+    ) {
+        let target = self.raw_state_by_type_id(target.0, target.1);
+        target.define();
+
+        // The observer only holds the states, not the map, so that it does not keep the map
+        // alive. Values of the source are observed one at a time, so the current value of the
+        // target can not change between reading it and injecting the projected value.
+        let source = self.raw_state_by_type_id(source.0, source.1);
+        source.observe(move |value: &Inner| {
+            // Record the target on the errors injected into it, like constructors do.
+            let propagate = |err: Error| Err(err.propagated_through_erased(target.type_name()));
+            match value {
+                Inner::Ready(Ok(value)) => {
+                    let current = target.current_ok();
+                    // A panicking selector must not unwind into the injecting task.
+                    match catch_unwind(|| selector(value, current.as_ref())) {
+                        Ok(Some(projected)) => target.inject(projected.or_else(propagate)),
+                        Ok(None) => {}
+                        Err(err) => target.inject(propagate(err)),
+                    }
+                }
+                Inner::Ready(Err(err)) => target.inject(propagate(err.clone())),
+                // Do not keep serving the projection of a value that is no longer available.
+                Inner::Pending => {
+                    target.define();
+                    target.retract();
+                }
+                Inner::Undefined => target.undefine(),
+            }
+        });
+    }
 }
 
 #[cfg(test)]
@@ -280,15 +662,14 @@ mod tests {
     use std::sync::atomic::{AtomicBool, Ordering};
     use std::time::Duration;
 
-    use tokio::time::timeout;
+    use dime_util::runtime::TestRuntime;
 
     use crate::Error;
     use crate::injector::Watch;
+    use crate::runtime::{Runtime, Task};
 
     use super::*;
 
-    const TIMEOUT: Duration = Duration::from_millis(500);
-
     #[derive(Clone, Debug, Default, PartialEq, Eq)]
     struct Address(&'static str);
 
@@ -322,21 +703,17 @@ mod tests {
         }
     }
 
-    #[tokio::test]
-    async fn test_inject_db() {
+    #[test]
+    fn test_inject_db() {
+        let rt = TestRuntime::new();
         let injector = Arc::new(StateMap::new());
 
-        let mut watch_db = injector.watch::<Database>();
-
-        timeout(TIMEOUT, async {
-            let err = watch_db.wait().await.unwrap_err();
-            assert!(err.is_not_defined_for::<Database>());
-        })
-        .await
-        .unwrap();
+        let watch_db = injector.watch::<Database>();
+        let err = watch_db.current().unwrap_err();
+        assert!(err.is_not_defined_for::<Database>());
 
         let cloned = injector.clone();
-        tokio::spawn(async move {
+        rt.spawn(async move {
             let injector = cloned;
 
             injector.define::<Database>();
@@ -363,32 +740,383 @@ mod tests {
         });
 
         injector.inject(Ok(Address("foo")));
-        let db1 = timeout(TIMEOUT, async {
-            watch_db.changed().await.unwrap();
-            watch_db.wait().await.unwrap()
-        })
-        .await
-        .unwrap();
+        rt.run_until_stalled();
+        let db1 = watch_db.current().unwrap();
         assert_eq!(db1.address(), &Address("foo"));
         assert!(db1.is_connected());
 
         injector.inject(Ok(Address("bar")));
-        let db2 = timeout(TIMEOUT, async {
-            watch_db.changed().await.unwrap();
-            watch_db.wait().await.unwrap()
-        })
-        .await
-        .unwrap();
+        rt.run_until_stalled();
+        let db2 = watch_db.current().unwrap();
         assert_eq!(db2.address(), &Address("bar"));
         assert!(!db1.is_connected());
 
         injector.inject::<Address>(Err(Error::other("something went wrong")));
-        let err = timeout(TIMEOUT, async {
-            watch_db.changed().await.unwrap();
-            watch_db.wait().await.unwrap_err()
-        })
-        .await
-        .unwrap();
-        assert!(err.is_other());
+        rt.run_until_stalled();
+        assert!(watch_db.current().unwrap_err().is_other());
+    }
+
+    #[test]
+    fn test_borrow() {
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        struct Counter(i32);
+
+        let rt = TestRuntime::new();
+        let injector = StateMap::new();
+
+        let watch = injector.watch::<Counter>();
+        assert!(watch.borrow().unwrap_err().is_not_defined_for::<Counter>());
+
+        injector.define::<Counter>();
+        let cloned = injector.watch::<Counter>();
+        let task = rt.spawn(async move {
+            let mut watch = cloned;
+            watch.wait_ref().await.map(|counter| counter.0)
+        });
+
+        rt.run_until_stalled();
+        let counter = Arc::new(Counter(1));
+        injector.inject_shared(Ok(Arc::clone(&counter)));
+        let got = rt.block_on(task.join()).unwrap().unwrap();
+        assert_eq!(got, 1);
+
+        let value = watch.borrow().unwrap();
+        assert_eq!(*value, Counter(1));
+        assert!(Arc::ptr_eq(&value, &counter));
+
+        // Holding a value does not block injections into the state.
+        injector.inject::<Counter>(Err(Error::other("something went wrong")));
+        assert!(watch.borrow().unwrap_err().is_other());
+        assert_eq!(*value, Counter(1));
+
+        let injector = Arc::new(injector);
+        let cloned = Arc::clone(&injector);
+        let task = rt.spawn(async move {
+            let mut watch = cloned.watch::<Counter>();
+            watch.wait_ok_ref().await.map(|counter| counter.0)
+        });
+
+        rt.run_until_stalled();
+        injector.inject(Ok(Counter(2)));
+        let got = rt.block_on(task.join()).unwrap().unwrap();
+        assert_eq!(got, 2);
+    }
+
+    #[test]
+    fn test_inject_erased() {
+        let rt = TestRuntime::new();
+        let injector = StateMap::new();
+        let (type_id, type_name) = (TypeId::of::<Address>(), type_name::<Address>());
+
+        let mut watch = injector.watch::<Address>();
+        let mut watch_erased = injector.watch_erased(type_id, type_name);
+        injector.define_erased(type_id, type_name);
+
+        injector
+            .inject_erased(type_id, type_name, Ok(Erased::new(Address("foo"))))
+            .unwrap();
+        let got = rt.block_on(watch.wait()).unwrap();
+        assert_eq!(got, Address("foo"));
+        let got = rt.block_on(watch_erased.wait()).unwrap();
+        assert_eq!(got.downcast::<Address>().unwrap(), Address("foo"));
+
+        let err = injector
+            .inject_erased(type_id, type_name, Ok(Erased::new("bar")))
+            .unwrap_err();
+        assert!(err.is_type_mismatch());
+        assert_eq!(watch.current().unwrap(), Address("foo"));
+
+        injector
+            .inject_erased(
+                type_id,
+                type_name,
+                Err(Error::other("something went wrong")),
+            )
+            .unwrap();
+        assert!(watch_erased.current().unwrap_err().is_other());
+    }
+
+    #[test]
+    fn test_dyn_injector() {
+        use crate::injector::DynInjector;
+
+        let rt = TestRuntime::new();
+        let injector: Arc<dyn DynInjector> = Arc::new(StateMap::new());
+
+        let mut watch = injector.watch::<Address>();
+        let mut watch_shared = injector.watch_shared::<Address>();
+        injector.define::<Address>();
+        assert!(watch.current().unwrap_err().is_pending());
+
+        let cloned = Arc::clone(&injector);
+        rt.spawn(async move { cloned.inject(Ok(Address("foo"))) });
+        let got = rt.block_on(watch.wait()).unwrap();
+        assert_eq!(got, Address("foo"));
+
+        injector.inject_shared(Ok(Arc::new(Address("bar"))));
+        let got = rt.block_on(async {
+            watch_shared.changed().await.unwrap();
+            watch_shared.wait().await.unwrap()
+        });
+        assert_eq!(*got, Address("bar"));
+
+        let err = injector
+            .inject_dyn(
+                TypeId::of::<Address>(),
+                type_name::<Address>(),
+                Ok(Erased::new("baz")),
+            )
+            .unwrap_err();
+        assert!(err.is_type_mismatch());
+    }
+
+    #[test]
+    fn test_stale_on_error() {
+        use crate::component::{Component, LastOk, WatchFrom};
+
+        let rt = TestRuntime::new();
+        let injector = StateMap::new();
+        injector.define::<Address>();
+
+        let mut watch = injector.watch::<Address>();
+        let mut watch_last_ok = LastOk::<Component<Address>>::watch_from(&injector);
+
+        injector.inject(Ok(Address("foo")));
+        injector.inject::<Address>(Err(Error::other(std::io::Error::other("bad"))));
+        assert!(watch.current_last_ok().unwrap_err().is_other());
+
+        injector.set_error_policy::<Address>(ErrorPolicy::StaleOnError);
+        assert_eq!(
+            injector.error_policy::<Address>(),
+            ErrorPolicy::StaleOnError
+        );
+        injector.inject(Ok(Address("foo")));
+        injector.inject::<Address>(Err(Error::other(std::io::Error::other("bad"))));
+
+        assert!(watch.current().unwrap_err().is_other());
+        assert_eq!(watch.current_last_ok().unwrap(), Address("foo"));
+        assert_eq!(
+            watch_last_ok.current().unwrap(),
+            LastOk(Component(Address("foo")))
+        );
+        rt.block_on(async {
+            assert!(watch.wait().await.unwrap_err().is_other());
+            assert_eq!(watch.wait_last_ok().await.unwrap(), Address("foo"));
+            assert_eq!(
+                watch_last_ok.wait().await.unwrap(),
+                LastOk(Component(Address("foo")))
+            );
+        });
+
+        injector.inject(Ok(Address("bar")));
+        assert_eq!(watch.current_last_ok().unwrap(), Address("bar"));
+
+        injector.set_error_policy::<Address>(ErrorPolicy::Replace);
+        injector.inject::<Address>(Err(Error::other(std::io::Error::other("bad"))));
+        assert!(watch.current_last_ok().unwrap_err().is_other());
+    }
+
+    #[test]
+    fn test_validator() {
+        fn validate(address: &Address) -> Result<()> {
+            if address.0.is_empty() {
+                return Err(Error::other("empty address"));
+            }
+            Ok(())
+        }
+
+        let rt = TestRuntime::new();
+        let injector = StateMap::new()
+            .with_validator::<Address, _>(validate)
+            .with_rejecting_validator::<Database, _>(|db: &Database| validate(db.address()));
+        let mut events = injector.events();
+
+        let watch = injector.watch::<Address>();
+        injector.inject(Ok(Address("foo")));
+        assert_eq!(watch.current().unwrap(), Address("foo"));
+        assert!(events.try_next().is_none());
+
+        injector.inject(Ok(Address("")));
+        let err = watch.current().unwrap_err();
+        assert!(err.is_invalid());
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "invalid value of type `{}`: empty address",
+                type_name::<Address>()
+            )
+        );
+        let event = rt.block_on(events.next()).unwrap();
+        assert!(matches!(
+            event,
+            StateEvent::Invalid { type_id, error, rejected: false, .. }
+                if type_id == TypeId::of::<Address>() && error.is_invalid()
+        ));
+
+        let watch = injector.watch::<Database>();
+        injector.inject(Ok(Database::connect(Address("foo"))));
+        injector.inject(Ok(Database::connect(Address(""))));
+        assert_eq!(watch.current().unwrap().address(), &Address("foo"));
+        let event = rt.block_on(events.next()).unwrap();
+        assert!(matches!(event, StateEvent::Invalid { rejected: true, .. }));
+
+        drop(injector);
+        assert!(rt.block_on(events.next()).is_none());
+    }
+
+    #[test]
+    fn test_validator_panic() {
+        let rt = TestRuntime::new();
+        let injector = StateMap::new().with_validator::<usize, _>(|len: &usize| {
+            assert_ne!(*len, 0, "empty length");
+            Ok(())
+        });
+        let mut events = injector.events();
+
+        let watch = injector.watch::<usize>();
+        injector.inject(Ok(0_usize));
+        let err = watch.current().unwrap_err();
+        assert!(err.is_invalid());
+        assert!(err.to_string().contains("empty length"));
+        let event = rt.block_on(events.next()).unwrap();
+        assert!(matches!(
+            event,
+            StateEvent::Invalid {
+                rejected: false,
+                ..
+            }
+        ));
+
+        injector.inject(Ok(3_usize));
+        assert_eq!(watch.current().unwrap(), 3);
+    }
+
+    #[test]
+    fn test_expire_due() {
+        let injector = StateMap::new();
+        let now = Instant::now();
+        let watch = injector.watch::<Address>();
+        injector.select(|address: &Address| address.0.len());
+        let watch_len = injector.watch::<usize>();
+
+        injector.inject_expiring(Ok(Address("foo")), now + Duration::from_secs(10));
+        assert_eq!(
+            injector.expire_due(now),
+            Some(now + Duration::from_secs(10))
+        );
+        assert_eq!(watch.current().unwrap(), Address("foo"));
+        assert_eq!(watch_len.current().unwrap(), 3);
+
+        // Components selected from an expired value expire along with it.
+        assert_eq!(injector.expire_due(now + Duration::from_secs(10)), None);
+        assert!(watch.current().unwrap_err().is_pending());
+        assert!(watch_len.current().unwrap_err().is_pending());
+
+        // Injecting another value cancels the expiry of the previous one.
+        injector.inject_expiring(Ok(Address("foo")), now + Duration::from_secs(20));
+        injector.inject(Ok(Address("bar")));
+        assert_eq!(injector.expire_due(now + Duration::from_secs(20)), None);
+        assert_eq!(watch.current().unwrap(), Address("bar"));
+    }
+
+    #[test]
+    fn test_select_concurrent() {
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        struct Count(u32);
+
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        struct Doubled(u32);
+
+        let injector = Arc::new(StateMap::new());
+        injector.select(|count: &Count| Doubled(count.0 * 2));
+
+        let threads: Vec<_> = (0..4)
+            .map(|thread| {
+                let injector = Arc::clone(&injector);
+                std::thread::spawn(move || {
+                    for i in 0..1000 {
+                        injector.inject(Ok(Count(thread * 1000 + i)));
+                    }
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        // The derived value follows the last injected value, whichever thread injected it.
+        let count = injector.watch::<Count>().current().unwrap();
+        let doubled = injector.watch::<Doubled>().current().unwrap();
+        assert_eq!(doubled, Doubled(count.0 * 2));
+    }
+
+    #[test]
+    fn test_select_reentrant() {
+        #[derive(Clone, Debug, PartialEq, Eq)]
+        struct Port(u16);
+
+        let injector = Arc::new(StateMap::new());
+        injector.inject(Ok(Address("foo")));
+
+        // A selector may observe its own state.
+        injector.select(|address: &Address| address.clone());
+
+        // A selector may register another selector, which sees the current value first.
+        let cloned = Arc::clone(&injector);
+        injector.select(move |address: &Address| {
+            cloned.select(|port: &Port| port.0);
+            Port(u16::try_from(address.0.len()).unwrap())
+        });
+
+        injector.inject(Ok(Address("barbaz")));
+        assert_eq!(
+            injector.watch::<Address>().current().unwrap(),
+            Address("barbaz")
+        );
+        assert_eq!(injector.watch::<Port>().current().unwrap(), Port(6));
+        assert_eq!(injector.watch::<u16>().current().unwrap(), 6);
+    }
+
+    #[test]
+    fn test_select_retract() {
+        let injector = Arc::new(StateMap::new());
+        injector.select(|pair: &(u32, u32)| pair.0);
+        let watch = injector.watch::<u32>();
+
+        injector.inject::<(u32, u32)>(Ok((1, 2)));
+        assert_eq!(watch.current().unwrap(), 1);
+
+        // The derived value does not outlive the value it was selected from.
+        injector.retract::<(u32, u32)>();
+        assert!(watch.current().unwrap_err().is_pending());
+
+        injector.inject::<(u32, u32)>(Ok((1, 3)));
+        assert_eq!(watch.current().unwrap(), 1);
+
+        injector.undefine::<(u32, u32)>();
+        assert!(watch.current().unwrap_err().is_not_defined_for::<u32>());
+
+        injector.define::<(u32, u32)>();
+        assert!(watch.current().unwrap_err().is_pending());
+    }
+
+    #[test]
+    fn test_select_dyn_injector() {
+        use crate::component::Select;
+        use crate::injector::{DynInjector, InjectorTask};
+
+        let rt = TestRuntime::new();
+        let injector: Arc<dyn DynInjector> = Arc::new(StateMap::new());
+        let select = Select::new(|address: &Address| address.0.len());
+        rt.block_on(select.run(Arc::clone(&injector))).unwrap();
+
+        let mut watch = injector.watch::<usize>();
+        assert!(watch.current().unwrap_err().is_pending());
+
+        injector.inject(Ok(Address("foo")));
+        assert_eq!(rt.block_on(watch.wait()).unwrap(), 3);
+
+        injector.inject(Err::<Address, _>(Error::other("something went wrong")));
+        assert!(watch.current().unwrap_err().is_other());
     }
 }
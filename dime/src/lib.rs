@@ -354,8 +354,15 @@
 pub(crate) mod macros;
 
 #[doc(inline)]
-pub use dime_core::{Erased, Error, Injector, Result, Runtime, erased, error, runtime};
+pub use dime_core::{
+    Erased, Error, Injector, LocalErased, LocalInjector, LocalRuntime, Result, Runtime, Timer,
+    erased, error, runtime,
+};
 
 pub mod component;
 pub mod container;
 pub mod injector;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+
+mod sync;
@@ -0,0 +1,138 @@
+//! A notification primitive backed by a list of wakers.
+
+use std::pin::Pin;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::task::{Context, Poll, Waker};
+
+/// Notifies any number of asynchronous tasks waiting on it.
+///
+/// To avoid missing notifications, a listener should be created with [`listen`](Self::listen)
+/// *before* checking the condition it is waiting for, and awaited only if the condition is not
+/// yet met.
+#[derive(Debug, Default)]
+pub struct Event {
+    listeners: Mutex<Listeners>,
+}
+
+#[derive(Debug, Default)]
+struct Listeners {
+    entries: Vec<Option<Entry>>,
+    free: Vec<usize>,
+}
+
+#[derive(Debug, Default)]
+struct Entry {
+    notified: bool,
+    waker: Option<Waker>,
+}
+
+impl Event {
+    /// Creates a new `Event`.
+    pub const fn new() -> Self {
+        Self {
+            listeners: Mutex::new(Listeners {
+                entries: Vec::new(),
+                free: Vec::new(),
+            }),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Listeners> {
+        self.listeners
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Registers a listener that completes on the next call to [`notify_all`](Self::notify_all).
+    pub fn listen(&self) -> EventListener<'_> {
+        let mut listeners = self.lock();
+        let entry = Some(Entry::default());
+
+        let key = if let Some(key) = listeners.free.pop() {
+            listeners.entries[key] = entry;
+            key
+        } else {
+            listeners.entries.push(entry);
+            listeners.entries.len() - 1
+        };
+
+        EventListener {
+            event: self,
+            key: Some(key),
+        }
+    }
+
+    /// Notifies every registered listener.
+    pub fn notify_all(&self) {
+        let wakers: Vec<Waker> = {
+            let mut listeners = self.lock();
+            listeners
+                .entries
+                .iter_mut()
+                .flatten()
+                .filter_map(|entry| {
+                    entry.notified = true;
+                    entry.waker.take()
+                })
+                .collect()
+        };
+
+        // Wake outside of the lock, as wakers may run arbitrary code.
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+}
+
+impl Listeners {
+    fn remove(&mut self, key: usize) -> Option<Entry> {
+        let entry = self.entries[key].take();
+        self.free.push(key);
+        entry
+    }
+}
+
+/// A future that completes once its [`Event`] is notified.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct EventListener<'a> {
+    event: &'a Event,
+    key: Option<usize>,
+}
+
+impl Future for EventListener<'_> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let Some(key) = self.key else {
+            return Poll::Ready(());
+        };
+
+        let mut listeners = self.event.lock();
+        let entry = listeners.entries[key]
+            .as_mut()
+            .expect("entry of a live listener should be present");
+
+        if entry.notified {
+            listeners.remove(key);
+            drop(listeners);
+            self.key = None;
+            return Poll::Ready(());
+        }
+
+        match &mut entry.waker {
+            Some(waker) => waker.clone_from(cx.waker()),
+            None => entry.waker = Some(cx.waker().clone()),
+        }
+
+        Poll::Pending
+    }
+}
+
+impl Drop for EventListener<'_> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            self.event.lock().remove(key);
+        }
+    }
+}
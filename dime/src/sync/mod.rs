@@ -0,0 +1,4 @@
+//! Runtime-agnostic synchronization primitives.
+
+pub mod event;
+pub mod watch;
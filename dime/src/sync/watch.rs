@@ -0,0 +1,317 @@
+//! A single-value channel that notifies receivers of changes.
+//!
+//! This is a runtime-agnostic counterpart of `tokio::sync::watch`, implementing only what the
+//! injector states need.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard};
+
+use crate::sync::event::Event;
+
+#[derive(Debug)]
+struct Shared<T> {
+    value: RwLock<T>,
+    /// Incremented every time the value is modified. Only modified while holding the write lock
+    /// of `value`.
+    version: AtomicUsize,
+    senders: AtomicUsize,
+    event: Event,
+}
+
+impl<T> Shared<T> {
+    fn read(&self) -> RwLockReadGuard<'_, T> {
+        self.value.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn is_closed(&self) -> bool {
+        self.senders.load(Ordering::Acquire) == 0
+    }
+}
+
+/// Sends values to the associated [`Receiver`]s.
+#[derive(Debug)]
+pub struct Sender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+/// Receives values from the associated [`Sender`]s.
+#[derive(Debug)]
+pub struct Receiver<T> {
+    shared: Arc<Shared<T>>,
+    seen: usize,
+}
+
+/// An error returned when all [`Sender`]s of a channel have been dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecvError(());
+
+impl std::fmt::Display for RecvError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("channel closed")
+    }
+}
+
+impl std::error::Error for RecvError {}
+
+impl From<RecvError> for crate::Error {
+    fn from(_: RecvError) -> Self {
+        Self::Closed
+    }
+}
+
+/// Creates a new channel with an initial value.
+pub fn channel<T>(init: T) -> (Sender<T>, Receiver<T>) {
+    let shared = Arc::new(Shared {
+        value: RwLock::new(init),
+        version: AtomicUsize::new(0),
+        senders: AtomicUsize::new(1),
+        event: Event::new(),
+    });
+
+    let rx = Receiver {
+        shared: Arc::clone(&shared),
+        seen: 0,
+    };
+
+    (Sender { shared }, rx)
+}
+
+impl<T> Sender<T> {
+    /// Modifies the value in place if `f` returns `true`, notifying receivers.
+    ///
+    /// If `f` returns `false`, receivers are not notified. It is up to `f` to leave the value
+    /// untouched in that case.
+    pub fn send_if_modified<F>(&self, f: F) -> bool
+    where
+        F: FnOnce(&mut T) -> bool,
+    {
+        {
+            let mut value = self
+                .shared
+                .value
+                .write()
+                .unwrap_or_else(PoisonError::into_inner);
+            if !f(&mut value) {
+                return false;
+            }
+            self.shared.version.fetch_add(1, Ordering::Release);
+        }
+
+        self.shared.event.notify_all();
+        true
+    }
+
+    /// Returns a reference to the current value.
+    pub fn borrow(&self) -> RwLockReadGuard<'_, T> {
+        self.shared.read()
+    }
+
+    /// Creates a new receiver that considers the current value as seen.
+    pub fn subscribe(&self) -> Receiver<T> {
+        // Hold the lock so that the version matches the value.
+        let _value = self.shared.read();
+        Receiver {
+            shared: Arc::clone(&self.shared),
+            seen: self.shared.version.load(Ordering::Acquire),
+        }
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.shared.senders.fetch_add(1, Ordering::Relaxed);
+        Self {
+            shared: Arc::clone(&self.shared),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        if self.shared.senders.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.shared.event.notify_all();
+        }
+    }
+}
+
+impl<T> Receiver<T> {
+    /// Returns a reference to the current value, without marking it as seen.
+    pub fn borrow(&self) -> RwLockReadGuard<'_, T> {
+        self.shared.read()
+    }
+
+    /// Waits until the value changes from the last seen one, and marks the new value as seen.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RecvError`] if all senders have been dropped and there is no unseen value.
+    #[expect(
+        clippy::future_not_send,
+        reason = "the future is `Send` if `T` is `Send` and `Sync`"
+    )]
+    pub async fn changed(&mut self) -> Result<(), RecvError> {
+        loop {
+            let listener = self.shared.event.listen();
+
+            let version = self.shared.version.load(Ordering::Acquire);
+            if version != self.seen {
+                self.seen = version;
+                return Ok(());
+            }
+            if self.shared.is_closed() {
+                return Err(RecvError(()));
+            }
+
+            listener.await;
+        }
+    }
+
+    /// Waits until the value satisfies `f`, and marks it as seen.
+    ///
+    /// The current value is checked first, regardless of whether it was already seen.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RecvError`] if all senders have been dropped before the value satisfies `f`.
+    #[expect(
+        clippy::future_not_send,
+        reason = "the future is `Send` if `T` is `Send` and `Sync`"
+    )]
+    pub async fn wait_for<F>(&mut self, mut f: F) -> Result<RwLockReadGuard<'_, T>, RecvError>
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let shared: &Shared<T> = &self.shared;
+        let seen = &mut self.seen;
+
+        loop {
+            let listener = shared.event.listen();
+
+            {
+                let value = shared.read();
+                *seen = shared.version.load(Ordering::Acquire);
+                if f(&value) {
+                    return Ok(value);
+                }
+            }
+            if shared.is_closed() {
+                return Err(RecvError(()));
+            }
+
+            listener.await;
+        }
+    }
+}
+
+impl<T> Clone for Receiver<T> {
+    fn clone(&self) -> Self {
+        Self {
+            shared: Arc::clone(&self.shared),
+            seen: self.seen,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use dime_util::runtime::TestRuntime;
+
+    use crate::runtime::{Runtime, Task};
+
+    use super::*;
+
+    fn send<T>(tx: &Sender<T>, value: T) {
+        tx.send_if_modified(|current| {
+            *current = value;
+            true
+        });
+    }
+
+    #[test]
+    fn test_changed() {
+        let rt = TestRuntime::new();
+        let (tx, mut rx) = channel(0);
+
+        let task = rt.spawn(async move {
+            rx.changed().await.unwrap();
+            *rx.borrow()
+        });
+
+        rt.run_until_stalled();
+        send(&tx, 1);
+        let got = rt.block_on(task.join()).unwrap();
+        assert_eq!(got, 1);
+    }
+
+    #[test]
+    fn test_send_if_modified() {
+        let rt = TestRuntime::new();
+        let (tx, mut rx) = channel(0);
+
+        let changed = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&changed);
+        let task = rt.spawn(async move {
+            rx.changed().await.unwrap();
+            flag.store(true, Ordering::Relaxed);
+            *rx.borrow()
+        });
+
+        assert!(!tx.send_if_modified(|_| false));
+        rt.run_until_stalled();
+        assert!(!changed.load(Ordering::Relaxed));
+
+        assert!(tx.send_if_modified(|value| {
+            *value = 1;
+            true
+        }));
+        assert_eq!(rt.block_on(task.join()).unwrap(), 1);
+        assert!(changed.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn test_wait_for() {
+        let rt = TestRuntime::new();
+        let (tx, mut rx) = channel(0);
+
+        let task = rt.spawn(async move { *rx.wait_for(|value| *value >= 2).await.unwrap() });
+
+        for value in 1..=3 {
+            rt.run_until_stalled();
+            send(&tx, value);
+        }
+        let got = rt.block_on(task.join()).unwrap();
+        assert!(got >= 2);
+    }
+
+    #[test]
+    fn test_closed() {
+        let rt = TestRuntime::new();
+        let (tx, mut rx) = channel(0);
+        let cloned = tx.clone();
+
+        let changed = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&changed);
+        let task = rt.spawn(async move {
+            rx.changed().await.unwrap();
+            flag.store(true, Ordering::Relaxed);
+
+            // The unseen value is still delivered before the channel reports being closed.
+            let err = rx.changed().await.unwrap_err();
+            assert_eq!(err, RecvError(()));
+            assert!(rx.wait_for(|value| *value == 0).await.is_err());
+        });
+
+        // The channel stays open while another sender is alive.
+        drop(tx);
+        rt.run_until_stalled();
+        assert!(!changed.load(Ordering::Relaxed));
+
+        send(&cloned, 1);
+        drop(cloned);
+        rt.block_on(task.join()).unwrap();
+        assert!(changed.load(Ordering::Relaxed));
+    }
+}
@@ -0,0 +1,502 @@
+//! Conformance checks for [`Injector`] implementations.
+//!
+//! Each check runs against an injector and panics if it does not uphold the contract documented
+//! on [`Injector`]. Futures are polled with a no-op waker, so the checks do not depend on any
+//! runtime. Use [`injector_conformance_tests`](crate::injector_conformance_tests) to generate a
+//! test for each check. The same checks are available for
+//! [`LocalInjector`](crate::injector::LocalInjector) implementations in [`local`].
+//!
+//! # Example
+//!
+//! ```
+//! mod state_map {
+//!     dime::injector_conformance_tests!(dime::injector::StateMap::new, select);
+//! }
+//! ```
+#![allow(
+    clippy::missing_panics_doc,
+    reason = "the checks are meant to panic on non-conforming injectors"
+)]
+
+use std::pin::{Pin, pin};
+use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
+
+use crate::Error;
+use crate::injector::{Injector, SelectInjector, Watch};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Value(u32);
+
+fn poll_once<F>(fut: Pin<&mut F>) -> Poll<F::Output>
+where
+    F: Future,
+{
+    fut.poll(&mut Context::from_waker(Waker::noop()))
+}
+
+fn ready<F>(fut: Pin<&mut F>, method: &str) -> F::Output
+where
+    F: Future,
+{
+    match poll_once(fut) {
+        Poll::Ready(output) => output,
+        Poll::Pending => panic!("`{method}` should be ready"),
+    }
+}
+
+fn pending<F>(fut: Pin<&mut F>, method: &str)
+where
+    F: Future,
+{
+    assert!(poll_once(fut).is_pending(), "`{method}` should be pending");
+}
+
+/// Generates a test for each conformance check, running against injectors created by `factory`.
+///
+/// Pass `select` after the factory to also check [`SelectInjector`](crate::injector::SelectInjector)
+/// implementations. See [`testing`](crate::testing).
+#[macro_export]
+macro_rules! injector_conformance_tests {
+    ($factory:expr) => {
+        $crate::injector_conformance_tests!(
+            @tests $factory,
+            check_current,
+            check_wait_undefined,
+            check_wait_pending,
+            check_wait_always,
+            check_wait_ok,
+            check_changed,
+            check_watch_after_inject,
+            check_shared,
+            check_retract,
+        );
+    };
+    ($factory:expr, select) => {
+        $crate::injector_conformance_tests!($factory);
+        $crate::injector_conformance_tests!(@tests $factory, check_select_retract);
+    };
+    (@tests $factory:expr, $($check:ident),* $(,)?) => {
+        $(
+            #[test]
+            fn $check() {
+                $crate::testing::$check(&($factory)());
+            }
+        )*
+    };
+}
+
+/// Generates a test for each conformance check, running against local injectors created by
+/// `factory`.
+///
+/// See [`testing::local`](crate::testing::local).
+#[macro_export]
+macro_rules! local_injector_conformance_tests {
+    ($factory:expr) => {
+        $crate::local_injector_conformance_tests!(
+            @tests $factory,
+            check_current,
+            check_wait_undefined,
+            check_wait_pending,
+            check_wait_always,
+            check_wait_ok,
+            check_changed,
+            check_watch_after_inject,
+            check_retract,
+        );
+    };
+    (@tests $factory:expr, $($check:ident),* $(,)?) => {
+        $(
+            #[test]
+            fn $check() {
+                $crate::testing::local::$check(&($factory)());
+            }
+        )*
+    };
+}
+
+// The checks shared by `Injector` and `LocalInjector`, which have the same contract apart from the
+// `Send` and `Sync` bounds.
+macro_rules! define_checks {
+    ($injector:ident) => {
+        /// `current` errors with `NotDefined` or `Pending` until a result is injected, while
+        /// `current_optional` returns `None`.
+        pub fn check_current<I>(injector: &I)
+        where
+            I: $injector,
+        {
+            let watch = injector.watch::<Value>();
+            assert!(watch.current().unwrap_err().is_not_defined_for::<Value>());
+            assert_eq!(watch.current_optional().unwrap(), None);
+
+            injector.define::<Value>();
+            assert!(watch.current().unwrap_err().is_pending());
+            assert_eq!(watch.current_optional().unwrap(), None);
+
+            injector.inject(Ok(Value(1)));
+            assert_eq!(watch.current().unwrap(), Value(1));
+            assert_eq!(watch.current_optional().unwrap(), Some(Value(1)));
+        }
+
+        /// `wait` errors with `NotDefined` and `wait_optional` returns `None` on an undefined type,
+        /// while `wait_always` and `wait_ok` keep waiting.
+        pub fn check_wait_undefined<I>(injector: &I)
+        where
+            I: $injector,
+        {
+            let mut watch = injector.watch::<Value>();
+
+            let err = ready(pin!(watch.wait()), "wait").unwrap_err();
+            assert!(err.is_not_defined_for::<Value>());
+            assert_eq!(
+                ready(pin!(watch.wait_optional()), "wait_optional").unwrap(),
+                None
+            );
+            pending(pin!(watch.wait_always()), "wait_always");
+            pending(pin!(watch.wait_ok()), "wait_ok");
+        }
+
+        /// `wait` and `wait_optional` wait on a pending type until a result is injected.
+        pub fn check_wait_pending<I>(injector: &I)
+        where
+            I: $injector,
+        {
+            let mut watch = injector.watch::<Value>();
+            let mut watch_optional = injector.watch::<Value>();
+            injector.define::<Value>();
+
+            let mut wait = pin!(watch.wait());
+            let mut wait_optional = pin!(watch_optional.wait_optional());
+            pending(wait.as_mut(), "wait");
+            pending(wait_optional.as_mut(), "wait_optional");
+
+            injector.inject(Ok(Value(1)));
+            assert_eq!(ready(wait, "wait").unwrap(), Value(1));
+            assert_eq!(
+                ready(wait_optional, "wait_optional").unwrap(),
+                Some(Value(1))
+            );
+        }
+
+        /// `wait_always` ignores `NotDefined` and `Pending` errors, but returns other errors.
+        pub fn check_wait_always<I>(injector: &I)
+        where
+            I: $injector,
+        {
+            let mut watch = injector.watch::<Value>();
+
+            {
+                let mut wait_always = pin!(watch.wait_always());
+                pending(wait_always.as_mut(), "wait_always");
+                injector.inject::<Value>(Err(Error::not_defined::<Value>()));
+                pending(wait_always.as_mut(), "wait_always");
+                injector.inject::<Value>(Err(Error::pending::<Value>()));
+                pending(wait_always.as_mut(), "wait_always");
+                injector.inject::<Value>(Err(Error::other("something went wrong")));
+                assert!(ready(wait_always, "wait_always").unwrap_err().is_other());
+            }
+
+            injector.inject(Ok(Value(1)));
+            assert_eq!(
+                ready(pin!(watch.wait_always()), "wait_always").unwrap(),
+                Value(1)
+            );
+        }
+
+        /// `wait_ok` skips errors until an `Ok` value is injected.
+        pub fn check_wait_ok<I>(injector: &I)
+        where
+            I: $injector,
+        {
+            let mut watch = injector.watch::<Value>();
+            injector.inject::<Value>(Err(Error::other("something went wrong")));
+
+            assert!(ready(pin!(watch.wait()), "wait").unwrap_err().is_other());
+
+            let mut wait_ok = pin!(watch.wait_ok());
+            pending(wait_ok.as_mut(), "wait_ok");
+            injector.inject(Ok(Value(1)));
+            assert_eq!(ready(wait_ok, "wait_ok").unwrap(), Value(1));
+        }
+
+        /// `changed` completes on every definition and injection, even of an equal value.
+        pub fn check_changed<I>(injector: &I)
+        where
+            I: $injector,
+        {
+            let mut watch = injector.watch::<Value>();
+            pending(pin!(watch.changed()), "changed");
+
+            injector.define::<Value>();
+            ready(pin!(watch.changed()), "changed").unwrap();
+            pending(pin!(watch.changed()), "changed");
+
+            for value in [Ok(Value(1)), Ok(Value(1)), Err(Error::other("oops"))] {
+                let mut changed = pin!(watch.changed());
+                pending(changed.as_mut(), "changed");
+                injector.inject(value);
+                ready(changed, "changed").unwrap();
+            }
+        }
+
+        /// Watches created after an injection observe the injected value.
+        pub fn check_watch_after_inject<I>(injector: &I)
+        where
+            I: $injector,
+        {
+            injector.inject(Ok(Value(1)));
+
+            let mut watch = injector.watch::<Value>();
+            assert_eq!(watch.current().unwrap(), Value(1));
+            assert_eq!(ready(pin!(watch.wait()), "wait").unwrap(), Value(1));
+            pending(pin!(watch.changed()), "changed");
+        }
+
+        /// `retract` reverts a value to pending and `undefine` reverts it to undefined, waking
+        /// watchers only if the state changes.
+        pub fn check_retract<I>(injector: &I)
+        where
+            I: $injector,
+        {
+            let mut watch = injector.watch::<Value>();
+            injector.retract::<Value>();
+            assert!(watch.current().unwrap_err().is_not_defined_for::<Value>());
+
+            injector.inject(Ok(Value(1)));
+            ready(pin!(watch.changed()), "changed").unwrap();
+
+            {
+                let mut changed = pin!(watch.changed());
+                injector.retract::<Value>();
+                ready(changed.as_mut(), "changed").unwrap();
+            }
+            assert!(watch.current().unwrap_err().is_pending());
+
+            {
+                let mut changed = pin!(watch.changed());
+                injector.retract::<Value>();
+                pending(changed.as_mut(), "changed");
+                injector.undefine::<Value>();
+                ready(changed, "changed").unwrap();
+            }
+            assert!(watch.current().unwrap_err().is_not_defined_for::<Value>());
+
+            let mut wait = pin!(watch.wait_always());
+            pending(wait.as_mut(), "wait_always");
+            injector.inject(Ok(Value(2)));
+            assert_eq!(ready(wait, "wait_always").unwrap(), Value(2));
+        }
+    };
+}
+
+define_checks!(Injector);
+
+/// Runs every check against injectors created by `factory`, apart from
+/// [`check_select_retract`] which requires a [`SelectInjector`].
+pub fn check_all<I, F>(factory: F)
+where
+    I: Injector,
+    F: Fn() -> I,
+{
+    check_current(&factory());
+    check_wait_undefined(&factory());
+    check_wait_pending(&factory());
+    check_wait_always(&factory());
+    check_wait_ok(&factory());
+    check_changed(&factory());
+    check_watch_after_inject(&factory());
+    check_shared(&factory());
+    check_retract(&factory());
+}
+
+/// Shared and cloned values of a type share the same state.
+pub fn check_shared<I>(injector: &I)
+where
+    I: Injector,
+{
+    let watch = injector.watch::<Value>();
+    let watch_shared = injector.watch_shared::<Value>();
+
+    let value = Arc::new(Value(1));
+    injector.inject_shared(Ok(Arc::clone(&value)));
+    assert_eq!(watch.current().unwrap(), Value(1));
+    assert!(Arc::ptr_eq(&watch_shared.current().unwrap(), &value));
+
+    injector.inject(Ok(Value(2)));
+    assert_eq!(*watch_shared.current().unwrap(), Value(2));
+}
+
+/// Components selected from a value are retracted and undefined along with it, instead of keeping
+/// the projection of a value that is no longer available.
+pub fn check_select_retract<I>(injector: &I)
+where
+    I: SelectInjector,
+{
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    struct Selected(u32);
+
+    injector.select(|value: &Value| Selected(value.0));
+    let watch = injector.watch::<Selected>();
+
+    injector.inject(Ok(Value(1)));
+    assert_eq!(watch.current().unwrap(), Selected(1));
+
+    injector.retract::<Value>();
+    assert!(watch.current().unwrap_err().is_pending());
+
+    injector.inject(Ok(Value(1)));
+    assert_eq!(watch.current().unwrap(), Selected(1));
+
+    injector.undefine::<Value>();
+    assert!(
+        watch
+            .current()
+            .unwrap_err()
+            .is_not_defined_for::<Selected>()
+    );
+}
+
+/// Conformance checks for [`LocalInjector`](crate::injector::LocalInjector) implementations.
+///
+/// These are the checks for [`Injector`], apart from
+/// [`check_shared`] and
+/// [`check_select_retract`], run against a
+/// [`LocalInjector`](crate::injector::LocalInjector). Use
+/// [`crate::local_injector_conformance_tests`] to generate a test for each check.
+pub mod local {
+    use std::pin::pin;
+
+    use super::{Value, pending, ready};
+    use crate::Error;
+    use crate::injector::{LocalInjector, LocalWatch};
+
+    define_checks!(LocalInjector);
+
+    /// Runs every check against local injectors created by `factory`.
+    pub fn check_all<I, F>(factory: F)
+    where
+        I: LocalInjector,
+        F: Fn() -> I,
+    {
+        check_current(&factory());
+        check_wait_undefined(&factory());
+        check_wait_pending(&factory());
+        check_wait_always(&factory());
+        check_wait_ok(&factory());
+        check_changed(&factory());
+        check_watch_after_inject(&factory());
+        check_retract(&factory());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::Result;
+    use crate::injector::{DynInjector, StateMap};
+
+    use super::*;
+
+    /// An instrumented injector that counts injections.
+    #[derive(Default)]
+    struct Counting {
+        inner: StateMap,
+        injections: AtomicUsize,
+    }
+
+    impl SelectInjector for Counting {
+        fn select<T, U, F>(&self, selector: F)
+        where
+            T: Send + Sync + 'static,
+            U: PartialEq + Send + Sync + 'static,
+            F: Fn(&T) -> U + Send + Sync + 'static,
+        {
+            self.inner.select(selector);
+        }
+    }
+
+    impl Injector for Counting {
+        type Watch<T: Clone + Send + Sync + 'static> = <StateMap as Injector>::Watch<T>;
+
+        type SharedWatch<T: Send + Sync + 'static> = <StateMap as Injector>::SharedWatch<T>;
+
+        fn define<T>(&self)
+        where
+            T: Send + Sync + 'static,
+        {
+            self.inner.define::<T>();
+        }
+
+        fn inject<T>(&self, value: Result<T>)
+        where
+            T: Send + Sync + 'static,
+        {
+            self.injections.fetch_add(1, Ordering::Relaxed);
+            self.inner.inject(value);
+        }
+
+        fn inject_shared<T>(&self, value: Result<Arc<T>>)
+        where
+            T: Send + Sync + 'static,
+        {
+            self.injections.fetch_add(1, Ordering::Relaxed);
+            self.inner.inject_shared(value);
+        }
+
+        fn retract<T>(&self)
+        where
+            T: Send + Sync + 'static,
+        {
+            self.inner.retract::<T>();
+        }
+
+        fn undefine<T>(&self)
+        where
+            T: Send + Sync + 'static,
+        {
+            self.inner.undefine::<T>();
+        }
+
+        fn watch<T>(&self) -> Self::Watch<T>
+        where
+            T: Clone + Send + Sync + 'static,
+        {
+            self.inner.watch()
+        }
+
+        fn watch_shared<T>(&self) -> Self::SharedWatch<T>
+        where
+            T: Send + Sync + 'static,
+        {
+            self.inner.watch_shared()
+        }
+    }
+
+    mod state_map {
+        crate::injector_conformance_tests!(crate::injector::StateMap::new, select);
+    }
+
+    mod counting {
+        crate::injector_conformance_tests!(super::Counting::default, select);
+    }
+
+    mod local_state_map {
+        crate::local_injector_conformance_tests!(crate::injector::LocalStateMap::new);
+    }
+
+    #[test]
+    fn test_dyn_injector() {
+        let factory = || Arc::new(StateMap::new()) as Arc<dyn DynInjector>;
+        check_all(factory);
+        check_select_retract(&factory());
+    }
+
+    #[test]
+    fn test_counting() {
+        let injector = Counting::default();
+        check_shared(&injector);
+        assert_eq!(injector.injections.load(Ordering::Relaxed), 2);
+    }
+}
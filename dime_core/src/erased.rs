@@ -1,64 +1,70 @@
 //! Utilites around opaque values with erased type informations.
 
 use std::any::Any;
+use std::rc::Rc;
+use std::sync::Arc;
 
-/// Clones a trait object into a [`Box`].
-///
-/// As [`Clone`] requires its implementor to be [`Sized`], it is not
-/// dyn-compatible. This trait allows safe cloning on DSTs and trait objects
-/// at the cost of heap allocation.
-pub trait DynClone: Any + Send + Sync {
-    /// Clones `self` and wraps it inside a [`Box`].
-    fn dyn_clone(&self) -> Box<dyn DynClone>;
-}
-
-impl<T> DynClone for T
-where
-    T: Any + Clone + Send + Sync,
-{
-    fn dyn_clone(&self) -> Box<dyn DynClone> {
-        Box::new(self.clone())
-    }
-}
+use crate::Error;
 
 /// [`Erased`] is a container for value of an arbitrary type, as long as it
-/// implements [`Clone`], [`Send`], and [`Sync`] and is `'static`.
-pub struct Erased(Box<dyn DynClone + Send + Sync>, &'static str);
+/// implements [`Send`] and [`Sync`] and is `'static`.
+///
+/// The value is stored behind an [`Arc`], so cloning an `Erased` is cheap and does not require
+/// the underlying type to implement [`Clone`].
+#[derive(Clone)]
+pub struct Erased(Arc<dyn Any + Send + Sync>, &'static str);
 
 impl Erased {
     /// Creates a new `Erased` with the provided `value` of type `T`.
     ///
-    /// `T` must be `'static` implement [`Clone`], [`Send`], and [`Sync`].
+    /// `T` must be `'static` and implement [`Send`] and [`Sync`].
     #[must_use]
     pub fn new<T>(value: T) -> Self
     where
-        T: Clone + Send + Sync + 'static,
+        T: Send + Sync + 'static,
     {
-        Self(
-            Box::new(value) as Box<dyn DynClone + Send + Sync>,
-            std::any::type_name::<T>(),
-        )
+        Self::from_arc(Arc::new(value))
+    }
+
+    /// Creates a new `Erased` from a value of type `T` that is already behind an [`Arc`].
+    #[must_use]
+    pub fn from_arc<T>(value: Arc<T>) -> Self
+    where
+        T: Send + Sync + 'static,
+    {
+        Self(value, std::any::type_name::<T>())
     }
 
     /// Tries to downcast `self` into type `T`.
     ///
+    /// The value is moved out if `self` is its only owner, otherwise it is cloned.
+    ///
     /// # Errors
     ///
     /// If the underlying value is not of type `T`, this method will return
     /// itself as error.
     pub fn downcast<T>(self) -> Result<T, Self>
     where
-        T: 'static,
+        T: Clone + Send + Sync + 'static,
+    {
+        self.downcast_arc::<T>()
+            .map(|value| Arc::try_unwrap(value).unwrap_or_else(|value| T::clone(&value)))
+    }
+
+    /// Tries to downcast `self` into an [`Arc`] of type `T`, without cloning the value.
+    ///
+    /// # Errors
+    ///
+    /// If the underlying value is not of type `T`, this method will return
+    /// itself as error.
+    pub fn downcast_arc<T>(self) -> Result<Arc<T>, Self>
+    where
+        T: Send + Sync + 'static,
     {
-        if (&*self.0 as &dyn Any).is::<T>() {
-            #[expect(clippy::missing_panics_doc, reason = "already checked")]
-            let concrete = (self.0 as Box<dyn Any + Send + Sync>)
-                .downcast::<T>()
-                .expect("the concrete type of this box should be `T` as it was checked before downcasting.");
-            Ok(*concrete)
-        } else {
-            Err(self)
-        }
+        let Self(value, type_name) = self;
+        value
+            .downcast::<T>()
+            .map_err(|value| Self(value, type_name))
     }
 
     /// Returns the reference to the underlying [`Any`] trait object.
@@ -66,9 +72,16 @@ impl Erased {
         &*self.0
     }
 
-    /// Returns the mutalbe reference to the underlying [`Any`] trait object.
-    pub fn as_mut_any(&mut self) -> &mut (dyn Any + Send + Sync) {
-        &mut *self.0
+    /// Returns the mutable reference to the underlying [`Any`] trait object.
+    ///
+    /// Returns `None` if the value is shared with other clones of this `Erased`.
+    pub fn as_mut_any(&mut self) -> Option<&mut (dyn Any + Send + Sync)> {
+        Arc::get_mut(&mut self.0)
+    }
+
+    /// Returns the name of the underlying type.
+    pub const fn type_name(&self) -> &'static str {
+        self.1
     }
 }
 
@@ -80,31 +93,138 @@ impl std::ops::Deref for Erased {
     }
 }
 
-impl std::ops::DerefMut for Erased {
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut *self.0
+impl std::fmt::Debug for Erased {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Erased")
+            .field("type", &self.1)
+            .finish_non_exhaustive()
     }
 }
 
-impl Clone for Erased {
-    fn clone(&self) -> Self {
-        Self(self.0.dyn_clone(), self.1)
+/// [`LocalErased`] is a container for value of an arbitrary type, as long as it is `'static`.
+///
+/// Unlike [`Erased`], the value does not need to implement [`Send`] or [`Sync`], and is stored
+/// behind an [`Rc`].
+#[derive(Clone)]
+pub struct LocalErased(Rc<dyn Any>, &'static str);
+
+impl LocalErased {
+    /// Creates a new `LocalErased` with the provided `value` of type `T`.
+    #[must_use]
+    pub fn new<T>(value: T) -> Self
+    where
+        T: 'static,
+    {
+        Self::from_rc(Rc::new(value))
+    }
+
+    /// Creates a new `LocalErased` from a value of type `T` that is already behind an [`Rc`].
+    #[must_use]
+    pub fn from_rc<T>(value: Rc<T>) -> Self
+    where
+        T: 'static,
+    {
+        Self(value, std::any::type_name::<T>())
+    }
+
+    /// Tries to downcast `self` into type `T`.
+    ///
+    /// The value is moved out if `self` is its only owner, otherwise it is cloned.
+    ///
+    /// # Errors
+    ///
+    /// If the underlying value is not of type `T`, this method will return
+    /// itself as error.
+    pub fn downcast<T>(self) -> Result<T, Self>
+    where
+        T: Clone + 'static,
+    {
+        self.downcast_rc::<T>()
+            .map(|value| Rc::try_unwrap(value).unwrap_or_else(|value| T::clone(&value)))
+    }
+
+    /// Tries to downcast `self` into an [`Rc`] of type `T`, without cloning the value.
+    ///
+    /// # Errors
+    ///
+    /// If the underlying value is not of type `T`, this method will return
+    /// itself as error.
+    pub fn downcast_rc<T>(self) -> Result<Rc<T>, Self>
+    where
+        T: 'static,
+    {
+        let Self(value, type_name) = self;
+        value
+            .downcast::<T>()
+            .map_err(|value| Self(value, type_name))
+    }
+
+    /// Returns the reference to the underlying [`Any`] trait object.
+    pub fn as_any(&self) -> &dyn Any {
+        &*self.0
+    }
+
+    /// Returns the mutable reference to the underlying [`Any`] trait object.
+    ///
+    /// Returns `None` if the value is shared with other clones of this `LocalErased`.
+    pub fn as_mut_any(&mut self) -> Option<&mut dyn Any> {
+        Rc::get_mut(&mut self.0)
+    }
+
+    /// Returns the name of the underlying type.
+    pub const fn type_name(&self) -> &'static str {
+        self.1
     }
 }
 
-impl std::fmt::Debug for Erased {
+impl std::fmt::Debug for LocalErased {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("Erased")
+        f.debug_struct("LocalErased")
             .field("type", &self.1)
             .finish_non_exhaustive()
     }
 }
 
+/// Downcasts `value` into type `T`, or returns [`Error::TypeMismatch`].
+#[doc(hidden)]
+pub fn downcast<T>(value: Erased) -> Result<T, Error>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    value
+        .downcast()
+        .map_err(|value| Error::type_mismatch::<T>(value.type_name()))
+}
+
+/// Downcasts `value` into an [`Arc`] of type `T`, or returns [`Error::TypeMismatch`].
+#[doc(hidden)]
+pub fn downcast_arc<T>(value: Erased) -> Result<Arc<T>, Error>
+where
+    T: Send + Sync + 'static,
+{
+    value
+        .downcast_arc()
+        .map_err(|value| Error::type_mismatch::<T>(value.type_name()))
+}
+
+/// Downcasts `value` into type `T`, or returns [`Error::TypeMismatch`].
+#[doc(hidden)]
+pub fn downcast_local<T>(value: LocalErased) -> Result<T, Error>
+where
+    T: Clone + 'static,
+{
+    value
+        .downcast()
+        .map_err(|value| Error::type_mismatch::<T>(value.type_name()))
+}
+
 #[cfg(test)]
 mod tests {
-    use std::{any::TypeId, sync::Arc};
+    use std::any::TypeId;
+    use std::rc::Rc;
+    use std::sync::Arc;
 
-    use super::Erased;
+    use super::{Erased, LocalErased};
 
     #[allow(dead_code)]
     fn test_implements_send_and_sync() -> impl Send + Sync {
@@ -145,6 +265,7 @@ mod tests {
         let mut erased = Erased::new("Hello".to_string());
         erased
             .as_mut_any()
+            .unwrap()
             .downcast_mut::<String>()
             .unwrap()
             .push_str(", world!");
@@ -155,7 +276,7 @@ mod tests {
     #[test]
     fn test_downcast_mut_err() {
         let mut erased = Erased::new("Hello".to_string());
-        assert!(erased.as_mut_any().downcast_mut::<i32>().is_none());
+        assert!(erased.as_mut_any().unwrap().downcast_mut::<i32>().is_none());
     }
 
     #[test]
@@ -164,19 +285,41 @@ mod tests {
         assert_eq!(erased.as_any().type_id(), TypeId::of::<String>());
     }
 
+    #[test]
+    fn test_as_mut_any_shared() {
+        let mut erased = Erased::new("Hello".to_string());
+        let cloned = erased.clone();
+        assert!(erased.as_mut_any().is_none());
+
+        drop(cloned);
+        assert!(erased.as_mut_any().is_some());
+    }
+
     #[test]
     fn test_clone() {
         let a = Arc::new(100);
         let erased = Erased::new(Arc::clone(&a));
         assert_eq!(Arc::strong_count(&a), 2);
 
+        // Cloning shares the underlying value instead of cloning it.
         let cloned = erased.clone();
+        assert_eq!(Arc::strong_count(&a), 2);
+
+        // Downcasting a shared value clones it.
+        let got = cloned.downcast::<Arc<i32>>().unwrap();
         assert_eq!(Arc::strong_count(&a), 3);
 
-        drop(cloned);
+        drop(got);
         drop(erased);
     }
 
+    #[test]
+    fn test_non_clone() {
+        let erased = Erased::new(std::sync::Mutex::new(1));
+        let got = erased.downcast_arc::<std::sync::Mutex<i32>>().unwrap();
+        assert_eq!(*got.lock().unwrap(), 1);
+    }
+
     #[test]
     fn test_drop() {
         let a = Arc::new(100);
@@ -186,4 +329,24 @@ mod tests {
         drop(erased);
         assert_eq!(Arc::strong_count(&a), 1);
     }
+
+    #[test]
+    fn test_local_downcast() {
+        let erased = LocalErased::new(Rc::new("Hello".to_string()));
+        let err = erased.downcast::<String>().unwrap_err();
+        let got = err.downcast::<Rc<String>>().unwrap();
+        assert_eq!(*got, "Hello");
+    }
+
+    #[test]
+    fn test_local_clone() {
+        let a = Rc::new(100);
+        let erased = LocalErased::new(Rc::clone(&a));
+        let cloned = erased.clone();
+        assert_eq!(Rc::strong_count(&a), 2);
+
+        drop(cloned);
+        drop(erased);
+        assert_eq!(Rc::strong_count(&a), 1);
+    }
 }
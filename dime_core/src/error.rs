@@ -1,15 +1,54 @@
 //! Error types.
 
-use std::any::{TypeId, type_name};
+use std::any::{Any, TypeId, type_name};
 use std::error::Error as StdError;
 use std::sync::Arc;
 
+use crate::runtime::Elapsed;
+
 /// [`Error`] is an error that can be raised by functions and methods from this library.
 #[derive(Debug, Clone)]
 #[non_exhaustive]
 pub enum Error {
+    /// No value of the type is promised or available.
     NotDefined(TypeId, &'static str),
+    /// A value of the type is promised, but is not yet available.
+    Pending(TypeId, &'static str),
+    /// A value was read as a different type than the one it was stored as.
+    TypeMismatch {
+        expected: &'static str,
+        found: &'static str,
+    },
+    /// The underlying channel of a watch has been closed.
+    Closed,
+    /// A task panicked with the given message.
+    Panicked(Arc<str>),
+    /// An operation did not complete before its deadline.
+    TimedOut,
+    /// An operation was cancelled before it completed.
+    Cancelled,
+    /// Components depend on each other in a cycle. Contains the type names of the components in
+    /// the cycle.
+    Cycle(Arc<[&'static str]>),
+    /// A value injected into the state of a type was rejected by its validator.
+    Invalid {
+        type_name: &'static str,
+        reason: Arc<Self>,
+    },
+    /// Several providers of the type are registered, and none of them takes precedence over the
+    /// others.
+    Conflict(TypeId, &'static str),
+    /// Any other error, e.g. one returned by a constructor.
     Other(Arc<dyn StdError + Send + Sync + 'static>),
+    /// An error that has propagated through components, e.g. from a dependency of a constructor
+    /// to the constructed component.
+    ///
+    /// `path` lists the type names of the components the error has been injected into, starting
+    /// from the one closest to the `cause`.
+    Propagated {
+        path: Arc<[&'static str]>,
+        cause: Box<Self>,
+    },
 }
 
 impl Error {
@@ -20,6 +59,53 @@ impl Error {
         Self::NotDefined(TypeId::of::<T>(), type_name::<T>())
     }
 
+    pub fn pending<T>() -> Self
+    where
+        T: 'static,
+    {
+        Self::Pending(TypeId::of::<T>(), type_name::<T>())
+    }
+
+    pub fn panicked<S>(message: S) -> Self
+    where
+        S: Into<Arc<str>>,
+    {
+        Self::Panicked(message.into())
+    }
+
+    /// Creates a [`Panicked`](Self::Panicked) error from the payload of a panic, e.g. one returned
+    /// by [`std::panic::catch_unwind`].
+    pub fn from_panic(payload: &(dyn Any + Send)) -> Self {
+        Self::panicked(panic_message(payload))
+    }
+
+    pub fn type_mismatch<T>(found: &'static str) -> Self
+    where
+        T: 'static,
+    {
+        Self::TypeMismatch {
+            expected: type_name::<T>(),
+            found,
+        }
+    }
+
+    pub fn invalid<T>(reason: Self) -> Self
+    where
+        T: ?Sized + 'static,
+    {
+        Self::Invalid {
+            type_name: type_name::<T>(),
+            reason: Arc::new(reason),
+        }
+    }
+
+    pub fn conflict<T>() -> Self
+    where
+        T: 'static,
+    {
+        Self::Conflict(TypeId::of::<T>(), type_name::<T>())
+    }
+
     pub fn other<E>(err: E) -> Self
     where
         E: Into<Box<dyn StdError + Send + Sync>>,
@@ -27,19 +113,118 @@ impl Error {
         Self::Other(Arc::from(err.into()))
     }
 
+    /// Records that the error has been injected into component `T`.
+    ///
+    /// Nothing is recorded if `T` is already the last component in the path.
+    #[must_use]
+    pub fn propagated_through<T>(self) -> Self
+    where
+        T: ?Sized + 'static,
+    {
+        self.propagated_through_erased(type_name::<T>())
+    }
+
+    /// Records that the error has been injected into the component named `name`.
+    ///
+    /// See [`propagated_through`](Self::propagated_through).
+    #[must_use]
+    pub fn propagated_through_erased(self, name: &'static str) -> Self {
+        match self {
+            Self::Propagated { path, cause } if path.last() == Some(&name) => {
+                Self::Propagated { path, cause }
+            }
+            Self::Propagated { path, cause } => Self::Propagated {
+                path: path.iter().copied().chain([name]).collect(),
+                cause,
+            },
+            cause => Self::Propagated {
+                path: Arc::from([name]),
+                cause: Box::new(cause),
+            },
+        }
+    }
+
+    /// Returns the error that caused this error to propagate, or `self` if it did not propagate.
+    pub const fn root_cause(&self) -> &Self {
+        match self {
+            Self::Propagated { cause, .. } => cause,
+            _ => self,
+        }
+    }
+
+    /// Returns the type names of the components the error has propagated through, starting from
+    /// the one closest to the root cause.
+    pub fn path(&self) -> &[&'static str] {
+        match self {
+            Self::Propagated { path, .. } => path,
+            _ => &[],
+        }
+    }
+
+    /// Returns the type name of the component the error originates from.
+    ///
+    /// This is the undefined type if the root cause is [`NotDefined`](Self::NotDefined), or the
+    /// first component the error has been injected into otherwise.
+    pub fn root_component(&self) -> Option<&'static str> {
+        match self.root_cause() {
+            Self::NotDefined(_, type_name) => Some(type_name),
+            _ => self.path().first().copied(),
+        }
+    }
+
     pub const fn is_not_defined(&self) -> bool {
-        matches!(self, Self::NotDefined(_, _))
+        matches!(self.root_cause(), Self::NotDefined(_, _))
     }
 
     pub fn is_not_defined_for<T>(&self) -> bool
     where
         T: 'static,
     {
-        matches!(self, Self::NotDefined(id, _) if *id == TypeId::of::<T>())
+        matches!(self.root_cause(), Self::NotDefined(id, _) if *id == TypeId::of::<T>())
+    }
+
+    pub const fn is_pending(&self) -> bool {
+        matches!(self.root_cause(), Self::Pending(_, _))
+    }
+
+    pub const fn is_type_mismatch(&self) -> bool {
+        matches!(self.root_cause(), Self::TypeMismatch { .. })
+    }
+
+    pub const fn is_closed(&self) -> bool {
+        matches!(self.root_cause(), Self::Closed)
+    }
+
+    pub const fn is_panicked(&self) -> bool {
+        matches!(self.root_cause(), Self::Panicked(_))
+    }
+
+    pub const fn is_timed_out(&self) -> bool {
+        matches!(self.root_cause(), Self::TimedOut)
+    }
+
+    pub const fn is_cancelled(&self) -> bool {
+        matches!(self.root_cause(), Self::Cancelled)
+    }
+
+    pub const fn is_cycle(&self) -> bool {
+        matches!(self.root_cause(), Self::Cycle(_))
+    }
+
+    pub const fn is_invalid(&self) -> bool {
+        matches!(self.root_cause(), Self::Invalid { .. })
+    }
+
+    pub const fn is_conflict(&self) -> bool {
+        matches!(self.root_cause(), Self::Conflict(_, _))
     }
 
     pub const fn is_other(&self) -> bool {
-        matches!(self, Self::Other(_))
+        matches!(self.root_cause(), Self::Other(_))
+    }
+
+    pub const fn is_propagated(&self) -> bool {
+        matches!(self, Self::Propagated { .. })
     }
 }
 
@@ -49,7 +234,39 @@ impl std::fmt::Display for Error {
             Self::NotDefined(_, type_name) => {
                 write!(f, "type `{type_name}` is not defined")
             }
+            Self::Pending(_, type_name) => {
+                write!(f, "value of type `{type_name}` is not yet available")
+            }
+            Self::TypeMismatch { expected, found } => {
+                write!(f, "expected a value of type `{expected}`, found `{found}`")
+            }
+            Self::Closed => f.write_str("channel closed"),
+            Self::Panicked(message) => write!(f, "task panicked with message `{message}`"),
+            Self::TimedOut => f.write_str("deadline has elapsed"),
+            Self::Cancelled => f.write_str("operation was cancelled"),
+            Self::Cycle(path) => {
+                f.write_str("dependency cycle detected: ")?;
+                for name in path.iter() {
+                    write!(f, "{name} → ")?;
+                }
+                f.write_str(path.first().copied().unwrap_or_default())
+            }
+            Self::Invalid { type_name, reason } => {
+                write!(f, "invalid value of type `{type_name}`: {reason}")
+            }
+            Self::Conflict(_, type_name) => {
+                write!(f, "conflicting providers of type `{type_name}`")
+            }
             Self::Other(error) => error.fmt(f),
+            Self::Propagated { path, cause } => {
+                for (i, name) in path.iter().rev().enumerate() {
+                    if i > 0 {
+                        f.write_str(" ← ")?;
+                    }
+                    f.write_str(name)?;
+                }
+                write!(f, ": {cause}")
+            }
         }
     }
 }
@@ -58,11 +275,81 @@ impl StdError for Error {
     fn source(&self) -> Option<&(dyn StdError + 'static)> {
         match self {
             Self::Other(error) => Some(error),
+            Self::Invalid { reason, .. } => Some(reason),
+            Self::Propagated { cause, .. } => Some(cause),
             _ => None,
         }
     }
 }
 
+impl From<Elapsed> for Error {
+    fn from(_: Elapsed) -> Self {
+        Self::TimedOut
+    }
+}
+
 /// [`Result`] is an alias to [`core::result::Result`] with [`Error`] as the
 /// default error type.
 pub type Result<T, E = Error> = core::result::Result<T, E>;
+
+/// Returns the message of the payload of a panic, or `"Box<dyn Any>"` if the payload is not a
+/// string, like the default panic hook does.
+pub fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("Box<dyn Any>")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future;
+    use std::panic::catch_unwind;
+    use std::pin::pin;
+    use std::task::{Context, Poll, Waker};
+
+    use crate::runtime::Timeout;
+
+    use super::*;
+
+    #[test]
+    fn test_from_panic() {
+        let payload = catch_unwind(|| panic!("oops")).unwrap_err();
+        let err = Error::from_panic(&*payload);
+        assert!(err.is_panicked());
+        assert_eq!(err.to_string(), "task panicked with message `oops`");
+
+        let payload = catch_unwind(|| panic!("{}", 42)).unwrap_err();
+        assert!(
+            matches!(Error::from_panic(&*payload), Error::Panicked(message) if &*message == "42")
+        );
+    }
+
+    #[test]
+    fn test_predicates() {
+        let timeout = pin!(Timeout::new(future::pending::<()>(), future::ready(())));
+        let Poll::Ready(Err(elapsed)) = timeout.poll(&mut Context::from_waker(Waker::noop()))
+        else {
+            panic!("timeout should have elapsed");
+        };
+        let err = Error::from(elapsed).propagated_through::<u32>();
+        assert!(err.is_timed_out());
+        assert!(!err.is_cancelled());
+
+        let err = Error::Cycle(Arc::from(["A", "B"]));
+        assert!(err.is_cycle());
+        assert_eq!(err.to_string(), "dependency cycle detected: A → B → A");
+
+        let err = Error::invalid::<u32>(Error::other("too large")).propagated_through::<u64>();
+        assert!(err.is_invalid());
+        assert_eq!(
+            err.root_cause().to_string(),
+            "invalid value of type `u32`: too large"
+        );
+
+        let err = Error::conflict::<u32>();
+        assert!(err.is_conflict());
+        assert_eq!(err.to_string(), "conflicting providers of type `u32`");
+    }
+}
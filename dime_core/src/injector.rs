@@ -1,45 +1,139 @@
 //! [`Injector`] trait and common implementations.
 
+use std::any::TypeId;
+use std::rc::Rc;
 use std::sync::Arc;
+use std::time::Instant;
 
-use crate::Result;
+use crate::{Erased, Error, Result};
+
+mod dyn_injector;
+pub use dyn_injector::{DynInjector, DynSharedWatch, DynTypedWatch, DynWatch};
 
 /// A base trait for container to inject to and retrieve value from.
+///
+/// Values are stored in a shared form, so they can be retrieved either by cloning them with
+/// [`watch`](Self::watch), or as an [`Arc`] with [`watch_shared`](Self::watch_shared). The latter
+/// does not require the type to implement [`Clone`].
+///
+/// # Contract
+///
+/// Containers and components rely on the following semantics, which every implementation
+/// (e.g. sharded maps, layered injectors, or instrumented wrappers) must uphold. The state of each
+/// type starts *undefined*, becomes *pending* once the type is [defined](Self::define), and
+/// becomes *ready* once a result is [injected](Self::inject).
+///
+/// - Injected results are visible to every watch of the type as soon as `inject` returns,
+///   including watches created before the type was defined or injected.
+/// - [`watch`](Self::watch) and [`watch_shared`](Self::watch_shared) observe the same state, and
+///   [`inject`](Self::inject) and [`inject_shared`](Self::inject_shared) are interchangeable.
+/// - Until a result is injected, [`current`](Watch::current) returns
+///   [`Error::NotDefined`] on an undefined type and
+///   [`Error::Pending`] on a pending one, while
+///   [`current_optional`](Watch::current_optional) returns `None`.
+/// - [`wait`](Watch::wait) returns [`Error::NotDefined`] on an
+///   undefined type, and waits on a pending one. [`wait_optional`](Watch::wait_optional) returns
+///   `None` instead of the error.
+/// - [`wait_always`](Watch::wait_always) waits until a result other than
+///   [`Error::NotDefined`] or
+///   [`Error::Pending`] is injected.
+/// - [`wait_ok`](Watch::wait_ok) waits until an `Ok` value is injected.
+/// - [`current_last_ok`](Watch::current_last_ok) and [`wait_last_ok`](Watch::wait_last_ok)
+///   behave like [`current`](Watch::current) and [`wait_ok`](Watch::wait_ok), except that they
+///   may return the last `Ok` value instead of an injected error if the implementation keeps it.
+/// - [`retract`](Self::retract) reverts a ready state to pending, and
+///   [`undefine`](Self::undefine) reverts any state to undefined.
+/// - [`changed`](Watch::changed) completes once for every definition, injection, retraction, or
+///   undefinition since the watch last observed the state, even if the injected value is equal
+///   to the previous one. Retracting or undefining a state that is already pending or undefined
+///   respectively does not count as a change.
 pub trait Injector {
-    type Watch<T: Send + 'static>: Watch<Ty = T>;
+    type Watch<T: Clone + Send + Sync + 'static>: Watch<Ty = T>;
+
+    type SharedWatch<T: Send + Sync + 'static>: Watch<Ty = Arc<T>>;
 
     /// Tells the injector that a type might be injected to it.
     ///
     /// Depending on the implementation, Trying to retrieve value (e.g. by calling
     /// [`wait`](Watch::wait)) prior to calling this method for its type may panic, wait forever,
-    /// or return [`Error::NotDefined`](crate::error::Error::NotDefined).
+    /// or return [`Error::NotDefined`].
     /// Calling this method ensures that retrieving value of this type will wait until a value
     /// is available.
     fn define<T>(&self)
     where
-        T: Clone + Send + Sync + 'static;
+        T: Send + Sync + 'static;
 
     /// Inject a value of a given type into the injector.
     fn inject<T>(&self, value: Result<T>)
     where
-        T: Clone + Send + Sync + 'static;
+        T: Send + Sync + 'static;
+
+    /// Inject a value of a given type that is already behind an [`Arc`] into the injector.
+    fn inject_shared<T>(&self, value: Result<Arc<T>>)
+    where
+        T: Send + Sync + 'static;
+
+    /// Injects a value of a given type that expires at `expires_at`.
+    ///
+    /// Once expired, the state of the type reverts to pending, as if the type has just been
+    /// defined, unless another value is injected before then. Injectors that do not support
+    /// expiry inject the value as if by [`inject`](Self::inject), which is the default.
+    fn inject_expiring<T>(&self, value: Result<T>, expires_at: Instant)
+    where
+        T: Send + Sync + 'static,
+    {
+        let _ = expires_at;
+        self.inject(value);
+    }
+
+    /// Injects a value of a given type that is already behind an [`Arc`] and expires at
+    /// `expires_at`.
+    ///
+    /// See [`inject_expiring`](Self::inject_expiring).
+    fn inject_shared_expiring<T>(&self, value: Result<Arc<T>>, expires_at: Instant)
+    where
+        T: Send + Sync + 'static,
+    {
+        let _ = expires_at;
+        self.inject_shared(value);
+    }
+
+    /// Retracts the value of a given type, reverting its state to pending as if the type has just
+    /// been defined.
+    ///
+    /// Does nothing if the type is not defined.
+    fn retract<T>(&self)
+    where
+        T: Send + Sync + 'static;
+
+    /// Reverts the state of a given type to undefined, dropping its value.
+    fn undefine<T>(&self)
+    where
+        T: Send + Sync + 'static;
 
     /// Watches for values of a given type in the injector.
     fn watch<T>(&self) -> Self::Watch<T>
     where
         T: Clone + Send + Sync + 'static;
+
+    /// Watches for shared values of a given type in the injector.
+    fn watch_shared<T>(&self) -> Self::SharedWatch<T>
+    where
+        T: Send + Sync + 'static;
 }
 
 impl<I> Injector for Arc<I>
 where
-    I: Injector,
+    I: Injector + ?Sized,
 {
-    type Watch<T: Send + 'static> = I::Watch<T>;
+    type Watch<T: Clone + Send + Sync + 'static> = I::Watch<T>;
+
+    type SharedWatch<T: Send + Sync + 'static> = I::SharedWatch<T>;
 
     #[inline]
     fn define<T>(&self)
     where
-        T: Clone + Send + Sync + 'static,
+        T: Send + Sync + 'static,
     {
         (**self).define::<T>();
     }
@@ -47,11 +141,51 @@ where
     #[inline]
     fn inject<T>(&self, value: Result<T>)
     where
-        T: Clone + Send + Sync + 'static,
+        T: Send + Sync + 'static,
     {
         (**self).inject(value);
     }
 
+    #[inline]
+    fn inject_shared<T>(&self, value: Result<Arc<T>>)
+    where
+        T: Send + Sync + 'static,
+    {
+        (**self).inject_shared(value);
+    }
+
+    #[inline]
+    fn inject_expiring<T>(&self, value: Result<T>, expires_at: Instant)
+    where
+        T: Send + Sync + 'static,
+    {
+        (**self).inject_expiring(value, expires_at);
+    }
+
+    #[inline]
+    fn inject_shared_expiring<T>(&self, value: Result<Arc<T>>, expires_at: Instant)
+    where
+        T: Send + Sync + 'static,
+    {
+        (**self).inject_shared_expiring(value, expires_at);
+    }
+
+    #[inline]
+    fn retract<T>(&self)
+    where
+        T: Send + Sync + 'static,
+    {
+        (**self).retract::<T>();
+    }
+
+    #[inline]
+    fn undefine<T>(&self)
+    where
+        T: Send + Sync + 'static,
+    {
+        (**self).undefine::<T>();
+    }
+
     #[inline]
     fn watch<T>(&self) -> Self::Watch<T>
     where
@@ -59,18 +193,28 @@ where
     {
         (**self).watch()
     }
+
+    #[inline]
+    fn watch_shared<T>(&self) -> Self::SharedWatch<T>
+    where
+        T: Send + Sync + 'static,
+    {
+        (**self).watch_shared()
+    }
 }
 
 impl<I> Injector for Box<I>
 where
-    I: Injector,
+    I: Injector + ?Sized,
 {
-    type Watch<T: Send + 'static> = I::Watch<T>;
+    type Watch<T: Clone + Send + Sync + 'static> = I::Watch<T>;
+
+    type SharedWatch<T: Send + Sync + 'static> = I::SharedWatch<T>;
 
     #[inline]
     fn define<T>(&self)
     where
-        T: Clone + Send + Sync + 'static,
+        T: Send + Sync + 'static,
     {
         (**self).define::<T>();
     }
@@ -78,11 +222,51 @@ where
     #[inline]
     fn inject<T>(&self, value: Result<T>)
     where
-        T: Clone + Send + Sync + 'static,
+        T: Send + Sync + 'static,
     {
         (**self).inject(value);
     }
 
+    #[inline]
+    fn inject_shared<T>(&self, value: Result<Arc<T>>)
+    where
+        T: Send + Sync + 'static,
+    {
+        (**self).inject_shared(value);
+    }
+
+    #[inline]
+    fn inject_expiring<T>(&self, value: Result<T>, expires_at: Instant)
+    where
+        T: Send + Sync + 'static,
+    {
+        (**self).inject_expiring(value, expires_at);
+    }
+
+    #[inline]
+    fn inject_shared_expiring<T>(&self, value: Result<Arc<T>>, expires_at: Instant)
+    where
+        T: Send + Sync + 'static,
+    {
+        (**self).inject_shared_expiring(value, expires_at);
+    }
+
+    #[inline]
+    fn retract<T>(&self)
+    where
+        T: Send + Sync + 'static,
+    {
+        (**self).retract::<T>();
+    }
+
+    #[inline]
+    fn undefine<T>(&self)
+    where
+        T: Send + Sync + 'static,
+    {
+        (**self).undefine::<T>();
+    }
+
     #[inline]
     fn watch<T>(&self) -> Self::Watch<T>
     where
@@ -90,6 +274,276 @@ where
     {
         (**self).watch()
     }
+
+    #[inline]
+    fn watch_shared<T>(&self) -> Self::SharedWatch<T>
+    where
+        T: Send + Sync + 'static,
+    {
+        (**self).watch_shared()
+    }
+}
+
+/// An [`Injector`] that can derive values of a type from values of another type.
+///
+/// Selection relies on being notified of every change to the source state, so it is a separate
+/// extension of [`Injector`] rather than something every implementation has to support.
+pub trait SelectInjector: Injector {
+    /// Derives values of `U` from values of `T` using a projection.
+    ///
+    /// The projection is run with every value injected into the state of `T`, in the order they
+    /// are injected, and its result is only injected into the state of `U` if it differs from
+    /// the current value. Errors injected into the state of `T` are forwarded to the state of
+    /// `U`, and retracting or undefining `T` retracts or undefines `U` as well.
+    fn select<T, U, F>(&self, selector: F)
+    where
+        T: Send + Sync + 'static,
+        U: PartialEq + Send + Sync + 'static,
+        F: Fn(&T) -> U + Send + Sync + 'static;
+}
+
+impl<I> SelectInjector for Arc<I>
+where
+    I: SelectInjector + ?Sized,
+{
+    #[inline]
+    fn select<T, U, F>(&self, selector: F)
+    where
+        T: Send + Sync + 'static,
+        U: PartialEq + Send + Sync + 'static,
+        F: Fn(&T) -> U + Send + Sync + 'static,
+    {
+        (**self).select(selector);
+    }
+}
+
+impl<I> SelectInjector for Box<I>
+where
+    I: SelectInjector + ?Sized,
+{
+    #[inline]
+    fn select<T, U, F>(&self, selector: F)
+    where
+        T: Send + Sync + 'static,
+        U: PartialEq + Send + Sync + 'static,
+        F: Fn(&T) -> U + Send + Sync + 'static,
+    {
+        (**self).select(selector);
+    }
+}
+
+/// An injector that can be accessed with types only known at runtime.
+///
+/// Types are identified by their [`TypeId`] and name, and values are passed as [`Erased`].
+pub trait ErasedInjector {
+    type ErasedWatch: Watch<Ty = Erased>;
+
+    /// Tells the injector that a type might be injected to it.
+    ///
+    /// See [`Injector::define`].
+    fn define_erased(&self, type_id: TypeId, type_name: &'static str);
+
+    /// Injects a type-erased value of a given type into the injector.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::TypeMismatch`] without injecting
+    /// anything if the underlying type of `value` is not the type identified by `type_id`.
+    fn inject_erased(
+        &self,
+        type_id: TypeId,
+        type_name: &'static str,
+        value: Result<Erased>,
+    ) -> Result<()>;
+
+    /// Injects a type-erased value of a given type that expires at `expires_at`.
+    ///
+    /// See [`Injector::inject_expiring`]. Injectors that do not support expiry inject the value
+    /// as if by [`inject_erased`](Self::inject_erased), which is the default.
+    ///
+    /// # Errors
+    ///
+    /// See [`inject_erased`](Self::inject_erased).
+    fn inject_expiring_erased(
+        &self,
+        type_id: TypeId,
+        type_name: &'static str,
+        value: Result<Erased>,
+        expires_at: Instant,
+    ) -> Result<()> {
+        let _ = expires_at;
+        self.inject_erased(type_id, type_name, value)
+    }
+
+    /// Retracts the value of a given type.
+    ///
+    /// See [`Injector::retract`].
+    fn retract_erased(&self, type_id: TypeId, type_name: &'static str);
+
+    /// Reverts the state of a given type to undefined.
+    ///
+    /// See [`Injector::undefine`].
+    fn undefine_erased(&self, type_id: TypeId, type_name: &'static str);
+
+    /// Watches for type-erased values of a given type in the injector.
+    fn watch_erased(&self, type_id: TypeId, type_name: &'static str) -> Self::ErasedWatch;
+
+    /// Derives values of the target type from values of the source type using a type-erased
+    /// projection.
+    ///
+    /// See [`SelectInjector::select`] and [`erase_selector`].
+    fn select_erased(
+        &self,
+        source: (TypeId, &'static str),
+        target: (TypeId, &'static str),
+        selector: ErasedSelector,
+    );
+}
+
+impl<I> ErasedInjector for Arc<I>
+where
+    I: ErasedInjector + ?Sized,
+{
+    type ErasedWatch = I::ErasedWatch;
+
+    #[inline]
+    fn define_erased(&self, type_id: TypeId, type_name: &'static str) {
+        (**self).define_erased(type_id, type_name);
+    }
+
+    #[inline]
+    fn inject_erased(
+        &self,
+        type_id: TypeId,
+        type_name: &'static str,
+        value: Result<Erased>,
+    ) -> Result<()> {
+        (**self).inject_erased(type_id, type_name, value)
+    }
+
+    #[inline]
+    fn inject_expiring_erased(
+        &self,
+        type_id: TypeId,
+        type_name: &'static str,
+        value: Result<Erased>,
+        expires_at: Instant,
+    ) -> Result<()> {
+        (**self).inject_expiring_erased(type_id, type_name, value, expires_at)
+    }
+
+    #[inline]
+    fn retract_erased(&self, type_id: TypeId, type_name: &'static str) {
+        (**self).retract_erased(type_id, type_name);
+    }
+
+    #[inline]
+    fn undefine_erased(&self, type_id: TypeId, type_name: &'static str) {
+        (**self).undefine_erased(type_id, type_name);
+    }
+
+    #[inline]
+    fn watch_erased(&self, type_id: TypeId, type_name: &'static str) -> Self::ErasedWatch {
+        (**self).watch_erased(type_id, type_name)
+    }
+
+    #[inline]
+    fn select_erased(
+        &self,
+        source: (TypeId, &'static str),
+        target: (TypeId, &'static str),
+        selector: ErasedSelector,
+    ) {
+        (**self).select_erased(source, target, selector);
+    }
+}
+
+impl<I> ErasedInjector for Box<I>
+where
+    I: ErasedInjector + ?Sized,
+{
+    type ErasedWatch = I::ErasedWatch;
+
+    #[inline]
+    fn define_erased(&self, type_id: TypeId, type_name: &'static str) {
+        (**self).define_erased(type_id, type_name);
+    }
+
+    #[inline]
+    fn inject_erased(
+        &self,
+        type_id: TypeId,
+        type_name: &'static str,
+        value: Result<Erased>,
+    ) -> Result<()> {
+        (**self).inject_erased(type_id, type_name, value)
+    }
+
+    #[inline]
+    fn inject_expiring_erased(
+        &self,
+        type_id: TypeId,
+        type_name: &'static str,
+        value: Result<Erased>,
+        expires_at: Instant,
+    ) -> Result<()> {
+        (**self).inject_expiring_erased(type_id, type_name, value, expires_at)
+    }
+
+    #[inline]
+    fn retract_erased(&self, type_id: TypeId, type_name: &'static str) {
+        (**self).retract_erased(type_id, type_name);
+    }
+
+    #[inline]
+    fn undefine_erased(&self, type_id: TypeId, type_name: &'static str) {
+        (**self).undefine_erased(type_id, type_name);
+    }
+
+    #[inline]
+    fn watch_erased(&self, type_id: TypeId, type_name: &'static str) -> Self::ErasedWatch {
+        (**self).watch_erased(type_id, type_name)
+    }
+
+    #[inline]
+    fn select_erased(
+        &self,
+        source: (TypeId, &'static str),
+        target: (TypeId, &'static str),
+        selector: ErasedSelector,
+    ) {
+        (**self).select_erased(source, target, selector);
+    }
+}
+
+/// A type-erased projection passed to [`ErasedInjector::select_erased`].
+///
+/// It is called with a value of the source type and the current value of the target type, if
+/// any, and returns the result to inject into the state of the target type, or `None` to keep
+/// the current value.
+pub type ErasedSelector =
+    Box<dyn Fn(&Erased, Option<&Erased>) -> Option<Result<Erased>> + Send + Sync>;
+
+/// Turns a projection from `T` into `U` into an [`ErasedSelector`].
+///
+/// The returned selector keeps the current value if it is equal to the projected one, and
+/// returns [`Error::TypeMismatch`] if the source value is not of type `T`.
+pub fn erase_selector<T, U, F>(selector: F) -> ErasedSelector
+where
+    T: Send + Sync + 'static,
+    U: PartialEq + Send + Sync + 'static,
+    F: Fn(&T) -> U + Send + Sync + 'static,
+{
+    Box::new(move |value, current| {
+        let Some(value) = value.as_any().downcast_ref::<T>() else {
+            return Some(Err(Error::type_mismatch::<T>(value.type_name())));
+        };
+        let projected = selector(value);
+        let unchanged = current
+            .and_then(|current| current.as_any().downcast_ref::<U>())
+            .is_some_and(|current| *current == projected);
+        (!unchanged).then(|| Ok(Erased::new(projected)))
+    })
 }
 
 /// A task operating around an injector.
@@ -128,7 +582,7 @@ pub trait Watch {
     ///
     /// # Errors
     ///
-    /// This method returns [`Error`](crate::error::Error) if the evaluation
+    /// This method returns [`Error`] if the evaluation
     /// of the value returned an error.
     fn current(&self) -> Result<Self::Ty>;
 
@@ -136,7 +590,7 @@ pub trait Watch {
     ///
     /// # Errors
     ///
-    /// This method returns [`Error`](crate::error::Error) if the evaluation
+    /// This method returns [`Error`] if the evaluation
     /// of the value returned an error.
     fn current_optional(&self) -> Result<Option<Self::Ty>>;
 
@@ -145,9 +599,9 @@ pub trait Watch {
     /// # Errors
     ///
     /// This method returns
-    /// [`Error::NotDefined`](crate::error::Error::NotDefined)
+    /// [`Error::NotDefined`]
     /// if no value of type `T` is promised to the injector.
-    /// Otherwise, this method returns [`Error`](crate::error::Error) if the
+    /// Otherwise, this method returns [`Error`] if the
     /// evaluation of the value returned an error.
     fn wait(&mut self) -> impl Future<Output = Result<Self::Ty>> + Send;
 
@@ -156,7 +610,7 @@ pub trait Watch {
     ///
     /// # Errors
     ///
-    /// This method returns [`Error`](crate::error::Error) if the evaluation
+    /// This method returns [`Error`] if the evaluation
     /// of the value returned an error.
     fn wait_optional(&mut self) -> impl Future<Output = Result<Option<Self::Ty>>> + Send;
 
@@ -165,7 +619,7 @@ pub trait Watch {
     ///
     /// # Errors
     ///
-    /// This method returns [`Error`](crate::error::Error) if the evaluation
+    /// This method returns [`Error`] if the evaluation
     /// of the value returned an error.
     fn wait_always(&mut self) -> impl Future<Output = Result<Self::Ty>> + Send;
 
@@ -179,13 +633,47 @@ pub trait Watch {
     /// injector itself.
     fn wait_ok(&mut self) -> impl Future<Output = Result<Self::Ty>> + Send;
 
+    /// Immediately retrieves the current value, or the last `Ok` value if the current value is an
+    /// error and the injector keeps the last `Ok` value of type `T`.
+    ///
+    /// By default, this is the same as [`current`](Self::current).
+    ///
+    /// # Errors
+    ///
+    /// This method returns [`Error`] if the evaluation
+    /// of the value returned an error and no `Ok` value is kept.
+    fn current_last_ok(&self) -> Result<Self::Ty> {
+        self.current()
+    }
+
+    /// Waits until a value of type `T` is successfully created like [`wait_ok`](Self::wait_ok),
+    /// but returns immediately with the last `Ok` value if the current value is an error and
+    /// the injector keeps the last `Ok` value of type `T`.
+    ///
+    /// By default, this is the same as [`wait_ok`](Self::wait_ok).
+    ///
+    /// # Errors
+    ///
+    /// See [`wait_ok`](Self::wait_ok).
+    fn wait_last_ok(&mut self) -> impl Future<Output = Result<Self::Ty>> + Send {
+        self.wait_ok()
+    }
+
     /// Waits until the value of type `T` changes.
     ///
     /// # Errors
     ///
-    /// This method returns [`Error`](crate::error::Error) if the evaluation
+    /// This method returns [`Error`] if the evaluation
     /// of the value returned an error.
     fn changed(&mut self) -> impl Future<Output = Result<()>> + Send;
+
+    /// Returns `true` if any of the values this watch is made of is pending.
+    ///
+    /// Unlike checking the error returned by [`current`](Self::current), this also detects
+    /// pending values of aggregates where an earlier value is in error.
+    fn any_pending(&self) -> bool {
+        self.current().is_err_and(|err| err.is_pending())
+    }
 }
 
 // We can produce `()` out of thin air.
@@ -277,6 +765,18 @@ macro_rules! impl_watch_tuple {
                     try_join_ty($($ty),*).await
                 }
 
+                fn current_last_ok(&self) -> Result<Self::Ty> {
+                    let ($($ty,)*) = self;
+                    let ($($ty,)*) = ($($ty.current_last_ok()?,)*);
+                    Ok(($($ty,)*))
+                }
+
+                async fn wait_last_ok(&mut self) -> Result<Self::Ty> {
+                    let ($($ty,)*) = self;
+                    let ($($ty,)*) = ($($ty.wait_last_ok(),)*);
+                    try_join_ty($($ty),*).await
+                }
+
                 async fn changed(&mut self) -> Result<()> {
                     use std::pin::pin;
                     use std::task::Poll;
@@ -294,48 +794,355 @@ macro_rules! impl_watch_tuple {
                         Poll::Pending
                     }).await
                 }
+
+                fn any_pending(&self) -> bool {
+                    let ($($ty,)*) = self;
+                    false $(|| $ty.any_pending())*
+                }
             }
         };
     };
 }
 
-macro_rules! def_try_join_ty_fn {
+apply_tuples!(impl_watch_tuple);
+
+/// A thread-local variant of [`Injector`] for values that are not [`Send`] or [`Sync`].
+pub trait LocalInjector {
+    type Watch<T: Clone + 'static>: LocalWatch<Ty = T>;
+
+    /// Tells the injector that a type might be injected to it.
+    ///
+    /// See [`Injector::define`].
+    fn define<T>(&self)
+    where
+        T: 'static;
+
+    /// Inject a value of a given type into the injector.
+    fn inject<T>(&self, value: Result<T>)
+    where
+        T: 'static;
+
+    /// Retracts the value of a given type.
+    ///
+    /// See [`Injector::retract`].
+    fn retract<T>(&self)
+    where
+        T: 'static;
+
+    /// Reverts the state of a given type to undefined.
+    ///
+    /// See [`Injector::undefine`].
+    fn undefine<T>(&self)
+    where
+        T: 'static;
+
+    /// Watches for values of a given type in the injector.
+    fn watch<T>(&self) -> Self::Watch<T>
+    where
+        T: Clone + 'static;
+}
+
+impl<I> LocalInjector for Rc<I>
+where
+    I: LocalInjector,
+{
+    type Watch<T: Clone + 'static> = I::Watch<T>;
+
+    #[inline]
+    fn define<T>(&self)
+    where
+        T: 'static,
+    {
+        (**self).define::<T>();
+    }
+
+    #[inline]
+    fn inject<T>(&self, value: Result<T>)
+    where
+        T: 'static,
+    {
+        (**self).inject(value);
+    }
+
+    #[inline]
+    fn retract<T>(&self)
+    where
+        T: 'static,
+    {
+        (**self).retract::<T>();
+    }
+
+    #[inline]
+    fn undefine<T>(&self)
+    where
+        T: 'static,
+    {
+        (**self).undefine::<T>();
+    }
+
+    #[inline]
+    fn watch<T>(&self) -> Self::Watch<T>
+    where
+        T: Clone + 'static,
+    {
+        (**self).watch()
+    }
+}
+
+impl<I> LocalInjector for Box<I>
+where
+    I: LocalInjector,
+{
+    type Watch<T: Clone + 'static> = I::Watch<T>;
+
+    #[inline]
+    fn define<T>(&self)
+    where
+        T: 'static,
+    {
+        (**self).define::<T>();
+    }
+
+    #[inline]
+    fn inject<T>(&self, value: Result<T>)
+    where
+        T: 'static,
+    {
+        (**self).inject(value);
+    }
+
+    #[inline]
+    fn retract<T>(&self)
+    where
+        T: 'static,
+    {
+        (**self).retract::<T>();
+    }
+
+    #[inline]
+    fn undefine<T>(&self)
+    where
+        T: 'static,
+    {
+        (**self).undefine::<T>();
+    }
+
+    #[inline]
+    fn watch<T>(&self) -> Self::Watch<T>
+    where
+        T: Clone + 'static,
+    {
+        (**self).watch()
+    }
+}
+
+/// A task operating around a [`LocalInjector`].
+///
+/// Unlike [`InjectorTask`], the returned future does not need to be [`Send`].
+pub trait LocalInjectorTask<I> {
+    type Future: Future<Output = Result<()>>;
+
+    /// Run the task with the given injector.
+    ///
+    /// # Errors
+    ///
+    /// See [`InjectorTask::run`].
+    fn run(self, injector: I) -> Self::Future;
+}
+
+impl<I, F, Fut> LocalInjectorTask<I> for F
+where
+    F: FnOnce(I) -> Fut,
+    Fut: Future<Output = Result<()>> + 'static,
+{
+    type Future = Fut;
+
+    #[inline]
+    fn run(self, injector: I) -> Self::Future {
+        self(injector)
+    }
+}
+
+/// Watches for values of a given type in [`LocalInjector`].
+///
+/// This is the thread-local counterpart of [`Watch`], whose futures are not required to be
+/// [`Send`]. See [`Watch`] for the semantics of each method.
+pub trait LocalWatch {
+    /// The type of the value watched by the watch.
+    type Ty;
+
+    /// Immediately retrieves the current value.
+    ///
+    /// # Errors
+    ///
+    /// See [`Watch::current`].
+    fn current(&self) -> Result<Self::Ty>;
+
+    /// Immediately retrieves the current value.
+    ///
+    /// # Errors
+    ///
+    /// See [`Watch::current_optional`].
+    fn current_optional(&self) -> Result<Option<Self::Ty>>;
+
+    /// Waits until a value of type `T` is available if the injector is promised such value.
+    ///
+    /// # Errors
+    ///
+    /// See [`Watch::wait`].
+    fn wait(&mut self) -> impl Future<Output = Result<Self::Ty>>;
+
+    /// Waits until a value of type `T` is available if the injector is promised such value,
+    /// returning `None` otherwise.
+    ///
+    /// # Errors
+    ///
+    /// See [`Watch::wait_optional`].
+    fn wait_optional(&mut self) -> impl Future<Output = Result<Option<Self::Ty>>>;
+
+    /// Waits until a result value of type `T` is available regardless if the injector is promised
+    /// such value.
+    ///
+    /// # Errors
+    ///
+    /// See [`Watch::wait_always`].
+    fn wait_always(&mut self) -> impl Future<Output = Result<Self::Ty>>;
+
+    /// Waits until a value of type `T` is successfully created, regardless if the injector is
+    /// promised such value.
+    ///
+    /// # Errors
+    ///
+    /// See [`Watch::wait_ok`].
+    fn wait_ok(&mut self) -> impl Future<Output = Result<Self::Ty>>;
+
+    /// Waits until the value of type `T` changes.
+    ///
+    /// # Errors
+    ///
+    /// See [`Watch::changed`].
+    fn changed(&mut self) -> impl Future<Output = Result<()>>;
+
+    /// Returns `true` if any of the values this watch is made of is pending.
+    ///
+    /// See [`Watch::any_pending`].
+    fn any_pending(&self) -> bool {
+        self.current().is_err_and(|err| err.is_pending())
+    }
+}
+
+impl LocalWatch for () {
+    type Ty = ();
+
+    fn current(&self) -> Result<Self::Ty> {
+        Ok(())
+    }
+
+    fn current_optional(&self) -> Result<Option<Self::Ty>> {
+        Ok(Some(()))
+    }
+
+    async fn wait(&mut self) -> Result<Self::Ty> {
+        Ok(())
+    }
+
+    async fn wait_optional(&mut self) -> Result<Option<Self::Ty>> {
+        Ok(Some(()))
+    }
+
+    async fn wait_always(&mut self) -> Result<Self::Ty> {
+        Ok(())
+    }
+
+    async fn wait_ok(&mut self) -> Result<Self::Ty> {
+        Ok(())
+    }
+
+    fn changed(&mut self) -> impl Future<Output = Result<()>> {
+        std::future::pending()
+    }
+}
+
+macro_rules! impl_local_watch_tuple {
     ($($ty:ident),*) => {
-        async fn try_join_ty<$($ty,)* E>($($ty: $ty,)*) -> Result<($($ty::Ok,)*), E>
-        where
-            $($ty: $crate::macros::TryFuture<Err = E>,)*
-        {
-            use std::pin::pin;
-            use std::task::Poll;
-            use $crate::macros::TryFuture;
-
-            let ($($ty,)*) = ($($crate::macros::TryMaybeDone::new($ty),)*);
-            let ($(mut $ty,)*) = ($(pin!($ty),)*);
-
-            std::future::poll_fn(|cx| {
-                let mut done = true;
-
-                $(
-                    match $ty.as_mut().try_poll(cx) {
-                        Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
-                        poll => done &= poll.is_ready(),
-                    }
-                )*
-
-                if done {
-                    Poll::Ready(Ok((
-                        $({
-                            $ty.as_mut()
-                                .take_output()
-                                .expect("expected completed future")
-                        },)*
-                    )))
-                } else {
-                    Poll::Pending
+        #[allow(non_snake_case)]
+        #[allow(clippy::too_many_arguments)]
+        #[allow(clippy::type_complexity)]
+        const _: () = {
+            fn unwrap_option_tuple<$($ty,)*>($($ty: Option<$ty>,)*) -> Option<($($ty,)*)> {
+                Some(($($ty?,)*))
+            }
+
+            def_try_join_ty_fn!($($ty),*);
+
+            impl<$($ty,)*> LocalWatch for ($($ty,)*)
+            where
+                $($ty: LocalWatch,)*
+            {
+                type Ty = ($($ty::Ty,)*);
+
+                fn current(&self) -> Result<Self::Ty> {
+                    let ($($ty,)*) = self;
+                    let ($($ty,)*) = ($($ty.current()?,)*);
+                    Ok(($($ty,)*))
+                }
+
+                fn current_optional(&self) -> Result<Option<Self::Ty>> {
+                    let ($($ty,)*) = self;
+                    let ($($ty,)*) = ($($ty.current_optional()?,)*);
+                    Ok(unwrap_option_tuple($($ty,)*))
+                }
+
+                async fn wait(&mut self) -> Result<Self::Ty> {
+                    let ($($ty,)*) = self;
+                    let ($($ty,)*) = ($($ty.wait(),)*);
+                    try_join_ty($($ty),*).await
+                }
+
+                async fn wait_optional(&mut self) -> Result<Option<Self::Ty>> {
+                    let ($($ty,)*) = self;
+                    let ($($ty,)*) = ($($ty.wait_optional(),)*);
+                    let ($($ty,)*) = try_join_ty($($ty),*).await?;
+                    Ok(unwrap_option_tuple($($ty,)*))
                 }
-            }).await
-        }
+
+                async fn wait_always(&mut self) -> Result<Self::Ty> {
+                    let ($($ty,)*) = self;
+                    let ($($ty,)*) = ($($ty.wait_always(),)*);
+                    try_join_ty($($ty),*).await
+                }
+
+                async fn wait_ok(&mut self) -> Result<Self::Ty> {
+                    let ($($ty,)*) = self;
+                    let ($($ty,)*) = ($($ty.wait_ok(),)*);
+                    try_join_ty($($ty),*).await
+                }
+
+                async fn changed(&mut self) -> Result<()> {
+                    use std::pin::pin;
+                    use std::task::Poll;
+
+                    let ($($ty,)*) = self;
+                    let ($($ty,)*) = ($($ty.changed(),)*);
+                    let ($(mut $ty,)*) = ($(pin!($ty),)*);
+
+                    std::future::poll_fn(|cx| {
+                        $(
+                            if let Poll::Ready(res) = $ty.as_mut().poll(cx) {
+                                return Poll::Ready(res);
+                            }
+                        )*
+                        Poll::Pending
+                    }).await
+                }
+
+                fn any_pending(&self) -> bool {
+                    let ($($ty,)*) = self;
+                    false $(|| $ty.any_pending())*
+                }
+            }
+        };
     };
 }
 
-apply_tuples!(impl_watch_tuple);
+apply_tuples!(impl_local_watch_tuple);
@@ -0,0 +1,451 @@
+use std::any::{TypeId, type_name};
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::erased::{downcast, downcast_arc};
+use crate::injector::{
+    ErasedInjector, ErasedSelector, Injector, SelectInjector, Watch, erase_selector,
+};
+use crate::{Erased, Result};
+
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// An object-safe injector, usable as `dyn DynInjector`.
+///
+/// This trait is implemented for every [`ErasedInjector`], and `dyn DynInjector` implements
+/// [`Injector`] and [`SelectInjector`], so code can take an `Arc<dyn DynInjector>` instead of being generic over the
+/// injector type.
+pub trait DynInjector: Send + Sync {
+    /// See [`ErasedInjector::define_erased`].
+    fn define_dyn(&self, type_id: TypeId, type_name: &'static str);
+
+    /// See [`ErasedInjector::inject_erased`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::TypeMismatch`](crate::Error::TypeMismatch) without injecting anything if
+    /// the underlying type of `value` is not the type identified by `type_id`.
+    fn inject_dyn(
+        &self,
+        type_id: TypeId,
+        type_name: &'static str,
+        value: Result<Erased>,
+    ) -> Result<()>;
+
+    /// See [`ErasedInjector::inject_expiring_erased`].
+    ///
+    /// # Errors
+    ///
+    /// See [`inject_dyn`](Self::inject_dyn).
+    fn inject_expiring_dyn(
+        &self,
+        type_id: TypeId,
+        type_name: &'static str,
+        value: Result<Erased>,
+        expires_at: Instant,
+    ) -> Result<()>;
+
+    /// See [`ErasedInjector::retract_erased`].
+    fn retract_dyn(&self, type_id: TypeId, type_name: &'static str);
+
+    /// See [`ErasedInjector::undefine_erased`].
+    fn undefine_dyn(&self, type_id: TypeId, type_name: &'static str);
+
+    /// See [`ErasedInjector::watch_erased`].
+    fn watch_dyn(&self, type_id: TypeId, type_name: &'static str) -> DynWatch;
+
+    /// See [`ErasedInjector::select_erased`].
+    fn select_dyn(
+        &self,
+        source: (TypeId, &'static str),
+        target: (TypeId, &'static str),
+        selector: ErasedSelector,
+    );
+}
+
+impl<I> DynInjector for I
+where
+    I: ErasedInjector + Send + Sync,
+    I::ErasedWatch: Send + Sync + 'static,
+{
+    #[inline]
+    fn define_dyn(&self, type_id: TypeId, type_name: &'static str) {
+        self.define_erased(type_id, type_name);
+    }
+
+    #[inline]
+    fn inject_dyn(
+        &self,
+        type_id: TypeId,
+        type_name: &'static str,
+        value: Result<Erased>,
+    ) -> Result<()> {
+        self.inject_erased(type_id, type_name, value)
+    }
+
+    #[inline]
+    fn inject_expiring_dyn(
+        &self,
+        type_id: TypeId,
+        type_name: &'static str,
+        value: Result<Erased>,
+        expires_at: Instant,
+    ) -> Result<()> {
+        self.inject_expiring_erased(type_id, type_name, value, expires_at)
+    }
+
+    #[inline]
+    fn retract_dyn(&self, type_id: TypeId, type_name: &'static str) {
+        self.retract_erased(type_id, type_name);
+    }
+
+    #[inline]
+    fn undefine_dyn(&self, type_id: TypeId, type_name: &'static str) {
+        self.undefine_erased(type_id, type_name);
+    }
+
+    #[inline]
+    fn watch_dyn(&self, type_id: TypeId, type_name: &'static str) -> DynWatch {
+        DynWatch::new(self.watch_erased(type_id, type_name))
+    }
+
+    #[inline]
+    fn select_dyn(
+        &self,
+        source: (TypeId, &'static str),
+        target: (TypeId, &'static str),
+        selector: ErasedSelector,
+    ) {
+        self.select_erased(source, target, selector);
+    }
+}
+
+impl Injector for dyn DynInjector {
+    type Watch<T: Clone + Send + Sync + 'static> = DynTypedWatch<T>;
+
+    type SharedWatch<T: Send + Sync + 'static> = DynSharedWatch<T>;
+
+    #[inline]
+    fn define<T>(&self)
+    where
+        T: Send + Sync + 'static,
+    {
+        self.define_dyn(TypeId::of::<T>(), type_name::<T>());
+    }
+
+    #[inline]
+    fn inject<T>(&self, value: Result<T>)
+    where
+        T: Send + Sync + 'static,
+    {
+        // This can not fail, as the value is always of type `T`.
+        let _ = self.inject_dyn(TypeId::of::<T>(), type_name::<T>(), value.map(Erased::new));
+    }
+
+    #[inline]
+    fn inject_shared<T>(&self, value: Result<Arc<T>>)
+    where
+        T: Send + Sync + 'static,
+    {
+        // This can not fail, as the value is always of type `T`.
+        let _ = self.inject_dyn(
+            TypeId::of::<T>(),
+            type_name::<T>(),
+            value.map(Erased::from_arc),
+        );
+    }
+
+    #[inline]
+    fn inject_expiring<T>(&self, value: Result<T>, expires_at: Instant)
+    where
+        T: Send + Sync + 'static,
+    {
+        // This can not fail, as the value is always of type `T`.
+        let _ = self.inject_expiring_dyn(
+            TypeId::of::<T>(),
+            type_name::<T>(),
+            value.map(Erased::new),
+            expires_at,
+        );
+    }
+
+    #[inline]
+    fn inject_shared_expiring<T>(&self, value: Result<Arc<T>>, expires_at: Instant)
+    where
+        T: Send + Sync + 'static,
+    {
+        // This can not fail, as the value is always of type `T`.
+        let _ = self.inject_expiring_dyn(
+            TypeId::of::<T>(),
+            type_name::<T>(),
+            value.map(Erased::from_arc),
+            expires_at,
+        );
+    }
+
+    #[inline]
+    fn retract<T>(&self)
+    where
+        T: Send + Sync + 'static,
+    {
+        self.retract_dyn(TypeId::of::<T>(), type_name::<T>());
+    }
+
+    #[inline]
+    fn undefine<T>(&self)
+    where
+        T: Send + Sync + 'static,
+    {
+        self.undefine_dyn(TypeId::of::<T>(), type_name::<T>());
+    }
+
+    #[inline]
+    fn watch<T>(&self) -> Self::Watch<T>
+    where
+        T: Clone + Send + Sync + 'static,
+    {
+        DynTypedWatch::new(self.watch_dyn(TypeId::of::<T>(), type_name::<T>()))
+    }
+
+    #[inline]
+    fn watch_shared<T>(&self) -> Self::SharedWatch<T>
+    where
+        T: Send + Sync + 'static,
+    {
+        DynSharedWatch::new(self.watch_dyn(TypeId::of::<T>(), type_name::<T>()))
+    }
+}
+
+impl SelectInjector for dyn DynInjector {
+    #[inline]
+    fn select<T, U, F>(&self, selector: F)
+    where
+        T: Send + Sync + 'static,
+        U: PartialEq + Send + Sync + 'static,
+        F: Fn(&T) -> U + Send + Sync + 'static,
+    {
+        self.select_dyn(
+            (TypeId::of::<T>(), type_name::<T>()),
+            (TypeId::of::<U>(), type_name::<U>()),
+            erase_selector(selector),
+        );
+    }
+}
+
+/// An object-safe counterpart of [`Watch`] for type-erased values.
+trait WatchObject: Send + Sync {
+    fn current(&self) -> Result<Erased>;
+
+    fn current_optional(&self) -> Result<Option<Erased>>;
+
+    fn wait(&mut self) -> BoxFuture<'_, Result<Erased>>;
+
+    fn wait_optional(&mut self) -> BoxFuture<'_, Result<Option<Erased>>>;
+
+    fn wait_always(&mut self) -> BoxFuture<'_, Result<Erased>>;
+
+    fn wait_ok(&mut self) -> BoxFuture<'_, Result<Erased>>;
+
+    fn current_last_ok(&self) -> Result<Erased>;
+
+    fn wait_last_ok(&mut self) -> BoxFuture<'_, Result<Erased>>;
+
+    fn changed(&mut self) -> BoxFuture<'_, Result<()>>;
+}
+
+impl<W> WatchObject for W
+where
+    W: Watch<Ty = Erased> + Send + Sync,
+{
+    fn current(&self) -> Result<Erased> {
+        Watch::current(self)
+    }
+
+    fn current_optional(&self) -> Result<Option<Erased>> {
+        Watch::current_optional(self)
+    }
+
+    fn wait(&mut self) -> BoxFuture<'_, Result<Erased>> {
+        Box::pin(Watch::wait(self))
+    }
+
+    fn wait_optional(&mut self) -> BoxFuture<'_, Result<Option<Erased>>> {
+        Box::pin(Watch::wait_optional(self))
+    }
+
+    fn wait_always(&mut self) -> BoxFuture<'_, Result<Erased>> {
+        Box::pin(Watch::wait_always(self))
+    }
+
+    fn wait_ok(&mut self) -> BoxFuture<'_, Result<Erased>> {
+        Box::pin(Watch::wait_ok(self))
+    }
+
+    fn current_last_ok(&self) -> Result<Erased> {
+        Watch::current_last_ok(self)
+    }
+
+    fn wait_last_ok(&mut self) -> BoxFuture<'_, Result<Erased>> {
+        Box::pin(Watch::wait_last_ok(self))
+    }
+
+    fn changed(&mut self) -> BoxFuture<'_, Result<()>> {
+        Box::pin(Watch::changed(self))
+    }
+}
+
+/// Watches for type-erased values of a given type in [`DynInjector`].
+pub struct DynWatch {
+    inner: Box<dyn WatchObject>,
+}
+
+impl DynWatch {
+    /// Wraps a watch of type-erased values.
+    pub fn new<W>(watch: W) -> Self
+    where
+        W: Watch<Ty = Erased> + Send + Sync + 'static,
+    {
+        Self {
+            inner: Box::new(watch),
+        }
+    }
+}
+
+impl std::fmt::Debug for DynWatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DynWatch").finish_non_exhaustive()
+    }
+}
+
+impl Watch for DynWatch {
+    type Ty = Erased;
+
+    #[inline]
+    fn current(&self) -> Result<Erased> {
+        self.inner.current()
+    }
+
+    #[inline]
+    fn current_optional(&self) -> Result<Option<Erased>> {
+        self.inner.current_optional()
+    }
+
+    #[inline]
+    fn wait(&mut self) -> impl Future<Output = Result<Erased>> + Send {
+        self.inner.wait()
+    }
+
+    #[inline]
+    fn wait_optional(&mut self) -> impl Future<Output = Result<Option<Erased>>> + Send {
+        self.inner.wait_optional()
+    }
+
+    #[inline]
+    fn wait_always(&mut self) -> impl Future<Output = Result<Erased>> + Send {
+        self.inner.wait_always()
+    }
+
+    #[inline]
+    fn wait_ok(&mut self) -> impl Future<Output = Result<Erased>> + Send {
+        self.inner.wait_ok()
+    }
+
+    #[inline]
+    fn current_last_ok(&self) -> Result<Erased> {
+        self.inner.current_last_ok()
+    }
+
+    #[inline]
+    fn wait_last_ok(&mut self) -> impl Future<Output = Result<Erased>> + Send {
+        self.inner.wait_last_ok()
+    }
+
+    #[inline]
+    fn changed(&mut self) -> impl Future<Output = Result<()>> + Send {
+        self.inner.changed()
+    }
+}
+
+/// Watches for values of a given type in [`DynInjector`].
+#[derive(Debug)]
+pub struct DynTypedWatch<T> {
+    inner: DynWatch,
+    _marker: PhantomData<fn() -> T>,
+}
+
+/// Watches for shared values of a given type in [`DynInjector`].
+#[derive(Debug)]
+pub struct DynSharedWatch<T> {
+    inner: DynWatch,
+    _marker: PhantomData<fn() -> T>,
+}
+
+macro_rules! impl_dyn_typed_watch {
+    ($watch:ident, $ty:ty, $downcast:ident, [$($bounds:tt)*]) => {
+        impl<T> $watch<T> {
+            /// Wraps a watch of type-erased values of type `T`.
+            pub const fn new(inner: DynWatch) -> Self {
+                Self {
+                    inner,
+                    _marker: PhantomData,
+                }
+            }
+        }
+
+        impl<T> Watch for $watch<T>
+        where
+            T: $($bounds)*,
+        {
+            type Ty = $ty;
+
+            fn current(&self) -> Result<$ty> {
+                Watch::current(&self.inner).and_then($downcast::<T>)
+            }
+
+            fn current_optional(&self) -> Result<Option<$ty>> {
+                Watch::current_optional(&self.inner)
+                    .and_then(|value| value.map($downcast::<T>).transpose())
+            }
+
+            async fn wait(&mut self) -> Result<$ty> {
+                Watch::wait(&mut self.inner).await.and_then($downcast::<T>)
+            }
+
+            async fn wait_optional(&mut self) -> Result<Option<$ty>> {
+                Watch::wait_optional(&mut self.inner)
+                    .await
+                    .and_then(|value| value.map($downcast::<T>).transpose())
+            }
+
+            async fn wait_always(&mut self) -> Result<$ty> {
+                Watch::wait_always(&mut self.inner).await.and_then($downcast::<T>)
+            }
+
+            async fn wait_ok(&mut self) -> Result<$ty> {
+                Watch::wait_ok(&mut self.inner).await.and_then($downcast::<T>)
+            }
+
+            fn current_last_ok(&self) -> Result<$ty> {
+                Watch::current_last_ok(&self.inner).and_then($downcast::<T>)
+            }
+
+            async fn wait_last_ok(&mut self) -> Result<$ty> {
+                Watch::wait_last_ok(&mut self.inner).await.and_then($downcast::<T>)
+            }
+
+            async fn changed(&mut self) -> Result<()> {
+                Watch::changed(&mut self.inner).await
+            }
+        }
+    };
+}
+
+impl_dyn_typed_watch!(DynTypedWatch, T, downcast, [Clone + Send + Sync + 'static]);
+impl_dyn_typed_watch!(
+    DynSharedWatch,
+    Arc<T>,
+    downcast_arc,
+    [Send + Sync + 'static]
+);
@@ -12,7 +12,7 @@ pub mod error;
 pub mod injector;
 pub mod runtime;
 
-pub use erased::Erased;
+pub use erased::{Erased, LocalErased};
 pub use error::{Error, Result};
-pub use injector::Injector;
-pub use runtime::Runtime;
+pub use injector::{ErasedInjector, Injector, LocalInjector};
+pub use runtime::{LocalRuntime, Runtime, Timer};
@@ -20,6 +20,45 @@ macro_rules! apply_tuples {
     };
 }
 
+macro_rules! def_try_join_ty_fn {
+    ($($ty:ident),*) => {
+        async fn try_join_ty<$($ty,)* E>($($ty: $ty,)*) -> Result<($($ty::Ok,)*), E>
+        where
+            $($ty: $crate::macros::TryFuture<Err = E>,)*
+        {
+            use std::pin::pin;
+            use std::task::Poll;
+            use $crate::macros::TryFuture;
+
+            let ($($ty,)*) = ($($crate::macros::TryMaybeDone::new($ty),)*);
+            let ($(mut $ty,)*) = ($(pin!($ty),)*);
+
+            std::future::poll_fn(|cx| {
+                let mut done = true;
+
+                $(
+                    match $ty.as_mut().try_poll(cx) {
+                        Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                        poll => done &= poll.is_ready(),
+                    }
+                )*
+
+                if done {
+                    Poll::Ready(Ok((
+                        $({
+                            $ty.as_mut()
+                                .take_output()
+                                .expect("expected completed future")
+                        },)*
+                    )))
+                } else {
+                    Poll::Pending
+                }
+            }).await
+        }
+    };
+}
+
 pub trait TryFuture: Future {
     type Ok;
 
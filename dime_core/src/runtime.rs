@@ -1,9 +1,16 @@
 //! Traits for async runtime.
 
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use crate::Error;
+
 /// An async runtime to spawn asynchronous tasks.
 pub trait Runtime: Clone + Send + Sync + 'static {
     /// A handle to a running task.
-    type Task<T>: Task<Output = T, Error: Send + 'static, Join: Send + 'static> + Send
+    type Task<T>: Task<Output = T, Error: std::error::Error + Send + Sync + 'static, Join: Send + 'static>
+        + Send
     where
         T: Send + 'static;
 
@@ -12,8 +19,183 @@ pub trait Runtime: Clone + Send + Sync + 'static {
     where
         F: Future + Send + 'static,
         F::Output: Send + 'static;
+
+    /// Runs a blocking function on a thread where blocking is acceptable.
+    ///
+    /// Use this for CPU-heavy work or blocking I/O that would otherwise stall the threads running
+    /// asynchronous tasks.
+    fn spawn_blocking<F, T>(&self, f: F) -> Self::Task<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static;
+}
+
+/// An async runtime to spawn asynchronous tasks that are not [`Send`].
+///
+/// Tasks spawned by this runtime are run on the thread that spawned them.
+pub trait LocalRuntime: Clone + 'static {
+    /// A handle to a running task.
+    type Task<T>: Task<Output = T, Error: std::error::Error + 'static>
+    where
+        T: 'static;
+
+    /// Spawns an asynchronous task on the current thread.
+    fn spawn_local<F>(&self, fut: F) -> Self::Task<F::Output>
+    where
+        F: Future + 'static,
+        F::Output: 'static;
+}
+
+/// An async runtime that provides timers.
+pub trait Timer: Clone + Send + Sync + 'static {
+    /// The future returned by [`sleep`](Self::sleep) and [`sleep_until`](Self::sleep_until)
+    /// methods.
+    type Sleep: Future<Output = ()> + Send + 'static;
+
+    /// Returns the current instant according to the clock of the timer.
+    ///
+    /// The default implementation returns [`Instant::now`]. Runtimes with their own notion of time
+    /// (e.g. a paused or virtual clock) should override this method.
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    /// Waits until `deadline` is reached.
+    fn sleep_until(&self, deadline: Instant) -> Self::Sleep;
+
+    /// Waits until `duration` has elapsed.
+    fn sleep(&self, duration: Duration) -> Self::Sleep {
+        self.sleep_until(self.now() + duration)
+    }
+
+    /// Creates an [`Interval`] that yields once every `period`.
+    ///
+    /// The first tick completes immediately.
+    fn interval(&self, period: Duration) -> Interval<Self> {
+        Interval::new(self.clone(), period)
+    }
+
+    /// Requires `future` to complete before `duration` has elapsed.
+    fn timeout<F>(&self, duration: Duration, future: F) -> Timeout<F, Self::Sleep>
+    where
+        F: Future,
+    {
+        Timeout::new(future, self.sleep(duration))
+    }
 }
 
+/// Yields at a fixed period, created by [`Timer::interval`].
+#[derive(Debug)]
+pub struct Interval<Tm> {
+    timer: Tm,
+    period: Duration,
+    next: Instant,
+}
+
+impl<Tm> Interval<Tm>
+where
+    Tm: Timer,
+{
+    /// Creates a new `Interval` whose first tick completes immediately.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `period` is zero.
+    pub fn new(timer: Tm, period: Duration) -> Self {
+        assert!(!period.is_zero(), "`period` must be non-zero");
+
+        let next = timer.now();
+        Self {
+            timer,
+            period,
+            next,
+        }
+    }
+
+    /// Returns the period of the interval.
+    pub const fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// Waits until the next tick and returns the instant it was scheduled at.
+    ///
+    /// If ticks were missed (e.g. because the caller took longer than the period between calls),
+    /// the missed ticks are skipped and the next tick is scheduled one period from now.
+    pub async fn tick(&mut self) -> Instant {
+        let deadline = self.next;
+        self.timer.sleep_until(deadline).await;
+
+        let now = self.timer.now();
+        self.next = if now >= deadline + self.period {
+            now + self.period
+        } else {
+            deadline + self.period
+        };
+
+        deadline
+    }
+
+    /// Resets the interval so that the next tick completes one period from now.
+    pub fn reset(&mut self) {
+        self.next = self.timer.now() + self.period;
+    }
+}
+
+pin_project_lite::pin_project! {
+    /// A future that requires the inner future to complete before a deadline, created by
+    /// [`Timer::timeout`].
+    #[derive(Debug)]
+    #[must_use = "futures do nothing unless you `.await` or poll them"]
+    pub struct Timeout<F, S> {
+        #[pin]
+        future: F,
+        #[pin]
+        sleep: S,
+    }
+}
+
+impl<F, S> Timeout<F, S> {
+    /// Wraps `future` so that it fails with [`Elapsed`] once `sleep` completes.
+    pub const fn new(future: F, sleep: S) -> Self {
+        Self { future, sleep }
+    }
+
+    /// Unwraps `self` and returns the inner future.
+    pub fn into_inner(self) -> F {
+        self.future
+    }
+}
+
+impl<F, S> Future for Timeout<F, S>
+where
+    F: Future,
+    S: Future<Output = ()>,
+{
+    type Output = Result<F::Output, Elapsed>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+
+        if let Poll::Ready(output) = this.future.poll(cx) {
+            return Poll::Ready(Ok(output));
+        }
+
+        this.sleep.poll(cx).map(|()| Err(Elapsed(())))
+    }
+}
+
+/// An error returned by [`Timeout`] when the deadline has elapsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Elapsed(());
+
+impl std::fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("deadline has elapsed")
+    }
+}
+
+impl std::error::Error for Elapsed {}
+
 /// A handle to a running task.
 pub trait Task {
     /// The output type returned by the task.
@@ -30,6 +212,10 @@ pub trait Task {
 
     /// Takes the handle and yields until the task is completed.
     fn join(self) -> Self::Join;
+
+    /// Converts an error returned by [`join`](Self::join) into [`Error::Panicked`] if the task
+    /// panicked, or [`Error::Cancelled`] if it was aborted.
+    fn join_error(error: Self::Error) -> Error;
 }
 
 /// Aborts the wrapped task when it is dropped.
@@ -1,4 +1,3 @@
 //! Utilities for `dime` library.
 
-#[cfg(feature = "tokio")]
 pub mod runtime;
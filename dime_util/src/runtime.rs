@@ -1,7 +1,16 @@
 //! Runtime utilities.
 
+mod join;
+pub use join::{Join, JoinError};
+
+mod test;
+pub use test::{TestRuntime, TestSleep, TestTask};
+
+mod thread_pool;
+pub use thread_pool::{ThreadPoolRuntime, ThreadPoolSleep, ThreadPoolTask};
+
 #[cfg(feature = "tokio")]
 mod tokio;
 
 #[cfg(feature = "tokio")]
-pub use tokio::{TokioRuntime, TokioTask};
+pub use tokio::{TokioLocalRuntime, TokioRuntime, TokioTask};
@@ -0,0 +1,180 @@
+use std::any::Any;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::pin::{Pin, pin};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::task::{Context, Poll, Waker};
+
+use dime_core::Error;
+use dime_core::error::panic_message;
+
+/// A future that yields the output of a task spawned by a runtime in this module.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Join<T> {
+    state: Arc<JoinState<T>>,
+}
+
+/// An error returned when a task spawned by a runtime in this module fails to complete.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JoinError {
+    panic: Option<String>,
+}
+
+#[derive(Debug)]
+pub(super) struct JoinState<T> {
+    inner: Mutex<JoinInner<T>>,
+}
+
+#[derive(Debug)]
+struct JoinInner<T> {
+    result: Option<Result<T, JoinError>>,
+    waker: Option<Waker>,
+}
+
+/// Completes the join state with a cancellation error if dropped before completion.
+pub(super) struct Completer<T>(Option<Arc<JoinState<T>>>);
+
+pub(super) fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Wraps a future so that its output, panic or cancellation is reported to the returned state.
+///
+/// The wrapped future is `Send` whenever `fut` and its output are.
+pub(super) fn wrap_future<F>(fut: F) -> (impl Future<Output = ()>, Arc<JoinState<F::Output>>)
+where
+    F: Future + 'static,
+    F::Output: 'static,
+{
+    let state = JoinState::new();
+    let mut completer = Completer(Some(Arc::clone(&state)));
+
+    let wrapped = async move {
+        let mut fut = pin!(fut);
+        let output = std::future::poll_fn(|cx| {
+            match catch_unwind(AssertUnwindSafe(|| fut.as_mut().poll(cx))) {
+                Ok(Poll::Ready(output)) => Poll::Ready(Ok(output)),
+                Ok(Poll::Pending) => Poll::Pending,
+                Err(payload) => Poll::Ready(Err(JoinError::panicked(&*payload))),
+            }
+        })
+        .await;
+        completer.complete(output);
+    };
+
+    (wrapped, state)
+}
+
+/// Wraps a function so that its output or panic is reported to the returned state.
+pub(super) fn wrap_blocking<F, T>(f: F) -> (impl FnOnce() + Send, Arc<JoinState<T>>)
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    let state = JoinState::new();
+    let mut completer = Completer(Some(Arc::clone(&state)));
+
+    let wrapped = move || {
+        let output =
+            catch_unwind(AssertUnwindSafe(f)).map_err(|payload| JoinError::panicked(&*payload));
+        completer.complete(output);
+    };
+
+    (wrapped, state)
+}
+
+impl<T> Join<T> {
+    pub(super) const fn new(state: Arc<JoinState<T>>) -> Self {
+        Self { state }
+    }
+}
+
+impl<T> Future for Join<T> {
+    type Output = Result<T, JoinError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut inner = lock(&self.state.inner);
+        match inner.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                inner.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl JoinError {
+    fn panicked(payload: &(dyn Any + Send)) -> Self {
+        Self {
+            panic: Some(panic_message(payload).to_string()),
+        }
+    }
+
+    /// Returns `true` if the task was aborted.
+    pub const fn is_cancelled(&self) -> bool {
+        self.panic.is_none()
+    }
+
+    /// Returns `true` if the task panicked.
+    pub const fn is_panic(&self) -> bool {
+        self.panic.is_some()
+    }
+
+    /// Returns the panic message if the task panicked.
+    pub fn panic_message(&self) -> Option<&str> {
+        self.panic.as_deref()
+    }
+}
+
+impl std::fmt::Display for JoinError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.panic {
+            Some(message) => write!(f, "task panicked with message `{message}`"),
+            None => f.write_str("task was cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for JoinError {}
+
+impl From<JoinError> for Error {
+    fn from(error: JoinError) -> Self {
+        match error.panic {
+            Some(message) => Self::panicked(message),
+            None => Self::Cancelled,
+        }
+    }
+}
+
+impl<T> JoinState<T> {
+    fn new() -> Arc<Self> {
+        Arc::new(Self {
+            inner: Mutex::new(JoinInner {
+                result: None,
+                waker: None,
+            }),
+        })
+    }
+}
+
+impl<T> Completer<T> {
+    fn complete(&mut self, result: Result<T, JoinError>) {
+        if let Some(state) = self.0.take() {
+            let waker = {
+                let mut inner = lock(&state.inner);
+                inner.result = Some(result);
+                inner.waker.take()
+            };
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+        }
+    }
+}
+
+impl<T> Drop for Completer<T> {
+    fn drop(&mut self) {
+        self.complete(Err(JoinError { panic: None }));
+    }
+}
@@ -0,0 +1,728 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BinaryHeap, HashMap};
+use std::pin::{Pin, pin};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::task::{Context, Poll, Wake, Waker};
+use std::time::{Duration, Instant};
+
+use dime_core::Error;
+use dime_core::runtime::{LocalRuntime, Runtime, Task, Timer};
+
+use super::join::{self, Join, JoinError, JoinState, lock};
+
+/// A single-threaded, deterministic runtime with virtual time, intended for tests.
+///
+/// Spawned tasks only make progress while the runtime is driven by [`run_until_stalled`],
+/// [`advance`] or [`block_on`]. Runnable tasks are polled in a pseudo-random order determined by
+/// a seed, so a failing interleaving can be reproduced with [`with_seed`].
+///
+/// Time does not pass on its own: it is moved forward by [`advance`], or by [`block_on`] when no
+/// task can make progress otherwise. Blocking tasks are run inline when polled.
+///
+/// Tasks spawned with [`LocalRuntime::spawn_local`] must be driven from the thread that spawned
+/// them; polling them from another thread panics.
+///
+/// [`run_until_stalled`]: Self::run_until_stalled
+/// [`advance`]: Self::advance
+/// [`block_on`]: Self::block_on
+/// [`with_seed`]: Self::with_seed
+#[derive(Clone, Debug)]
+pub struct TestRuntime {
+    shared: Arc<Shared>,
+}
+
+/// A handle to a task spawned by [`TestRuntime`].
+#[derive(Debug)]
+pub struct TestTask<T> {
+    join: Arc<JoinState<T>>,
+    id: u64,
+    shared: Weak<Shared>,
+}
+
+/// A future that completes at a virtual deadline, created by [`TestRuntime`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct TestSleep {
+    shared: Arc<Shared>,
+    id: u64,
+    deadline: Instant,
+    registered: Option<Waker>,
+}
+
+#[derive(Debug)]
+struct Shared {
+    state: Mutex<State>,
+    next_id: AtomicU64,
+}
+
+#[derive(Debug)]
+struct State {
+    now: Instant,
+    rng: u64,
+    tasks: BTreeMap<u64, TaskSlot>,
+    ready: Vec<u64>,
+    timers: BinaryHeap<TimerEntry>,
+}
+
+type BoxFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+type LocalBoxFuture = Pin<Box<dyn Future<Output = ()>>>;
+
+thread_local! {
+    /// The futures of the local tasks spawned on this thread, by key.
+    static LOCAL_FUTURES: RefCell<HashMap<u64, LocalBoxFuture>> = RefCell::default();
+}
+
+/// The source of keys into [`LOCAL_FUTURES`], shared by all runtimes.
+static NEXT_LOCAL_KEY: AtomicU64 = AtomicU64::new(0);
+
+/// A `Send` stand-in for a local future, which stays on the thread that spawned it.
+struct LocalTask {
+    key: u64,
+}
+
+struct TaskSlot {
+    future: Option<BoxFuture>,
+    scheduled: bool,
+    aborted: bool,
+}
+
+struct TaskWaker {
+    id: u64,
+    shared: Weak<Shared>,
+}
+
+#[derive(Debug)]
+struct TimerEntry {
+    deadline: Instant,
+    id: u64,
+    waker: Waker,
+}
+
+const DEFAULT_SEED: u64 = 0x2545_f491_4f6c_dd1d;
+
+impl Default for TestRuntime {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TestRuntime {
+    /// Creates a runtime with the default seed.
+    pub fn new() -> Self {
+        Self::with_seed(DEFAULT_SEED)
+    }
+
+    /// Creates a runtime whose scheduling order is determined by the given seed.
+    pub fn with_seed(seed: u64) -> Self {
+        let state = State {
+            now: Instant::now(),
+            // Xorshift requires a non-zero state.
+            rng: if seed == 0 { DEFAULT_SEED } else { seed },
+            tasks: BTreeMap::new(),
+            ready: Vec::new(),
+            timers: BinaryHeap::new(),
+        };
+
+        Self {
+            shared: Arc::new(Shared {
+                state: Mutex::new(state),
+                next_id: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    /// Polls runnable tasks until none of them can make progress without time passing.
+    ///
+    /// # Panics
+    ///
+    /// This method must not be called from within a task of this runtime.
+    pub fn run_until_stalled(&self) {
+        loop {
+            let (id, mut future) = {
+                let mut state = lock(&self.shared.state);
+                if state.ready.is_empty() {
+                    return;
+                }
+
+                let index = (state.next_random() % state.ready.len() as u64) as usize;
+                let id = state.ready.swap_remove(index);
+                let Some(slot) = state.tasks.get_mut(&id) else {
+                    continue;
+                };
+                slot.scheduled = false;
+                let Some(future) = slot.future.take() else {
+                    continue;
+                };
+                (id, future)
+            };
+
+            let waker = Waker::from(Arc::new(TaskWaker {
+                id,
+                shared: Arc::downgrade(&self.shared),
+            }));
+            let mut cx = Context::from_waker(&waker);
+            let done = future.as_mut().poll(&mut cx).is_ready();
+
+            // Finished and aborted futures are dropped outside of the lock, since dropping them
+            // may wake other tasks.
+            let _finished = {
+                let mut state = lock(&self.shared.state);
+                match state.tasks.get_mut(&id) {
+                    Some(slot) if !done && !slot.aborted => {
+                        slot.future = Some(future);
+                        None
+                    }
+                    _ => {
+                        state.tasks.remove(&id);
+                        Some(future)
+                    }
+                }
+            };
+        }
+    }
+
+    /// Moves the virtual time forward, firing timers and running tasks along the way.
+    pub fn advance(&self, duration: Duration) {
+        let target = lock(&self.shared.state).now + duration;
+
+        loop {
+            self.run_until_stalled();
+            if !self.fire_next_timer(Some(target)) {
+                break;
+            }
+        }
+
+        let mut state = lock(&self.shared.state);
+        state.now = state.now.max(target);
+        drop(state);
+
+        self.run_until_stalled();
+    }
+
+    /// Runs a future to completion on the current thread, driving the spawned tasks.
+    ///
+    /// The virtual time is moved forward to the next timer whenever neither the future nor the
+    /// spawned tasks can make progress.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the future can never complete, i.e. when no task is runnable and no
+    /// timer is pending.
+    pub fn block_on<F>(&self, future: F) -> F::Output
+    where
+        F: Future,
+    {
+        struct FlagWaker(AtomicBool);
+
+        impl Wake for FlagWaker {
+            fn wake(self: Arc<Self>) {
+                self.0.store(true, Ordering::Release);
+            }
+        }
+
+        let flag = Arc::new(FlagWaker(AtomicBool::new(false)));
+        let waker = Waker::from(Arc::clone(&flag));
+        let mut cx = Context::from_waker(&waker);
+        let mut future = pin!(future);
+
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+
+            loop {
+                self.run_until_stalled();
+                if flag.0.swap(false, Ordering::AcqRel) {
+                    break;
+                }
+                assert!(
+                    self.fire_next_timer(None),
+                    "`block_on` future can never complete"
+                );
+            }
+        }
+    }
+
+    /// Returns `true` if no task is runnable and no timer is pending.
+    pub fn is_idle(&self) -> bool {
+        let state = lock(&self.shared.state);
+        state.ready.is_empty() && state.timers.is_empty()
+    }
+
+    /// Asserts that no task is runnable and no timer is pending.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the runtime is not idle.
+    #[track_caller]
+    pub fn assert_idle(&self) {
+        let state = lock(&self.shared.state);
+        assert!(
+            state.ready.is_empty() && state.timers.is_empty(),
+            "runtime is not idle: {} runnable tasks, {} pending timers",
+            state.ready.len(),
+            state.timers.len(),
+        );
+    }
+
+    /// Fires the timers with the earliest deadline, unless it is later than `limit`.
+    ///
+    /// Returns `false` if there was no timer to fire.
+    fn fire_next_timer(&self, limit: Option<Instant>) -> bool {
+        let wakers = {
+            let mut state = lock(&self.shared.state);
+            let Some(deadline) = state.timers.peek().map(|entry| entry.deadline) else {
+                return false;
+            };
+            if limit.is_some_and(|limit| deadline > limit) {
+                return false;
+            }
+
+            state.now = state.now.max(deadline);
+            let mut wakers = Vec::new();
+            while state
+                .timers
+                .peek()
+                .is_some_and(|entry| entry.deadline <= deadline)
+            {
+                wakers.extend(state.timers.pop().map(|entry| entry.waker));
+            }
+            wakers
+        };
+
+        wakers.into_iter().for_each(Waker::wake);
+        true
+    }
+
+    fn next_id(&self) -> u64 {
+        self.shared.next_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    fn insert_task<T>(&self, future: BoxFuture, join: Arc<JoinState<T>>) -> TestTask<T> {
+        let id = self.next_id();
+
+        let mut state = lock(&self.shared.state);
+        state.tasks.insert(
+            id,
+            TaskSlot {
+                future: Some(future),
+                scheduled: true,
+                aborted: false,
+            },
+        );
+        state.ready.push(id);
+
+        TestTask {
+            join,
+            id,
+            shared: Arc::downgrade(&self.shared),
+        }
+    }
+}
+
+impl Runtime for TestRuntime {
+    type Task<T>
+        = TestTask<T>
+    where
+        T: Send + 'static;
+
+    fn spawn<F>(&self, fut: F) -> Self::Task<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let (wrapped, join) = join::wrap_future(fut);
+        self.insert_task(Box::pin(wrapped), join)
+    }
+
+    fn spawn_blocking<F, T>(&self, f: F) -> Self::Task<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let (wrapped, join) = join::wrap_blocking(f);
+        let TestTask { id, shared, .. } = self.spawn(async move { wrapped() });
+        TestTask { join, id, shared }
+    }
+}
+
+impl LocalRuntime for TestRuntime {
+    type Task<T>
+        = TestTask<T>
+    where
+        T: 'static;
+
+    fn spawn_local<F>(&self, fut: F) -> Self::Task<F::Output>
+    where
+        F: Future + 'static,
+        F::Output: 'static,
+    {
+        let (wrapped, join) = join::wrap_future(fut);
+        let key = NEXT_LOCAL_KEY.fetch_add(1, Ordering::Relaxed);
+        LOCAL_FUTURES.with_borrow_mut(|futures| futures.insert(key, Box::pin(wrapped)));
+        self.insert_task(Box::pin(LocalTask { key }), join)
+    }
+}
+
+impl Timer for TestRuntime {
+    type Sleep = TestSleep;
+
+    fn now(&self) -> Instant {
+        lock(&self.shared.state).now
+    }
+
+    fn sleep_until(&self, deadline: Instant) -> Self::Sleep {
+        TestSleep {
+            shared: Arc::clone(&self.shared),
+            id: self.next_id(),
+            deadline,
+            registered: None,
+        }
+    }
+}
+
+impl<T> Task for TestTask<T> {
+    type Output = T;
+
+    type Error = JoinError;
+
+    type Join = Join<T>;
+
+    fn abort(&self) {
+        let Some(shared) = self.shared.upgrade() else {
+            return;
+        };
+
+        let _future = {
+            let mut state = lock(&shared.state);
+            let Some(slot) = state.tasks.get_mut(&self.id) else {
+                return;
+            };
+            slot.aborted = true;
+            // If the task is being polled, it is dropped once the poll returns instead.
+            let future = slot.future.take();
+            if future.is_some() {
+                state.tasks.remove(&self.id);
+            }
+            future
+        };
+    }
+
+    fn join(self) -> Self::Join {
+        Join::new(self.join)
+    }
+
+    fn join_error(error: Self::Error) -> Error {
+        error.into()
+    }
+}
+
+impl Future for TestSleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        let mut state = lock(&this.shared.state);
+        if state.now >= this.deadline {
+            return Poll::Ready(());
+        }
+
+        if !this
+            .registered
+            .as_ref()
+            .is_some_and(|waker| waker.will_wake(cx.waker()))
+        {
+            state.timers.retain(|entry| entry.id != this.id);
+            state.timers.push(TimerEntry {
+                deadline: this.deadline,
+                id: this.id,
+                waker: cx.waker().clone(),
+            });
+            this.registered = Some(cx.waker().clone());
+        }
+
+        Poll::Pending
+    }
+}
+
+impl Drop for TestSleep {
+    fn drop(&mut self) {
+        if self.registered.is_some() {
+            lock(&self.shared.state)
+                .timers
+                .retain(|entry| entry.id != self.id);
+        }
+    }
+}
+
+impl Future for LocalTask {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let mut future = LOCAL_FUTURES
+            .with_borrow_mut(|futures| futures.remove(&self.key))
+            .expect("local task polled on a thread other than the one that spawned it");
+
+        // The future is polled outside of the borrow, since it may spawn other local tasks.
+        let poll = future.as_mut().poll(cx);
+        if poll.is_pending() {
+            LOCAL_FUTURES.with_borrow_mut(|futures| futures.insert(self.key, future));
+        }
+        poll
+    }
+}
+
+impl Drop for LocalTask {
+    fn drop(&mut self) {
+        // A future dropped from another thread stays behind until its own thread exits.
+        let future = LOCAL_FUTURES
+            .try_with(|futures| futures.borrow_mut().remove(&self.key))
+            .ok()
+            .flatten();
+        drop(future);
+    }
+}
+
+impl State {
+    fn next_random(&mut self) -> u64 {
+        // xorshift64
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        self.rng
+    }
+}
+
+impl Wake for TaskWaker {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        let Some(shared) = self.shared.upgrade() else {
+            return;
+        };
+
+        let mut state = lock(&shared.state);
+        if let Some(slot) = state.tasks.get_mut(&self.id)
+            && !slot.scheduled
+        {
+            slot.scheduled = true;
+            state.ready.push(self.id);
+        }
+    }
+}
+
+impl std::fmt::Debug for TaskSlot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TaskSlot")
+            .field("scheduled", &self.scheduled)
+            .field("aborted", &self.aborted)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for TimerEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for TimerEntry {}
+
+impl PartialOrd for TimerEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TimerEntry {
+    // Reversed, so that `BinaryHeap` pops the earliest deadline first.
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (other.deadline, other.id).cmp(&(self.deadline, self.id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use std::sync::atomic::AtomicUsize;
+
+    use super::*;
+
+    #[test]
+    fn test_run_until_stalled() {
+        let rt = TestRuntime::new();
+        let counter = Arc::new(AtomicUsize::new(0));
+
+        for _ in 0..4 {
+            let counter = Arc::clone(&counter);
+            rt.spawn(async move {
+                counter.fetch_add(1, Ordering::Relaxed);
+            });
+        }
+        assert_eq!(counter.load(Ordering::Relaxed), 0);
+
+        rt.run_until_stalled();
+        assert_eq!(counter.load(Ordering::Relaxed), 4);
+        rt.assert_idle();
+    }
+
+    #[test]
+    fn test_advance() {
+        let rt = TestRuntime::new();
+        let start = rt.now();
+        let done = Arc::new(AtomicBool::new(false));
+
+        let cloned = rt.clone();
+        let flag = Arc::clone(&done);
+        rt.spawn(async move {
+            cloned.sleep(Duration::from_secs(60)).await;
+            flag.store(true, Ordering::Relaxed);
+        });
+
+        rt.advance(Duration::from_secs(59));
+        assert!(!done.load(Ordering::Relaxed));
+        assert!(!rt.is_idle());
+
+        rt.advance(Duration::from_secs(1));
+        assert!(done.load(Ordering::Relaxed));
+        assert_eq!(rt.now() - start, Duration::from_secs(60));
+        rt.assert_idle();
+    }
+
+    #[test]
+    fn test_block_on_auto_advance() {
+        let rt = TestRuntime::new();
+        let start = rt.now();
+
+        let cloned = rt.clone();
+        let task = rt.spawn(async move {
+            cloned.sleep(Duration::from_secs(3600)).await;
+            "done"
+        });
+        assert_eq!(rt.block_on(task.join()).unwrap(), "done");
+        assert_eq!(rt.now() - start, Duration::from_secs(3600));
+    }
+
+    #[test]
+    fn test_timeout() {
+        let rt = TestRuntime::new();
+        let start = rt.now();
+
+        let res = rt.block_on(rt.timeout(Duration::from_secs(1), std::future::pending::<()>()));
+        assert!(res.is_err());
+        assert_eq!(rt.now() - start, Duration::from_secs(1));
+
+        let cloned = rt.clone();
+        let res = rt.block_on(rt.timeout(Duration::from_secs(10), async move {
+            cloned.sleep(Duration::from_secs(5)).await;
+            "done"
+        }));
+        assert_eq!(res.unwrap(), "done");
+        assert_eq!(rt.now() - start, Duration::from_secs(6));
+        rt.assert_idle();
+    }
+
+    #[test]
+    fn test_sleep_until() {
+        let rt = TestRuntime::new();
+        let start = rt.now();
+
+        rt.block_on(rt.sleep_until(start + Duration::from_secs(60)));
+        assert_eq!(rt.now() - start, Duration::from_secs(60));
+
+        // A deadline in the past completes immediately.
+        rt.block_on(rt.sleep_until(start));
+        assert_eq!(rt.now() - start, Duration::from_secs(60));
+        rt.assert_idle();
+    }
+
+    #[test]
+    fn test_interval() {
+        let rt = TestRuntime::new();
+        let start = rt.now();
+        let period = Duration::from_secs(10);
+
+        let mut interval = rt.interval(period);
+        assert_eq!(interval.period(), period);
+        assert_eq!(rt.block_on(interval.tick()), start);
+        assert_eq!(rt.block_on(interval.tick()), start + period);
+        assert_eq!(rt.now() - start, period);
+
+        // Missed ticks are skipped, and the next tick is one period after the late one.
+        rt.block_on(rt.sleep(Duration::from_secs(25)));
+        assert_eq!(rt.block_on(interval.tick()), start + 2 * period);
+        assert_eq!(
+            rt.block_on(interval.tick()),
+            start + Duration::from_secs(45)
+        );
+
+        interval.reset();
+        assert_eq!(
+            rt.block_on(interval.tick()),
+            start + Duration::from_secs(55)
+        );
+        rt.assert_idle();
+    }
+
+    #[test]
+    fn test_abort() {
+        let rt = TestRuntime::new();
+
+        let task = rt.spawn(std::future::pending::<()>());
+        rt.run_until_stalled();
+        task.abort();
+        assert!(rt.block_on(task.join()).unwrap_err().is_cancelled());
+    }
+
+    #[test]
+    fn test_spawn_local() {
+        let rt = TestRuntime::new();
+        let counter = Rc::new(Cell::new(0));
+
+        let task = rt.spawn_local({
+            let rt = rt.clone();
+            let counter = Rc::clone(&counter);
+            async move {
+                let inner = rt.spawn_local({
+                    let counter = Rc::clone(&counter);
+                    async move { counter.set(counter.get() + 1) }
+                });
+                rt.sleep(Duration::from_secs(1)).await;
+                inner.join().await.unwrap();
+                counter.set(counter.get() + 1);
+                counter
+            }
+        });
+        rt.run_until_stalled();
+        assert_eq!(counter.get(), 1);
+
+        let pending = rt.spawn_local(std::future::pending::<Rc<()>>());
+        rt.advance(Duration::from_secs(1));
+        assert_eq!(rt.block_on(task.join()).unwrap().get(), 2);
+
+        pending.abort();
+        assert!(rt.block_on(pending.join()).unwrap_err().is_cancelled());
+        rt.assert_idle();
+    }
+
+    #[test]
+    fn test_seeded_order() {
+        fn order(seed: u64) -> Vec<usize> {
+            let rt = TestRuntime::with_seed(seed);
+            let order = Arc::new(Mutex::new(Vec::new()));
+            for i in 0..8 {
+                let order = Arc::clone(&order);
+                rt.spawn(async move { lock(&order).push(i) });
+            }
+            rt.run_until_stalled();
+            lock(&order).clone()
+        }
+
+        assert_eq!(order(1), order(1));
+        assert_ne!(order(1), order(2));
+    }
+}
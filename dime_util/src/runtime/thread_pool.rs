@@ -0,0 +1,690 @@
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::pin::{Pin, pin};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, PoisonError, TryLockError, Weak};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+use std::time::{Duration, Instant};
+
+use dime_core::Error;
+use dime_core::runtime::{Runtime, Task, Timer};
+
+use super::join::{self, Join, JoinError, JoinState, lock};
+
+/// A minimal multi-threaded runtime built on [`std::thread`].
+///
+/// Asynchronous tasks are run by a fixed number of worker threads sharing a single work queue.
+/// Blocking tasks are run on up to [`MAX_BLOCKING_THREADS`](Self::MAX_BLOCKING_THREADS)
+/// additional threads, which are spawned on demand and exit after being idle for a while. Timers
+/// are driven by a separate timer thread.
+///
+/// The threads are shut down once every clone of the runtime is dropped. Pending tasks are then
+/// dropped, and joining them returns a cancellation error. Blocking tasks that already started
+/// run to completion. Note that tasks holding a clone of the runtime keep it alive.
+#[derive(Clone, Debug)]
+pub struct ThreadPoolRuntime {
+    handle: Arc<Handle>,
+}
+
+/// A handle to a task spawned by [`ThreadPoolRuntime`].
+#[derive(Debug)]
+pub struct ThreadPoolTask<T> {
+    join: Arc<JoinState<T>>,
+    cell: Option<Arc<TaskCell>>,
+}
+
+/// A future that completes at a deadline, created by [`ThreadPoolRuntime`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct ThreadPoolSleep {
+    pool: Arc<Pool>,
+    deadline: Instant,
+    registered: Option<WakerSlot>,
+}
+
+/// Shuts the pool down when every clone of the runtime is dropped.
+#[derive(Debug)]
+struct Handle {
+    pool: Arc<Pool>,
+}
+
+#[derive(Debug, Default)]
+struct Pool {
+    shutdown: AtomicBool,
+    queue: Mutex<VecDeque<Arc<TaskCell>>>,
+    queue_cond: Condvar,
+    timers: Mutex<BinaryHeap<TimerEntry>>,
+    timers_cond: Condvar,
+    tasks: Mutex<HashMap<u64, Weak<TaskCell>>>,
+    next_id: AtomicU64,
+    blocking: Mutex<BlockingPool>,
+    blocking_cond: Condvar,
+}
+
+type BoxFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+type BlockingJob = Box<dyn FnOnce() + Send>;
+
+struct TaskCell {
+    id: u64,
+    pool: Arc<Pool>,
+    future: Mutex<Option<BoxFuture>>,
+    scheduled: AtomicBool,
+    aborted: AtomicBool,
+}
+
+/// The waker of a [`ThreadPoolSleep`], shared with its timer entry so that re-polling the sleep
+/// with another waker updates the entry instead of adding one.
+type WakerSlot = Arc<Mutex<Option<Waker>>>;
+
+#[derive(Debug)]
+struct TimerEntry {
+    deadline: Instant,
+    waker: WakerSlot,
+}
+
+#[derive(Default)]
+struct BlockingPool {
+    queue: VecDeque<BlockingJob>,
+    threads: usize,
+    idle: usize,
+}
+
+impl Default for ThreadPoolRuntime {
+    /// Creates a runtime with as many worker threads as the available parallelism.
+    fn default() -> Self {
+        let threads = thread::available_parallelism().map_or(1, std::num::NonZero::get);
+        Self::new(threads)
+    }
+}
+
+impl ThreadPoolRuntime {
+    /// The maximum number of threads running blocking tasks at once. Blocking tasks spawned
+    /// while every such thread is busy wait for one to become available.
+    pub const MAX_BLOCKING_THREADS: usize = 64;
+
+    /// How long a thread running blocking tasks waits for another one before exiting.
+    const BLOCKING_KEEP_ALIVE: Duration = Duration::from_secs(10);
+
+    /// Creates a runtime with the given number of worker threads.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `threads` is zero or if the threads can not be spawned.
+    pub fn new(threads: usize) -> Self {
+        assert!(threads > 0, "`threads` must be non-zero");
+
+        let pool = Arc::new(Pool::default());
+
+        for i in 0..threads {
+            let pool = Arc::clone(&pool);
+            thread::Builder::new()
+                .name(format!("dime-worker-{i}"))
+                .spawn(move || pool.run_worker())
+                .expect("failed to spawn worker thread");
+        }
+
+        {
+            let pool = Arc::clone(&pool);
+            thread::Builder::new()
+                .name("dime-timer".to_string())
+                .spawn(move || pool.run_timer())
+                .expect("failed to spawn timer thread");
+        }
+
+        Self {
+            handle: Arc::new(Handle { pool }),
+        }
+    }
+
+    /// Runs a future to completion on the current thread, blocking until it completes.
+    ///
+    /// Tasks spawned by the future run on the worker threads of the runtime.
+    pub fn block_on<F>(&self, future: F) -> F::Output
+    where
+        F: Future,
+    {
+        struct ThreadWaker(Thread);
+
+        impl Wake for ThreadWaker {
+            fn wake(self: Arc<Self>) {
+                self.0.unpark();
+            }
+        }
+
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        let mut future = pin!(future);
+
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+            thread::park();
+        }
+    }
+}
+
+impl Runtime for ThreadPoolRuntime {
+    type Task<T>
+        = ThreadPoolTask<T>
+    where
+        T: Send + 'static;
+
+    fn spawn<F>(&self, fut: F) -> Self::Task<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let (wrapped, join) = join::wrap_future(fut);
+
+        let pool = &self.handle.pool;
+        let cell = Arc::new(TaskCell {
+            id: pool.next_id.fetch_add(1, Ordering::Relaxed),
+            pool: Arc::clone(pool),
+            future: Mutex::new(Some(Box::pin(wrapped))),
+            scheduled: AtomicBool::new(false),
+            aborted: AtomicBool::new(false),
+        });
+        lock(&pool.tasks).insert(cell.id, Arc::downgrade(&cell));
+        cell.schedule();
+
+        ThreadPoolTask {
+            join,
+            cell: Some(cell),
+        }
+    }
+
+    fn spawn_blocking<F, T>(&self, f: F) -> Self::Task<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let (wrapped, join) = join::wrap_blocking(f);
+        self.handle.pool.spawn_blocking(Box::new(wrapped));
+        ThreadPoolTask { join, cell: None }
+    }
+}
+
+impl Timer for ThreadPoolRuntime {
+    type Sleep = ThreadPoolSleep;
+
+    fn sleep_until(&self, deadline: Instant) -> Self::Sleep {
+        ThreadPoolSleep {
+            pool: Arc::clone(&self.handle.pool),
+            deadline,
+            registered: None,
+        }
+    }
+}
+
+impl<T> Task for ThreadPoolTask<T> {
+    type Output = T;
+
+    type Error = JoinError;
+
+    type Join = Join<T>;
+
+    /// Aborts the task.
+    ///
+    /// Tasks spawned with [`spawn_blocking`](Runtime::spawn_blocking) can not be aborted.
+    fn abort(&self) {
+        if let Some(cell) = &self.cell {
+            cell.aborted.store(true, Ordering::Release);
+            cell.schedule();
+        }
+    }
+
+    fn join(self) -> Self::Join {
+        Join::new(self.join)
+    }
+
+    fn join_error(error: Self::Error) -> Error {
+        error.into()
+    }
+}
+
+impl Future for ThreadPoolSleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if Instant::now() >= self.deadline {
+            return Poll::Ready(());
+        }
+
+        if let Some(slot) = &self.registered {
+            let mut waker = lock(slot);
+            if !waker
+                .as_ref()
+                .is_some_and(|waker| waker.will_wake(cx.waker()))
+            {
+                *waker = Some(cx.waker().clone());
+            }
+            return Poll::Pending;
+        }
+
+        let mut timers = lock(&self.pool.timers);
+        // Nothing drives the timers after shutdown, and the entry would only keep the waker
+        // alive.
+        if self.pool.is_shutdown() {
+            return Poll::Pending;
+        }
+        let slot = Arc::new(Mutex::new(Some(cx.waker().clone())));
+        timers.push(TimerEntry {
+            deadline: self.deadline,
+            waker: Arc::clone(&slot),
+        });
+        drop(timers);
+        self.pool.timers_cond.notify_one();
+        self.registered = Some(slot);
+
+        Poll::Pending
+    }
+}
+
+impl Drop for ThreadPoolSleep {
+    fn drop(&mut self) {
+        // Drop the waker now rather than when the entry is due.
+        if let Some(slot) = &self.registered {
+            lock(slot).take();
+        }
+    }
+}
+
+impl Drop for Handle {
+    fn drop(&mut self) {
+        let pool = &self.pool;
+        // Set the flag while holding the locks, so that no thread misses the notification
+        // between checking the flag and waiting on the condition variable, and nothing is queued
+        // after the queues are drained.
+        let queued = {
+            let mut queue = lock(&pool.queue);
+            pool.shutdown.store(true, Ordering::Release);
+            std::mem::take(&mut *queue)
+        };
+        pool.queue_cond.notify_all();
+        let timers = std::mem::take(&mut *lock(&pool.timers));
+        pool.timers_cond.notify_all();
+        let jobs = std::mem::take(&mut lock(&pool.blocking).queue);
+        pool.blocking_cond.notify_all();
+
+        // Drop everything outside of the locks, as dropping futures runs arbitrary code. Dropping
+        // the queues also breaks the reference cycles between the pool and its tasks.
+        drop((queued, timers, jobs));
+        let tasks: Vec<_> = lock(&pool.tasks)
+            .values()
+            .filter_map(Weak::upgrade)
+            .collect();
+        for task in tasks {
+            task.cancel();
+        }
+    }
+}
+
+impl Pool {
+    fn is_shutdown(&self) -> bool {
+        self.shutdown.load(Ordering::Acquire)
+    }
+
+    fn run_worker(&self) {
+        loop {
+            let task = {
+                let mut queue = lock(&self.queue);
+                loop {
+                    if self.is_shutdown() {
+                        return;
+                    }
+                    if let Some(task) = queue.pop_front() {
+                        break task;
+                    }
+                    queue = self
+                        .queue_cond
+                        .wait(queue)
+                        .unwrap_or_else(PoisonError::into_inner);
+                }
+            };
+
+            task.run();
+        }
+    }
+
+    fn run_timer(&self) {
+        let mut timers = lock(&self.timers);
+        loop {
+            if self.is_shutdown() {
+                return;
+            }
+
+            let now = Instant::now();
+            let mut due = Vec::new();
+            while timers.peek().is_some_and(|entry| entry.deadline <= now) {
+                due.extend(timers.pop().and_then(|entry| lock(&entry.waker).take()));
+            }
+
+            if !due.is_empty() {
+                drop(timers);
+                due.into_iter().for_each(Waker::wake);
+                timers = lock(&self.timers);
+                continue;
+            }
+
+            timers = match timers.peek() {
+                Some(entry) => {
+                    let timeout = entry.deadline - now;
+                    self.timers_cond
+                        .wait_timeout(timers, timeout)
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .0
+                }
+                None => self
+                    .timers_cond
+                    .wait(timers)
+                    .unwrap_or_else(PoisonError::into_inner),
+            };
+        }
+    }
+
+    fn spawn_blocking(self: &Arc<Self>, job: BlockingJob) {
+        let mut blocking = lock(&self.blocking);
+        if self.is_shutdown() {
+            // Dropping the job completes the join state with a cancellation error.
+            drop(blocking);
+            drop(job);
+            return;
+        }
+
+        blocking.queue.push_back(job);
+        // Spawn a thread unless enough idle threads can take the queued jobs.
+        let spawn = blocking.queue.len() > blocking.idle
+            && blocking.threads < ThreadPoolRuntime::MAX_BLOCKING_THREADS;
+        if spawn {
+            blocking.threads += 1;
+        }
+        drop(blocking);
+        self.blocking_cond.notify_one();
+
+        if spawn {
+            let pool = Arc::clone(self);
+            thread::Builder::new()
+                .name("dime-blocking".to_string())
+                .spawn(move || pool.run_blocking())
+                .expect("failed to spawn blocking thread");
+        }
+    }
+
+    fn run_blocking(&self) {
+        let mut blocking = lock(&self.blocking);
+        loop {
+            if let Some(job) = blocking.queue.pop_front() {
+                drop(blocking);
+                job();
+                blocking = lock(&self.blocking);
+                continue;
+            }
+            if self.is_shutdown() {
+                break;
+            }
+
+            blocking.idle += 1;
+            let (guard, timeout) = self
+                .blocking_cond
+                .wait_timeout(blocking, ThreadPoolRuntime::BLOCKING_KEEP_ALIVE)
+                .unwrap_or_else(PoisonError::into_inner);
+            blocking = guard;
+            blocking.idle -= 1;
+            if timeout.timed_out() && blocking.queue.is_empty() {
+                break;
+            }
+        }
+        blocking.threads -= 1;
+    }
+}
+
+impl TaskCell {
+    fn schedule(self: &Arc<Self>) {
+        if !self.scheduled.swap(true, Ordering::AcqRel) {
+            let mut queue = lock(&self.pool.queue);
+            if self.pool.is_shutdown() {
+                drop(queue);
+                self.cancel();
+                return;
+            }
+            queue.push_back(Arc::clone(self));
+            drop(queue);
+            self.pool.queue_cond.notify_one();
+        }
+    }
+
+    /// Drops the future, which completes the join state with a cancellation error.
+    ///
+    /// If the future is being polled, it is dropped by the worker polling it instead, which checks
+    /// for shutdown afterwards.
+    fn cancel(&self) {
+        let mut slot = match self.future.try_lock() {
+            Ok(slot) => slot,
+            Err(TryLockError::Poisoned(err)) => err.into_inner(),
+            Err(TryLockError::WouldBlock) => return,
+        };
+        let future = slot.take();
+        drop(slot);
+        drop(future);
+    }
+
+    fn run(self: Arc<Self>) {
+        // Clear the flag before polling, so that wake-ups during the poll schedule the task again.
+        self.scheduled.store(false, Ordering::Release);
+
+        let mut slot = lock(&self.future);
+        let Some(future) = slot.as_mut() else {
+            return;
+        };
+
+        if self.aborted.load(Ordering::Acquire) {
+            // Dropping the future completes the join state with a cancellation error.
+            *slot = None;
+            return;
+        }
+
+        let waker = Waker::from(Arc::clone(&self));
+        let mut cx = Context::from_waker(&waker);
+        if future.as_mut().poll(&mut cx).is_ready() || self.pool.is_shutdown() {
+            let future = slot.take();
+            drop(slot);
+            drop(future);
+        }
+    }
+}
+
+impl Drop for TaskCell {
+    fn drop(&mut self) {
+        lock(&self.pool.tasks).remove(&self.id);
+    }
+}
+
+impl Wake for TaskCell {
+    fn wake(self: Arc<Self>) {
+        self.schedule();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.schedule();
+    }
+}
+
+impl std::fmt::Debug for TaskCell {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TaskCell")
+            .field("id", &self.id)
+            .field("scheduled", &self.scheduled)
+            .field("aborted", &self.aborted)
+            .finish_non_exhaustive()
+    }
+}
+
+impl std::fmt::Debug for BlockingPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BlockingPool")
+            .field("queued", &self.queue.len())
+            .field("threads", &self.threads)
+            .field("idle", &self.idle)
+            .finish()
+    }
+}
+
+impl PartialEq for TimerEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.deadline == other.deadline
+    }
+}
+
+impl Eq for TimerEntry {}
+
+impl PartialOrd for TimerEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for TimerEntry {
+    // Reversed, so that `BinaryHeap` pops the earliest deadline first.
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        other.deadline.cmp(&self.deadline)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_spawn_and_join() {
+        let rt = ThreadPoolRuntime::new(2);
+
+        let task = rt.spawn(async { 1 + 1 });
+        assert_eq!(rt.block_on(task.join()).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_spawn_nested() {
+        let rt = ThreadPoolRuntime::new(2);
+
+        let cloned = rt.clone();
+        let task = rt.spawn(async move {
+            let inner = cloned.spawn(async { "inner" });
+            inner.join().await.unwrap()
+        });
+        assert_eq!(rt.block_on(task.join()).unwrap(), "inner");
+    }
+
+    #[test]
+    fn test_abort() {
+        let rt = ThreadPoolRuntime::new(1);
+
+        let task = rt.spawn(std::future::pending::<()>());
+        task.abort();
+        let err = rt.block_on(task.join()).unwrap_err();
+        assert!(err.is_cancelled());
+    }
+
+    #[test]
+    fn test_panic() {
+        let rt = ThreadPoolRuntime::new(1);
+
+        let task = rt.spawn(async { panic!("boom") });
+        let err = rt.block_on(task.join()).unwrap_err();
+        assert_eq!(err.panic_message(), Some("boom"));
+
+        // The worker thread survives the panic.
+        let task = rt.spawn(async { 1 });
+        assert_eq!(rt.block_on(task.join()).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_spawn_blocking() {
+        let rt = ThreadPoolRuntime::new(1);
+        let (tx, rx) = mpsc::channel();
+
+        let task = rt.spawn_blocking(move || rx.recv().unwrap());
+        tx.send(5).unwrap();
+        assert_eq!(rt.block_on(task.join()).unwrap(), 5);
+    }
+
+    #[test]
+    fn test_sleep() {
+        let rt = ThreadPoolRuntime::new(1);
+
+        let start = Instant::now();
+        let cloned = rt.clone();
+        let task = rt.spawn(async move {
+            cloned.sleep(Duration::from_millis(20)).await;
+            cloned.sleep(Duration::from_millis(10)).await;
+        });
+        rt.block_on(task.join()).unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(30));
+    }
+
+    #[test]
+    fn test_shutdown() {
+        let rt = ThreadPoolRuntime::new(1);
+        let (started_tx, started_rx) = mpsc::channel();
+        let (tx, rx) = mpsc::channel::<()>();
+
+        // Block the only worker so that the next task stays queued.
+        let running = rt.spawn(async move {
+            started_tx.send(()).unwrap();
+            rx.recv().unwrap();
+        });
+        started_rx.recv().unwrap();
+        let queued = rt.spawn(async { 1 });
+        // A parked task whose waker is only held by its own timer.
+        let parked = rt.spawn(rt.sleep(Duration::from_secs(3600)));
+
+        drop(rt);
+        tx.send(()).unwrap();
+
+        let other = ThreadPoolRuntime::new(1);
+        other.block_on(running.join()).unwrap();
+        assert!(other.block_on(queued.join()).unwrap_err().is_cancelled());
+        assert!(other.block_on(parked.join()).unwrap_err().is_cancelled());
+    }
+
+    #[test]
+    fn test_sleep_repoll() {
+        struct Noop;
+
+        impl Wake for Noop {
+            fn wake(self: Arc<Self>) {}
+        }
+
+        let rt = ThreadPoolRuntime::new(1);
+        let mut sleep = pin!(rt.sleep(Duration::from_secs(3600)));
+        for _ in 0..3 {
+            let waker = Waker::from(Arc::new(Noop));
+            let poll = sleep.as_mut().poll(&mut Context::from_waker(&waker));
+            assert!(poll.is_pending());
+        }
+        assert_eq!(lock(&rt.handle.pool.timers).len(), 1);
+    }
+
+    #[test]
+    fn test_spawn_blocking_bounded() {
+        let rt = ThreadPoolRuntime::new(1);
+
+        let tasks: Vec<_> = (0..ThreadPoolRuntime::MAX_BLOCKING_THREADS * 2)
+            .map(|i| {
+                rt.spawn_blocking(move || {
+                    thread::sleep(Duration::from_millis(10));
+                    i
+                })
+            })
+            .collect();
+        assert!(lock(&rt.handle.pool.blocking).threads <= ThreadPoolRuntime::MAX_BLOCKING_THREADS);
+
+        for (i, task) in tasks.into_iter().enumerate() {
+            assert_eq!(rt.block_on(task.join()).unwrap(), i);
+        }
+    }
+}
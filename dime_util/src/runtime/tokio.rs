@@ -1,10 +1,19 @@
-use dime_core::runtime::{Runtime, Task};
+use std::time::{Duration, Instant};
+
+use dime_core::Error;
+use dime_core::runtime::{LocalRuntime, Runtime, Task, Timer};
 
 /// A `tokio` runtime.
 #[derive(Clone, Default, Debug)]
 pub struct TokioRuntime {}
 
-/// A wrapper to task spawned by [`TokioRuntime`].
+/// A `tokio` runtime that spawns tasks on the current [`LocalSet`](tokio::task::LocalSet).
+///
+/// Spawning a task outside of a `LocalSet` context panics.
+#[derive(Clone, Default, Debug)]
+pub struct TokioLocalRuntime {}
+
+/// A wrapper to task spawned by [`TokioRuntime`] or [`TokioLocalRuntime`].
 #[derive(Debug)]
 pub struct TokioTask<T> {
     handle: tokio::task::JoinHandle<T>,
@@ -34,6 +43,63 @@ impl Runtime for TokioRuntime {
             handle: tokio::task::spawn(fut),
         }
     }
+
+    #[inline]
+    fn spawn_blocking<F, T>(&self, f: F) -> Self::Task<T>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        TokioTask {
+            handle: tokio::task::spawn_blocking(f),
+        }
+    }
+}
+
+impl TokioLocalRuntime {
+    /// Creates a runtime.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl LocalRuntime for TokioLocalRuntime {
+    type Task<T>
+        = TokioTask<T>
+    where
+        T: 'static;
+
+    #[inline]
+    fn spawn_local<F>(&self, fut: F) -> Self::Task<F::Output>
+    where
+        F: Future + 'static,
+        F::Output: 'static,
+    {
+        TokioTask {
+            handle: tokio::task::spawn_local(fut),
+        }
+    }
+}
+
+impl Timer for TokioRuntime {
+    type Sleep = tokio::time::Sleep;
+
+    #[inline]
+    fn now(&self) -> Instant {
+        // This respects paused time in `tokio`, unlike `Instant::now`.
+        tokio::time::Instant::now().into_std()
+    }
+
+    #[inline]
+    fn sleep_until(&self, deadline: Instant) -> Self::Sleep {
+        tokio::time::sleep_until(deadline.into())
+    }
+
+    #[inline]
+    fn sleep(&self, duration: Duration) -> Self::Sleep {
+        tokio::time::sleep(duration)
+    }
 }
 
 impl<T> Task for TokioTask<T> {
@@ -52,4 +118,70 @@ impl<T> Task for TokioTask<T> {
     fn join(self) -> Self::Join {
         self.handle
     }
+
+    fn join_error(error: Self::Error) -> Error {
+        match error.try_into_panic() {
+            Ok(payload) => Error::from_panic(&*payload),
+            Err(error) if error.is_cancelled() => Error::Cancelled,
+            Err(error) => Error::other(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_sleep_until() {
+        let rt = TokioRuntime::new();
+        let start = rt.now();
+
+        rt.sleep_until(start + Duration::from_secs(60)).await;
+        assert_eq!(rt.now() - start, Duration::from_secs(60));
+
+        // A deadline in the past completes immediately.
+        rt.sleep_until(start).await;
+        assert_eq!(rt.now() - start, Duration::from_secs(60));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_timeout() {
+        let rt = TokioRuntime::new();
+        let start = rt.now();
+
+        let res = rt
+            .timeout(Duration::from_secs(1), std::future::pending::<()>())
+            .await;
+        assert!(res.is_err());
+        assert_eq!(rt.now() - start, Duration::from_secs(1));
+
+        let res = rt
+            .timeout(Duration::from_secs(10), async {
+                rt.sleep(Duration::from_secs(5)).await;
+                "done"
+            })
+            .await;
+        assert_eq!(res.unwrap(), "done");
+        assert_eq!(rt.now() - start, Duration::from_secs(6));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_interval() {
+        let rt = TokioRuntime::new();
+        let start = rt.now();
+        let period = Duration::from_secs(10);
+
+        let mut interval = rt.interval(period);
+        assert_eq!(interval.tick().await, start);
+        assert_eq!(interval.tick().await, start + period);
+
+        // Missed ticks are skipped, and the next tick is one period after the late one.
+        rt.sleep(Duration::from_secs(25)).await;
+        assert_eq!(interval.tick().await, start + 2 * period);
+        assert_eq!(interval.tick().await, start + Duration::from_secs(45));
+
+        interval.reset();
+        assert_eq!(interval.tick().await, start + Duration::from_secs(55));
+    }
 }